@@ -2,9 +2,22 @@
 from . import confsampling
 from . import geometry
 from . import formalism
+from . import analysis
+from . import statistics
+
+from .puckepy import (
+        PuckepyError,
+        FileFormatError,
+        AtomNotFoundError,
+        RingSizeError,
+        OutOfRangeError,
+        )
 
 __all__ = []
 
 __all__.extend(confsampling.__all__)
 __all__.extend(geometry.__all__)
 __all__.extend(formalism.__all__)
+__all__.extend(analysis.__all__)
+__all__.extend(statistics.__all__)
+__all__.extend(["PuckepyError", "FileFormatError", "AtomNotFoundError", "RingSizeError", "OutOfRangeError"])
@@ -7,6 +7,14 @@ Puckepy
     - confsampling
     - formalism
     - geometry
+    - analysis
+    - statistics
+
+ Every exception raised by puckepy inherits from `PuckepyError`
+    - FileFormatError   : a file cannot be read or parsed in its expected format
+    - AtomNotFoundError : a queried atom name does not exist in the parsed molecule
+    - RingSizeError     : the amount of atoms does not match the ring system
+    - OutOfRangeError   : a value or an index lies outside its permitted range
 """
 
 # Ensures that the stubs are picked up
@@ -14,8 +22,16 @@ from puckepy import (
     geometry as geometry,
     formalism as formalism,
     confsampling as confsampling,
+    analysis as analysis,
+    statistics as statistics,
 )
 
 
+class PuckepyError(Exception): ...
+class FileFormatError(PuckepyError): ...
+class AtomNotFoundError(PuckepyError): ...
+class RingSizeError(PuckepyError): ...
+class OutOfRangeError(PuckepyError): ...
+
 #__all__ = ["confsampling", "formalism", "geometry"]
 __all__ : list[str]
@@ -0,0 +1,13 @@
+"""
+puckepy.analysis
+================
+
+Import the analysis module in order to make sense of the energies
+that come back from the sampled conformational landscape
+"""
+
+# To get to sub modules
+from . import analysis
+from .analysis import *
+
+__all__ = analysis.__all__.copy()
@@ -0,0 +1,15 @@
+from puckepy.analysis.analysis import (
+        SpherePES as SpherePES,
+        TorusPES as TorusPES,
+        SphereFES as SphereFES,
+        PlaneFES as PlaneFES,
+        Clusters as Clusters,
+        kmedoids as kmedoids,
+        dbscan as dbscan,
+        hierarchical as hierarchical,
+        Ensemble as Ensemble,
+        boltzmann as boltzmann,
+        )
+
+
+__all__: list[str]
@@ -0,0 +1,297 @@
+from puckepy import puckepy
+
+
+__all__ = ["SpherePES", "TorusPES", "SphereFES", "PlaneFES", "Clusters", "kmedoids", "dbscan", "hierarchical", "Ensemble", "boltzmann"]
+
+
+class SpherePES :
+    """ Interpolate a potential energy surface on the Cremer-Pople sphere of a six-membered ring
+
+        Fits the `energies` at scattered (`theta`, `phi`) points, in radians as SixringAxes,
+        with radial basis functions (the inverse multiquadric on the chord between two points of the unit sphere) and a constant.
+        A `smoothing` of 0. interpolates the energies exactly; a larger smoothing trades exactness for a smoother surface.
+        The `shape` of the basis functions defaults to the median distance of every point to its nearest neighbour.
+
+        The stationary points are located on a Fibonacci sphere of 2000 points and refined by Newton steps on the gradient
+        ---------------
+        self.shape : float
+
+        Raises PuckepyError if the amount of theta, phi and energies are not equal,
+            or if points coincide without a smoothing
+        Raises OutOfRangeError if the `shape` is not positive or the `smoothing` is negative
+
+        >>> axes = SixringAxes(631)
+        >>> pes = SpherePES(axes.theta, axes.phi, energies)
+        >>> for theta, phi, energy in pes.minima() :
+        >>>     print(theta, phi, energy)
+    """
+    shape : float
+
+    def __new__(cls, theta: list[float], phi: list[float], energies: list[float], shape: float | None = None, smoothing: float = 0.) :
+        return puckepy.analysis.SpherePES(theta, phi, energies, shape, smoothing)
+
+    def energy(self, theta: float, phi: float) -> float :
+        """ The interpolated energy at (theta, phi)
+        """
+        return self.energy(theta, phi)
+
+    def evaluate(self, theta: list[float], phi: list[float]) -> list[float] :
+        """ The interpolated energies at every (theta, phi)
+        """
+        return self.evaluate(theta, phi)
+
+    def gradient(self, theta: float, phi: float) -> tuple[float, float] :
+        """ The derivatives (dE/dtheta, dE/dphi) of the energy at (theta, phi), per radian
+        """
+        return self.gradient(theta, phi)
+
+    def minima(self) -> list[tuple[float, float, float]] :
+        """ The local minima of the surface, as (theta, phi, energy) from low to high energy
+        """
+        return self.minima()
+
+    def saddle_points(self) -> list[tuple[float, float, float]] :
+        """ The saddle points of the surface, as (theta, phi, energy) from low to high energy
+        """
+        return self.saddle_points()
+
+
+class TorusPES :
+    """ Fit a potential energy surface on the periodic (phi, psi) torus of a peptide
+
+        Fits the `energies` at scattered (`phi`, `psi`) points, in degrees as PeptideAxes,
+        with a two-dimensional Fourier series of every wave (m, n) with |m|, |n| <= `order`, by least squares.
+        This takes (2 * `order` + 1)^2 coefficients, so at least as many points are needed.
+
+        The stationary points are located on a grid of every 2 degrees and refined by Newton steps on the gradient.
+        They are returned in [-180, 180) if any of the points is negative, else in [0, 360)
+        ---------------
+        self.order : int
+
+        Raises PuckepyError if the amount of phi, psi and energies are not equal,
+            or if there are fewer points than coefficients
+
+        >>> axes = PeptideAxes(37)
+        >>> pes = TorusPES(axes.x, axes.y, energies, order=6)
+        >>> for phi, psi, energy in pes.saddle_points() :
+        >>>     print(phi, psi, energy)
+    """
+    order : int
+
+    def __new__(cls, phi: list[float], psi: list[float], energies: list[float], order: int = 4) :
+        return puckepy.analysis.TorusPES(phi, psi, energies, order)
+
+    def energy(self, phi: float, psi: float) -> float :
+        """ The fitted energy at (phi, psi)
+        """
+        return self.energy(phi, psi)
+
+    def evaluate(self, phi: list[float], psi: list[float]) -> list[float] :
+        """ The fitted energies at every (phi, psi)
+        """
+        return self.evaluate(phi, psi)
+
+    def gradient(self, phi: float, psi: float) -> tuple[float, float] :
+        """ The derivatives (dE/dphi, dE/dpsi) of the energy at (phi, psi), per degree
+        """
+        return self.gradient(phi, psi)
+
+    def minima(self) -> list[tuple[float, float, float]] :
+        """ The local minima of the surface, as (phi, psi, energy) from low to high energy
+        """
+        return self.minima()
+
+    def saddle_points(self) -> list[tuple[float, float, float]] :
+        """ The saddle points of the surface, as (phi, psi, energy) from low to high energy
+        """
+        return self.saddle_points()
+
+
+class SphereFES :
+    """ Construct the free energy surface on the Cremer-Pople sphere of a six-membered ring
+
+        Bins the (`theta`, `phi`) of every frame, in radians as CP6().from_atomnames(), with the equal-area partition
+        of the grid of SixringAxes(`amount`) : every bin is centered on a point of that grid.
+        Every frame counts once, or by its `weights` (e.g. from reweighting a biased simulation).
+
+        Without a `bandwidth`, the frames are histogrammed. With a `bandwidth` (radians), every frame is spread over the bins
+        by a von Mises-Fisher kernel of concentration 1 / bandwidth^2, a smooth density estimate.
+
+        The free energy is -RT ln of the density of every bin, relative to the densest bin, at the `temperature` (K)
+        in `unit` "kJ/mol" or "kcal/mol". Empty bins have an infinite free energy
+        ---------------
+        self.theta : list[float]          # the center of every bin
+        self.phi : list[float]
+        self.population : list[float]     # sums to 1
+        self.free_energy : list[float]
+
+        Raises PuckepyError if the amount of theta, phi and weights are not equal, or for an unknown `unit`
+        Raises OutOfRangeError if a frame is not finite, a weight is negative, the `amount` is 0, or the `bandwidth` or the `temperature` is not positive
+
+        >>> cp6 = [CP6().from_atomnames(pdb, ["O5", "C1", "C2", "C3", "C4", "C5"]) for pdb in trajectory]
+        >>> theta, phi = [c[2] for c in cp6], [c[1] for c in cp6] # (amplitude, phase_angle, theta)
+        >>> fes = SphereFES(np.radians(theta), np.radians(phi), amount=400, bandwidth=0.1, unit="kcal/mol")
+    """
+    theta : list[float]
+    phi : list[float]
+    population : list[float]
+    free_energy : list[float]
+
+    def __new__(cls, theta: list[float], phi: list[float], amount: int = 630, weights: list[float] | None = None,
+                bandwidth: float | None = None, temperature: float = 298.15, unit: str = "kJ/mol") :
+        return puckepy.analysis.SphereFES(theta, phi, amount, weights, bandwidth, temperature, unit)
+
+
+class PlaneFES :
+    """ Construct the free energy surface on a plane of two angles
+
+        Bins the (`x`, `y`) of every frame : the (phi, psi) of a peptide, or the (phase angle, amplitude) of AS and CP5.
+        The plane holds `bins` x `y_bins` bins over the `x_range` and `y_range` (degrees), for every x bin all y bins, as PeptideAxes.
+        The `y_bins` and `y_range` default to those of x.
+
+        A `periodic` axis wraps the frames onto its range; a non-periodic axis drops the frames outside of its range.
+        Every frame counts once, or by its `weights` (e.g. from reweighting a biased simulation).
+
+        Without a `bandwidth`, the frames are histogrammed. With a `bandwidth` (in the units of the axes), every frame is spread
+        over the bins by a Gaussian kernel that wraps around the periodic axes, a smooth density estimate.
+
+        The free energy is -RT ln of the population of every bin, relative to the most populated bin, at the `temperature` (K)
+        in `unit` "kJ/mol" or "kcal/mol". Empty bins have an infinite free energy
+        ---------------
+        self.x : list[float]              # the center of every bin
+        self.y : list[float]
+        self.population : list[float]     # sums to 1
+        self.free_energy : list[float]
+
+        Raises PuckepyError if the amount of x, y and weights are not equal, if no frame lies within the ranges, or for an unknown `unit`
+        Raises OutOfRangeError if a frame is not finite, an axis has no bins or a decreasing range, if a weight is negative,
+            or the `bandwidth` or the `temperature` is not positive
+
+        >>> ramachandran = PlaneFES(phi, psi, bins=72, bandwidth=5.)
+        >>> pseudorotation = PlaneFES(phase_angles, amplitudes, bins=36, y_bins=12, x_range=(0., 360.), y_range=(0., 60.), periodic=(True, False))
+    """
+    x : list[float]
+    y : list[float]
+    population : list[float]
+    free_energy : list[float]
+
+    def __new__(cls, x: list[float], y: list[float], bins: int = 36, y_bins: int | None = None,
+                x_range: tuple[float, float] = (-180., 180.), y_range: tuple[float, float] | None = None,
+                periodic: tuple[bool, bool] = (True, True), weights: list[float] | None = None,
+                bandwidth: float | None = None, temperature: float = 298.15, unit: str = "kJ/mol") :
+        return puckepy.analysis.PlaneFES(x, y, bins, y_bins, x_range, y_range, periodic, weights, bandwidth, temperature, unit)
+
+
+class Clusters :
+    """ The clusters of a set of puckering coordinates, as returned by kmedoids(), dbscan() and hierarchical()
+
+        Clusters are numbered from large to small. A label of -1 is noise (only by dbscan()).
+        The representative of every cluster is its medoid, the point with the smallest sum of distances to the rest of its cluster
+        ---------------
+        self.labels : list[int]            # the cluster of every point
+        self.sizes : list[int]             # of every cluster
+        self.representatives : list[int]   # the index of the medoid of every cluster
+    """
+    labels : list[int]
+    sizes : list[int]
+    representatives : list[int]
+
+
+# The `metric` of the clustering functions follows the formalism of the points, in degrees
+#     "polar"  : (amplitude, phase_angle) of CP5 and AS, as points on the pseudorotation wheel
+#     "sphere" : (amplitude, phase_angle, theta) of CP6, as points on the sphere of radius amplitude
+#     "torus"  : (phi, psi) of a peptide, on the periodic torus
+# Distances are in the units of the amplitude, or in degrees on the torus.
+# Every pair of points is compared, so the time and memory grow with the square of the amount of points
+
+def kmedoids(points: list[list[float]], k: int, metric: str, max_iterations: int = 100) -> Clusters :
+    """ Cluster the puckering coordinates around `k` medoids
+        --------------------
+        The medoids are initialised by the greedy BUILD step of PAM, and updated until every point is assigned
+        to its nearest medoid and every medoid is the medoid of its cluster. The result is deterministic.
+
+        Raises PuckepyError for an unknown `metric`, or if the points do not match it
+        Raises OutOfRangeError if `k` is not within 1 -> the amount of distinct points
+
+        >>> puckers = [CP5().from_atomnames(pdb, ["O4'", "C1'", "C2'", "C3'", "C4'"]) for pdb in crest_conformers]
+        >>> clusters = kmedoids(puckers, k=2, metric="polar")       # North and South
+        >>> north = crest_conformers[clusters.representatives[0]]
+    """
+    return puckepy.analysis.kmedoids(points, k, metric, max_iterations)
+
+def dbscan(points: list[list[float]], epsilon: float, min_points: int, metric: str) -> Clusters :
+    """ Cluster the puckering coordinates by their density (DBSCAN)
+        --------------------
+        A point with at least `min_points` points (itself included) within `epsilon` is a core point.
+        Clusters grow from the core points over every point within `epsilon`; the other points are noise (-1).
+
+        Raises PuckepyError for an unknown `metric`, or if the points do not match it
+        Raises OutOfRangeError if `epsilon` is not positive
+
+        >>> clusters = dbscan(phi_psi, epsilon=20., min_points=10, metric="torus")
+    """
+    return puckepy.analysis.dbscan(points, epsilon, min_points, metric)
+
+def hierarchical(points: list[list[float]], metric: str, n_clusters: int | None = None, threshold: float | None = None, linkage: str = "average") -> Clusters :
+    """ Cluster the puckering coordinates hierarchically
+        --------------------
+        Merges the two nearest clusters until `n_clusters` remain, or until the nearest clusters lie further apart than the `threshold`.
+        The distance between two clusters is their `linkage` : "single" (nearest points), "complete" (furthest points)
+        or "average" (mean distance between their points).
+
+        Raises PuckepyError for an unknown `metric` or `linkage`, if the points do not match the metric,
+            or unless exactly one of `n_clusters` or `threshold` is queried
+        Raises OutOfRangeError if `n_clusters` is not within 1 -> the amount of points, or the `threshold` is negative
+
+        >>> puckers = [CP6().from_atomnames(pdb, ["O5'", "C1'", "C2'", "C3'", "C4'", "C5'"]) for pdb in md_frames]
+        >>> clusters = hierarchical(puckers, metric="sphere", threshold=0.2, linkage="complete")
+    """
+    return puckepy.analysis.hierarchical(points, metric, n_clusters, threshold, linkage)
+
+
+class Ensemble :
+    """ The thermally averaged pucker of a set of conformers, as returned by boltzmann()
+
+        The phase angles are averaged as directions (the circular mean), so that 350 and 10 degrees average to 0 and not to 180.
+        For CP6, the (phase_angle, theta) of every conformer are averaged as directions on the Cremer-Pople sphere.
+        The `resultant_length` is the length of that mean direction : 1 if all conformers share their phase angle,
+        towards 0 if the phase angles spread out over the wheel (or the sphere), where the mean direction means little
+        ---------------
+        self.populations : list[float]                    # of every conformer, sums to 1
+        self.amplitude : float                            # population-weighted
+        self.phase_angle : float                          # in [0, 360)
+        self.theta : float | None                         # CP6 only
+        self.resultant_length : float
+        self.families : list[tuple[str, float]]           # (IUPAC conformer, population), from high to low
+        self.sectors : list[tuple[str, float]] | None     # (North/East/South/West, population), CP5 and AS only
+    """
+    populations : list[float]
+    amplitude : float
+    phase_angle : float
+    theta : float | None
+    resultant_length : float
+    families : list[tuple[str, float]]
+    sectors : list[tuple[str, float]] | None
+
+
+def boltzmann(puckers: list[list[float]], energies: list[float], formalism: str, temperature: float = 298.15,
+              unit: str = "kJ/mol", atomnames: list[str] | None = None) -> Ensemble :
+    """ Boltzmann-average the puckers of a set of conformers by their energies
+        --------------------
+        The `puckers` are those of the `formalism`, in degrees :
+            "CP5" and "AS" : (amplitude, phase_angle), as CP5().from_atomnames() and AS().from_atomnames()
+            "CP6"          : (amplitude, phase_angle, theta), as CP6().from_atomnames()
+
+        Every conformer has a population of exp(-(E - E_min) / RT), normalised to 1, at the `temperature` (K)
+        with the `energies` in `unit` "kJ/mol" or "kcal/mol". Only the differences between the energies matter.
+        The populations are summed by the IUPAC conformer of every pucker, named after the `atomnames` as Conformer
+
+        Raises PuckepyError for an unknown `formalism` or `unit`, or if the amount of puckers and energies are not equal
+        Raises RingSizeError if a pucker does not have the amount of values of the formalism
+        Raises OutOfRangeError if an energy or a pucker is not finite, or the `temperature` is not positive
+
+        >>> puckers = [CP5().from_atomnames(pdb, ["O4'", "C1'", "C2'", "C3'", "C4'"]) for pdb in crest_conformers]
+        >>> ensemble = boltzmann(puckers, energies, "CP5", unit="kcal/mol")
+        >>> print(ensemble.phase_angle, dict(ensemble.sectors)["North"])
+    """
+    return puckepy.analysis.boltzmann(puckers, energies, formalism, temperature, unit, atomnames)
@@ -0,0 +1,65 @@
+__all__: list[str]
+
+
+class SpherePES :
+    shape: float
+
+    def __new__(cls, theta: list[float], phi: list[float], energies: list[float], shape: float | None = None, smoothing: float = 0.) -> SpherePES : ...
+    def energy(self, theta: float, phi: float) -> float : ...
+    def evaluate(self, theta: list[float], phi: list[float]) -> list[float] : ...
+    def gradient(self, theta: float, phi: float) -> tuple[float, float] : ...
+    def minima(self) -> list[tuple[float, float, float]] : ...
+    def saddle_points(self) -> list[tuple[float, float, float]] : ...
+
+class TorusPES :
+    order: int
+
+    def __new__(cls, phi: list[float], psi: list[float], energies: list[float], order: int = 4) -> TorusPES : ...
+    def energy(self, phi: float, psi: float) -> float : ...
+    def evaluate(self, phi: list[float], psi: list[float]) -> list[float] : ...
+    def gradient(self, phi: float, psi: float) -> tuple[float, float] : ...
+    def minima(self) -> list[tuple[float, float, float]] : ...
+    def saddle_points(self) -> list[tuple[float, float, float]] : ...
+
+class SphereFES :
+    theta: list[float]
+    phi: list[float]
+    population: list[float]
+    free_energy: list[float]
+
+    def __new__(cls, theta: list[float], phi: list[float], amount: int = 630, weights: list[float] | None = None,
+                bandwidth: float | None = None, temperature: float = 298.15, unit: str = "kJ/mol") -> SphereFES : ...
+
+class PlaneFES :
+    x: list[float]
+    y: list[float]
+    population: list[float]
+    free_energy: list[float]
+
+    def __new__(cls, x: list[float], y: list[float], bins: int = 36, y_bins: int | None = None,
+                x_range: tuple[float, float] = (-180., 180.), y_range: tuple[float, float] | None = None,
+                periodic: tuple[bool, bool] = (True, True), weights: list[float] | None = None,
+                bandwidth: float | None = None, temperature: float = 298.15, unit: str = "kJ/mol") -> PlaneFES : ...
+
+class Clusters :
+    labels: list[int]
+    sizes: list[int]
+    representatives: list[int]
+
+def kmedoids(points: list[list[float]], k: int, metric: str, max_iterations: int = 100) -> Clusters : ...
+
+def dbscan(points: list[list[float]], epsilon: float, min_points: int, metric: str) -> Clusters : ...
+
+def hierarchical(points: list[list[float]], metric: str, n_clusters: int | None = None, threshold: float | None = None, linkage: str = "average") -> Clusters : ...
+
+class Ensemble :
+    populations: list[float]
+    amplitude: float
+    phase_angle: float
+    theta: float | None
+    resultant_length: float
+    families: list[tuple[str, float]]
+    sectors: list[tuple[str, float]] | None
+
+def boltzmann(puckers: list[list[float]], energies: list[float], formalism: str, temperature: float = 298.15,
+              unit: str = "kJ/mol", atomnames: list[str] | None = None) -> Ensemble : ...
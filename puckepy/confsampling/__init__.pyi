@@ -1,10 +1,16 @@
 from puckepy.confsampling.confsampling import (
+        Fourring as Fourring,
         Fivering as Fivering,
+        FiveringPolar as FiveringPolar,
         Sixring as Sixring,
+        Sevenring as Sevenring,
         Peptide as Peptide,
         FiveringAxes as FiveringAxes,
         SixringAxes as SixringAxes,
+        SevenringAxes as SevenringAxes,
         PeptideAxes as PeptideAxes,
+        Ramachandran as Ramachandran,
+        Refinement as Refinement,
         )
 
 
@@ -1,7 +1,41 @@
 from puckepy import puckepy
 
+from typing import TypeAlias
+Coordinates3D: TypeAlias = tuple[float, float, float]
 
-__all__ = ["Fivering","Sixring", "Peptide", "FiveringAxes","SixringAxes", "PeptideAxes"]
+
+__all__ = ["Fourring", "Fivering", "FiveringPolar", "Sixring", "Sevenring", "Peptide", "FiveringAxes","SixringAxes", "SevenringAxes", "PeptideAxes", "Ramachandran", "Refinement"]
+
+
+class Fourring :
+
+    """ Construct a set of torsions for scanning fourring space. 
+
+        A four-membered ring has a single puckering coordinate, the signed Cremer-Pople amplitude.
+        The `interval` parameter uses linear_space() function to calculate the amplitudes,
+        over [-`max_amplitude`, `max_amplitude`, `interval`] (Angstrom), passing through the planar ring.
+
+        The ring geometry takes the same `bondlengths` or `elements` as CP4().invert()
+        ---------------
+        self.amplitude : list[float]
+        self.nu1 : list[float] # 1-2-3-4, [0, 360). The other torsions only alternate in sign
+        self.coordinates : list[list[Coordinates3D]]
+
+        Raises OutOfRangeError if `max_amplitude` is not within (0, 1]
+
+        >>> fourring = Fourring(21) # Every 0.03 Angstrom
+        >>> for amplitude, nu1 in zip(fourring.amplitude, fourring.nu1)
+        >>>     print(amplitude, nu1)
+
+        >>> oxetane = Fourring(21, max_amplitude=0.2, elements=["O", "C", "C", "C"])
+    """
+    amplitude : list[float]
+    nu1 : list[float]
+    coordinates : list[list[Coordinates3D]]
+
+    def __new__(cls, interval: int, max_amplitude: float = 0.3, bondlengths: list[float] | None = None, elements: list[str] | None = None) :
+
+        return puckepy.confsampling.Fourring(interval, max_amplitude, bondlengths, elements)
 
 
 class Fivering :
@@ -14,19 +48,74 @@ class Fivering :
 
         The extent of the range is : [-60, 60, `interval`]
         ---------------
+        sampling : "grid" (default), or a sequence that returns exactly `amount` points :
+                   "fibonacci", "sobol", "halton" (low-discrepancy) or "random" (uniform, reproducible by its `seed`)
+        amount : the amount of points of a sequence, defaults to `interval * interval`
+        ---------------
         self.nu1 : list[float]
         self.nu3 : list[float]
 
+        Only the constraint torsions are returned, the ring itself is left to the optimisation.
+        To keep the coordinates of the sampled conformers, sample with FiveringPolar instead
+
+        Raises PuckepyError for an unknown sampling, or an `amount` on the grid
+
         >>> fivering = Fivering(21) # Every 6 degrees
         >>> for nu1, nu3 in zip(fivering.nu1, fivering.nu3)
         >>>     print(nu1, nu3)
+
+        >>> fivering = Fivering(21, sampling="sobol", amount=400)
     """
     nu1 : list[float]
     nu3 : list[float]
 
-    def __new__(cls, interval: int) :
+    def __new__(cls, interval: int, sampling: str = "grid", amount: int | None = None, seed: int = 0) :
+
+        return puckepy.confsampling.Fivering(interval, sampling, amount, seed)
+
+    @staticmethod
+    def from_axes(axes: "FiveringAxes") -> "Fivering" :
+        """ Get the constraint torsions of the points of a FiveringAxes, e.g. those proposed by Refinement().refine()
+
+            >>> fivering = Fivering.from_axes(refinement.refine())
+        """
+        return puckepy.confsampling.Fivering.from_axes(axes)
+
+
 
-        return puckepy.confsampling.Fivering(interval)
+class FiveringPolar :
+
+    """ Construct a set of conformers for sampling fivering space, on the Cremer-Pople (amplitude, phase_angle).
+
+        Every amplitude shell is sampled at `phase_interval` evenly spaced phase angles [0, 360).
+        Unlike the square Zx/Zy grid of Fivering(), no points fall outside of the physical amplitudes.
+        The planar shell (amplitude == 0.) is sampled once.
+
+        The ring geometry takes the same `bondlengths`, `angles` or `elements` as CP5().invert()
+        ---------------
+        self.amplitude : list[float]
+        self.phase_angle : list[float]
+        self.nu1 : list[float] # O4'-C1'-C2'-C3', [0, 360)
+        self.nu3 : list[float] # C2'-C3'-C4'-O4', [0, 360)
+        self.coordinates : list[list[Coordinates3D]]
+
+        Raises OutOfRangeError if an amplitude is negative or larger than 1, or if `phase_interval` is 0
+
+        >>> fivering = FiveringPolar(amplitudes=[0., 0.1, 0.2, 0.3, 0.4, 0.5], phase_interval=36) # Every 10 degrees
+        >>> for nu1, nu3 in zip(fivering.nu1, fivering.nu3)
+        >>>     print(nu1, nu3)
+        >>> write_models_to_pdb("fivering_polar.pdb", fivering.coordinates, "FIV")
+    """
+    amplitude : list[float]
+    phase_angle : list[float]
+    nu1 : list[float]
+    nu3 : list[float]
+    coordinates : list[list[Coordinates3D]]
+
+    def __new__(cls, amplitudes: list[float], phase_interval: int, bondlengths: list[float] | None = None, 
+                angles: list[float | None] | None = None, elements: list[str] | None = None) :
+
+        return puckepy.confsampling.FiveringPolar(amplitudes, phase_interval, bondlengths, angles, elements)
 
     
 class Peptide :
@@ -38,18 +127,39 @@ class Peptide :
 
         The extent of the range is : [0, 360, `interval`]
         ---------------
+        psi_interval : resolution of the psi axis, defaults to `interval`
+        phi_range, psi_range : (start, stop) of the axis, in degrees
+        periodic : exclude the stop of the range, so that 0 and 360 are not both sampled
+        convention : None (as spanned by the range), "positive" [0, 360) or "signed" [-180, 180)
+                     The range defaults to [-180, 180] for "signed", else to [0, 360]
+                     A range of a full turn wraps its stop onto its start, so its axis is always periodic
+        sampling : "grid" (default), or a sequence that returns exactly `amount` points :
+                   "fibonacci", "sobol", "halton" (low-discrepancy) or "random" (uniform, reproducible by its `seed`)
+        amount : the amount of points of a sequence, defaults to `interval * psi_interval`
+        ---------------
         self.phi : list[float]
         self.psi : list[float]
 
+        A pair of backbone dihedrals is not a ring, so there are no coordinates to keep
+
+        Raises OutOfRangeError if a range is not increasing, or spans more than 360 degrees
+        Raises PuckepyError for an unknown sampling, or an `amount` on the grid
+
         >>> peptide = Peptide(37) # Every 10 degrees
         >>> for phi, psi in zip(peptide.phi, peptide.psi)
         >>>     print(phi, psi)
+
+        >>> peptide = Peptide(36, periodic=True, convention="signed") # -180, -170, ..., 170
+        >>> peptide = Peptide(19, psi_interval=36, phi_range=(-180., 0.), convention="signed")
+        >>> peptide = Peptide(37, sampling="random", amount=1000, seed=42)
     """
     phi : list[float]
     psi : list[float]
 
-    def __new__(cls, interval: int) : 
-        return puckepy.confsampling.Peptide(interval)
+    def __new__(cls, interval: int, psi_interval: int | None = None, phi_range: tuple[float, float] | None = None, 
+                psi_range: tuple[float, float] | None = None, periodic: bool = False, convention: str | None = None,
+                sampling: str = "grid", amount: int | None = None, seed: int = 0) : 
+        return puckepy.confsampling.Peptide(interval, psi_interval, phi_range, psi_range, periodic, convention, sampling, amount, seed)
 
 class Sixring :
     """ Construct a set of torsions for sampling sixring space 
@@ -58,20 +168,78 @@ class Sixring :
         Cremer-Pople globe with points and approximate the `amount` to an evenly
         distributed set of points.
         ---------------
+        sampling : "grid" (default), or a sequence that returns exactly `amount` points :
+                   "fibonacci", "sobol", "halton" (low-discrepancy) or "random" (uniform, reproducible by its `seed`)
+                   "fibonacci" covers the globe by the Fibonacci sphere
+        ---------------
         self.alpha1 : list[float]
         self.alpha2 : list[float]
         self.alpha3 : list[float]
+        self.coordinates : list[list[Coordinates3D]] # only if `keep_coordinates`, else empty
 
         >>> sixring = Sixring(631) # Generate 630 points
         >>> for a1, a2, a3 in zip(sixring.alpha1,sixring.alpha2, sixring.alpha3)
         >>>     print(a1, a2, a3)
+
+        Keep the Cartesian coordinates of the conformers, to write them out afterwards
+        >>> sixring = Sixring(631, keep_coordinates=True)
+        >>> write_models_to_pdb("sixring_grid.pdb", sixring.coordinates, "SIX")
+
+        Exactly 630 points
+        >>> sixring = Sixring(630, sampling="fibonacci")
     """
     alpha1 : list[float]
     alpha2 : list[float]
     alpha3 : list[float]
+    coordinates : list[list[Coordinates3D]]
+
+    def __new__(cls, amount: int, keep_coordinates: bool = False, sampling: str = "grid", seed: int = 0) :
+        return puckepy.confsampling.Sixring(amount, keep_coordinates, sampling, seed)
+
+    @staticmethod
+    def from_axes(axes: "SixringAxes", keep_coordinates: bool = False) -> "Sixring" :
+        """ Get the constraint torsions of the points of a SixringAxes, e.g. those proposed by Refinement().refine()
 
-    def __new__(cls, amount: int) :
-        return puckepy.confsampling.Sixring(amount)
+            >>> sixring = Sixring.from_axes(refinement.refine())
+        """
+        return puckepy.confsampling.Sixring.from_axes(axes, keep_coordinates)
+
+class Sevenring :
+    """ Construct a set of torsions for sampling sevenring space 
+
+        The conformational space of a seven-membered ring is 4D : (q2, phi2, q3, phi3).
+        The amplitudes q2 and q3 use linear_space() over [0, `max_amplitude`, `amplitude_interval`],
+        and only pairs with a total amplitude sqrt(q2² + q3²) <= `max_amplitude` are kept.
+        The phase angles phi2 and phi3 are sampled at `phase_interval` evenly spaced angles [0, 360).
+        A phase angle is sampled once where its amplitude is 0.
+
+        The conformers are built with tetrahedral bondlengths and angles (1.54 Angstrom, 109.47 degrees).
+        Conformers that cannot close with this geometry are left out.
+        ---------------
+        self.nu1 : list[float] # 1-2-3-4, [0, 360)
+        self.nu2 : list[float] # 2-3-4-5, [0, 360)
+        self.nu3 : list[float] # 3-4-5-6, [0, 360)
+        self.nu4 : list[float] # 4-5-6-7, [0, 360)
+        self.coordinates : list[list[Coordinates3D]] # only if `keep_coordinates`, else empty
+
+        Raises OutOfRangeError if `max_amplitude` is not a positive number
+
+        >>> sevenring = Sevenring(6, 12) # q2, q3 every 0.2 Angstrom, phi2, phi3 every 30 degrees
+        >>> for nu1, nu2, nu3, nu4 in zip(sevenring.nu1, sevenring.nu2, sevenring.nu3, sevenring.nu4)
+        >>>     print(nu1, nu2, nu3, nu4)
+
+        Keep the Cartesian coordinates of the conformers, to write them out afterwards
+        >>> sevenring = Sevenring(6, 12, keep_coordinates=True)
+        >>> write_models_to_pdb("sevenring_grid.pdb", sevenring.coordinates, "SEV")
+    """
+    nu1 : list[float]
+    nu2 : list[float]
+    nu3 : list[float]
+    nu4 : list[float]
+    coordinates : list[list[Coordinates3D]]
+
+    def __new__(cls, amplitude_interval: int, phase_interval: int, max_amplitude: float = 1.0, keep_coordinates: bool = False) :
+        return puckepy.confsampling.Sevenring(amplitude_interval, phase_interval, max_amplitude, keep_coordinates)
 
 class FiveringAxes :
 
@@ -86,6 +254,8 @@ class FiveringAxes :
         self.zx : list[float]
         self.zy : list[float]
 
+        Takes the same `sampling`, `amount` and `seed` as Fivering()
+
         >>> fivering_axes = FiveringAxes(21) # Every 6 degrees
         >>> for zx, zy in zip(fivering_axes.zx, fivering_axes.zy)
         >>>     print(zx, zy)
@@ -93,9 +263,9 @@ class FiveringAxes :
     zx : list[float]
     zy : list[float]
 
-    def __new__(cls, interval: int) :
+    def __new__(cls, interval: int, sampling: str = "grid", amount: int | None = None, seed: int = 0) :
 
-        return puckepy.confsampling.FiveringAxes(interval)
+        return puckepy.confsampling.FiveringAxes(interval, sampling, amount, seed)
 
     
 class PeptideAxes :
@@ -106,6 +276,7 @@ class PeptideAxes :
         `interval * interval` of pairs of restraints.
 
         The extent of the range is : [0, 360, `interval`]
+        Takes the same `psi_interval`, `phi_range`, `psi_range`, `periodic`, `convention`, `sampling`, `amount` and `seed` as Peptide()
         ---------------
         self.x : list[float]
         self.y : list[float]
@@ -117,8 +288,10 @@ class PeptideAxes :
     x : list[float]
     y : list[float]
 
-    def __new__(cls, interval: int) : 
-        return puckepy.confsampling.PeptideAxes(interval)
+    def __new__(cls, interval: int, psi_interval: int | None = None, phi_range: tuple[float, float] | None = None, 
+                psi_range: tuple[float, float] | None = None, periodic: bool = False, convention: str | None = None,
+                sampling: str = "grid", amount: int | None = None, seed: int = 0) : 
+        return puckepy.confsampling.PeptideAxes(interval, psi_interval, phi_range, psi_range, periodic, convention, sampling, amount, seed)
 
 class SixringAxes :
     """ Construct a set of axes for mapping sixring space 
@@ -131,6 +304,8 @@ class SixringAxes :
         self.theta : list[float]
         self.phi : list[float]
 
+        Takes the same `sampling` and `seed` as Sixring()
+
         >>> sixring_axes = SixringAxes(631) # Generate 630 points
         >>> for theta, phi in zip(sixring_axes.theta, sixring_axes.phi)
         >>>     print(sixring_axes.rho, theta, phi)
@@ -139,6 +314,150 @@ class SixringAxes :
     theta : list[float]
     phi : list[float]
 
-    def __new__(cls, amount: int) :
-        return puckepy.confsampling.SixringAxes(amount)
+    def __new__(cls, amount: int, sampling: str = "grid", seed: int = 0) :
+        return puckepy.confsampling.SixringAxes(amount, sampling, seed)
+
+class SevenringAxes :
+    """ Construct a set of axes for mapping sevenring space 
+
+        Takes the same parameters as Sevenring(), and returns the Cremer-Pople coordinates
+        of its conformers, in the same order
+        ---------------
+        self.q2 : list[float]
+        self.phi2 : list[float]
+        self.q3 : list[float]
+        self.phi3 : list[float]
+
+        >>> sevenring_axes = SevenringAxes(6, 12)
+        >>> for q2, phi2, q3, phi3 in zip(sevenring_axes.q2, sevenring_axes.phi2, sevenring_axes.q3, sevenring_axes.phi3)
+        >>>     print(q2, phi2, q3, phi3)
+    """
+    q2 : list[float]
+    phi2 : list[float]
+    q3 : list[float]
+    phi3 : list[float]
+
+    def __new__(cls, amplitude_interval: int, phase_interval: int, max_amplitude: float = 1.0) :
+        return puckepy.confsampling.SevenringAxes(amplitude_interval, phase_interval, max_amplitude)
+
+
+class Ramachandran :
+    """ Classify (phi, psi) dihedrals into the regions of the Ramachandran plot
+
+        Regions : "alpha", "beta", "ppii" (polyproline II), "alpha_L" (left-handed helix)
+        Points outside of every region are "disallowed".
+
+        One set of regions is kept per residue type : "general", "glycine", "proline", "pre-proline".
+        The default boundaries are coarse polygons, to be refined by `set_region()` and `set_density()`.
+        Dihedrals are wrapped onto [-180, 180), so both the measured [-180, 180] and the Peptide() [0, 360] 
+        ranges are accepted.
+        ---------------
+        This class does not have public attributes
 
+        >>> rama = Ramachandran()
+        >>> rama.classify(-60., -45.)
+        >>> "alpha"
+    """
+
+    def __new__(cls) :
+        return puckepy.confsampling.Ramachandran()
+
+    def set_region(self, residue_type: str, region: str, polygons: list[list[tuple[float, float]]]) -> None :
+        """ Replace the boundaries of a region, or add a new region. An empty list of polygons removes the region.
+
+            A region consists of one or more polygons, of (phi, psi) vertices in [-180, 180].
+            Regions are tested in order, new regions are appended.
+
+            Raises PuckepyError for an unknown residue type, or a polygon of less than 3 vertices
+
+            >>> rama.set_region("general", "alpha", [[(-160., -70.), (-45., -70.), (-45., 10.), (-160., 10.)]])
+        """
+        return self.set_region(residue_type, region, polygons)
+
+    def set_density(self, residue_type: str, density: list[list[float]], threshold: float) -> None :
+        """ Disallow every point where the density lies below the threshold, e.g. for outlier detection.
+
+            The density is a grid of [-180, 180) x [-180, 180), with the rows running over phi 
+            and the columns over psi (e.g. a histogram of high-resolution structures).
+            Points that pass the density, but lie outside of every region, are labelled "allowed".
+
+            >>> rama.set_density("general", histogram, threshold=0.0005)
+        """
+        return self.set_density(residue_type, density, threshold)
+
+    def classify(self, phi: float, psi: float, residue_type: str = "general") -> str :
+        """ Classify a single (phi, psi) pair
+
+            >>> Ramachandran().classify(60., 45., "glycine")
+            >>> "alpha_L"
+        """
+        return self.classify(phi, psi, residue_type)
+
+    def classify_peptide(self, peptide: Peptide, residue_type: str = "general") -> list[str] :
+        """ Label every point of a Peptide() grid, to skip the disallowed points before a scan
+
+            >>> peptide = Peptide(37)
+            >>> labels = Ramachandran().classify_peptide(peptide)
+            >>> allowed = [(phi, psi) for phi, psi, label in zip(peptide.phi, peptide.psi, labels) if label != "disallowed"]
+        """
+        return self.classify_peptide(peptide, residue_type)
+
+    def classify_protein(self, protein: puckepy.formalism.Protein) -> list[str | None] :
+        """ Label every residue of a Protein(), by the regions of its residue type.
+            GLY is classified as "glycine", PRO as "proline", a residue preceding a PRO as "pre-proline".
+
+            Residues without a phi or psi (termini, chain breaks) are None
+
+            >>> protein = Protein(Pdb("protein.pdb").parse())
+            >>> labels = Ramachandran().classify_protein(protein)
+            >>> outliers = [n for n, label in zip(protein.residuenumbers, labels) if label == "disallowed"]
+        """
+        return self.classify_protein(protein)
+
+
+class Refinement :
+    """ Refine a conformational grid around its low-energy regions
+
+        Starts from the Axes of a grid (SixringAxes, FiveringAxes or PeptideAxes) and the energy of every point.
+        Every refine() halves the spacing of the points, and proposes new points around every point that lies within 
+        `threshold` of the lowest energy : six around it on the Cremer-Pople sphere, eight on the (zx, zy) or (phi, psi) plane.
+        The refinement has converged once the spacing reaches the `resolution`, or no new points are proposed.
+
+        The (phi, psi) plane is periodic; both planes are bounded by the extent of their initial grid.
+        The spacing and the `resolution` are in degrees; on the sphere, as the arc between two points.
+        The `threshold` is in the units of the energies, relative to the lowest energy
+        ---------------
+        self.energies : list[float]   # of every point so far
+        self.spacing : float          # the current spacing of the points
+        self.converged : bool
+        self.axes : SixringAxes | FiveringAxes | PeptideAxes   # every point so far
+
+        Raises PuckepyError if the amount of energies does not match the amount of points
+
+        >>> axes = SixringAxes(600)
+        >>> refinement = Refinement(axes, energies, threshold=5., resolution=2.)
+        >>> while not refinement.converged:
+        >>>     new_axes = refinement.refine()
+        >>>     sixring = Sixring.from_axes(new_axes)
+        >>>     refinement.add(run_constrained_optimisations(sixring))
+    """
+    energies : list[float]
+    spacing : float
+    converged : bool
+    axes : SixringAxes | FiveringAxes | PeptideAxes
+
+    def __new__(cls, axes: SixringAxes | FiveringAxes | PeptideAxes, energies: list[float], threshold: float, resolution: float) :
+        return puckepy.confsampling.Refinement(axes, energies, threshold, resolution)
+
+    def refine(self) -> SixringAxes | FiveringAxes | PeptideAxes :
+        """ Propose the points of the next refinement, as the Axes class the Refinement was made with
+            Returns empty Axes once converged
+
+            Raises PuckepyError if the energies of the previous refine() have not been added yet
+        """
+        return self.refine()
+
+    def add(self, energies: list[float]) -> None :
+        """ Add the energies of the points of the last refine(), in the same order
+        """
+        return self.add(energies)
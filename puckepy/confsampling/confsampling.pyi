@@ -1,42 +1,103 @@
 
+from typing import TypeAlias
+Coordinates3D: TypeAlias = tuple[float, float, float]
+
+from puckepy.formalism import Protein
+
 __all__: list[str]
 
 
+class Fourring :
+    amplitude: list[float]
+    nu1: list[float]
+    coordinates: list[list[Coordinates3D]]
+
+    def __new__(cls, interval: int, max_amplitude: float = 0.3, bondlengths: list[float] | None = None, elements: list[str] | None = None) -> Fourring : ...
+
 class Fivering :
     nu1: list[float]
     nu3: list[float]
 
-    def __new__(cls, interval: int) -> Fivering : ...
+    def __new__(cls, interval: int, sampling: str = "grid", amount: int | None = None, seed: int = 0) -> Fivering : ...
+    @staticmethod
+    def from_axes(axes: FiveringAxes) -> Fivering : ...
+
+class FiveringPolar :
+    amplitude: list[float]
+    phase_angle: list[float]
+    nu1: list[float]
+    nu3: list[float]
+    coordinates: list[list[Coordinates3D]]
+
+    def __new__(cls, amplitudes: list[float], phase_interval: int, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> FiveringPolar : ...
 
 class Peptide :
     phi: list[float]
     psi: list[float]
 
-    def __new__(cls, interval: int) -> Peptide : ...
+    def __new__(cls, interval: int, psi_interval: int | None = None, phi_range: tuple[float, float] | None = None, psi_range: tuple[float, float] | None = None, periodic: bool = False, convention: str | None = None, sampling: str = "grid", amount: int | None = None, seed: int = 0) -> Peptide : ...
 
 class Sixring :
     alpha1: list[float]
     alpha2: list[float]
     alpha3: list[float]
+    coordinates: list[list[Coordinates3D]]
 
-    def __new__(cls, amount: int) -> Sixring : ...
+    def __new__(cls, amount: int, keep_coordinates: bool = False, sampling: str = "grid", seed: int = 0) -> Sixring : ...
+    @staticmethod
+    def from_axes(axes: SixringAxes, keep_coordinates: bool = False) -> Sixring : ...
+
+class Sevenring :
+    nu1: list[float]
+    nu2: list[float]
+    nu3: list[float]
+    nu4: list[float]
+    coordinates: list[list[Coordinates3D]]
+
+    def __new__(cls, amplitude_interval: int, phase_interval: int, max_amplitude: float = 1.0, keep_coordinates: bool = False) -> Sevenring : ...
 
 class FiveringAxes :
     zx: list[float]
     zy: list[float]
 
-    def __new__(cls, interval: int) -> FiveringAxes : ...
+    def __new__(cls, interval: int, sampling: str = "grid", amount: int | None = None, seed: int = 0) -> FiveringAxes : ...
 
 class PeptideAxes :
     x: list[float]
     y: list[float]
 
-    def __new__(cls, interval: int) -> PeptideAxes : ...
+    def __new__(cls, interval: int, psi_interval: int | None = None, phi_range: tuple[float, float] | None = None, psi_range: tuple[float, float] | None = None, periodic: bool = False, convention: str | None = None, sampling: str = "grid", amount: int | None = None, seed: int = 0) -> PeptideAxes : ...
 
 class SixringAxes :
     rho: int
     theta: list[float]
     phi: list[float]
 
-    def __new__(cls, amount: int) -> SixringAxes : ...
+    def __new__(cls, amount: int, sampling: str = "grid", seed: int = 0) -> SixringAxes : ...
+
+class SevenringAxes :
+    q2: list[float]
+    phi2: list[float]
+    q3: list[float]
+    phi3: list[float]
+
+    def __new__(cls, amplitude_interval: int, phase_interval: int, max_amplitude: float = 1.0) -> SevenringAxes : ...
+
+class Ramachandran :
+
+    def __new__(cls) -> Ramachandran : ...
+    def set_region(self, residue_type: str, region: str, polygons: list[list[tuple[float, float]]]) -> None : ...
+    def set_density(self, residue_type: str, density: list[list[float]], threshold: float) -> None : ...
+    def classify(self, phi: float, psi: float, residue_type: str = "general") -> str : ...
+    def classify_peptide(self, peptide: Peptide, residue_type: str = "general") -> list[str] : ...
+    def classify_protein(self, protein: Protein) -> list[str | None] : ...
+
+class Refinement :
+    energies: list[float]
+    spacing: float
+    converged: bool
+    axes: SixringAxes | FiveringAxes | PeptideAxes
 
+    def __new__(cls, axes: SixringAxes | FiveringAxes | PeptideAxes, energies: list[float], threshold: float, resolution: float) -> Refinement : ...
+    def refine(self) -> SixringAxes | FiveringAxes | PeptideAxes : ...
+    def add(self, energies: list[float]) -> None : ...
@@ -1,12 +1,22 @@
 from puckepy.formalism.formalism import (
         Pdb as Pdb,
+        Mmcif as Mmcif,
         Xyz as Xyz,
+        CP4 as CP4,
         CP5 as CP5,
         CP6 as CP6,
+        CPN as CPN,
         AS as AS,
         SP as SP,
+        NucleicAcid as NucleicAcid,
+        Protein as Protein,
         write_to_pdb as write_to_pdb,
         write_to_xyz as write_to_xyz,
+        write_models_to_pdb as write_models_to_pdb,
+        write_frames_to_xyz as write_frames_to_xyz,
+        write_pdb as write_pdb,
+        infer_bonds as infer_bonds,
+        find_rings as find_rings,
         )
 
 __all__: list[str]
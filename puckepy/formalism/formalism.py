@@ -3,12 +3,24 @@ from puckepy import puckepy
 from typing import TypeAlias
 Coordinates3D: TypeAlias = tuple[float, float, float]
 
-__all__ = ["Pdb", "Xyz", "CP5", "CP6","AS", "SP"]   # Classes
-__all__.extend(["write_to_pdb", "write_to_xyz"])    # Function
+from numpy.typing import NDArray
+import numpy as np
+
+__all__ = ["Pdb", "Mmcif", "Xyz", "CP4", "CP5", "CP6", "CPN", "AS", "SP", "NucleicAcid", "Protein"]   # Classes
+__all__.extend(["write_to_pdb", "write_to_xyz", "write_models_to_pdb", "write_frames_to_xyz", "write_pdb", "infer_bonds", "find_rings"])    # Function
 
 class Pdb:
 
-    atom_names: list[str]
+    filename: str
+    data: str
+    hetatm: list[bool]
+    atomnames: list[str]
+    elements: list[str]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    chains: list[str]
+    occupancies: list[float]
+    bfactors: list[float]
     coordinates: list[list[float]] 
 
     def __new__(cls, filename: str) :
@@ -18,8 +30,18 @@ class Pdb:
             ATOM      2  C6'  A     10      24.803  51.735  41.199  1.00  0.00           C  
             ATOM      5  C5'  A     10      25.097  52.567  42.397  1.00  0.00           C  
             ---------------
-            self.atom_names : list[float]
+            self.filename : str
+            self.hetatm : list[bool] # True for HETATM records
+            self.atomnames : list[str]
+            self.elements : list[str] # element column, else guessed from the atomname
+            self.residuenames : list[str]
+            self.residuenumbers : list[int]
+            self.chains : list[str]
+            self.occupancies : list[float] # defaults to 1.00 when absent
+            self.bfactors : list[float] # defaults to 0.00 when absent
             self.coordinates : list[list[float]]
+            ---------------
+            Raises FileFormatError if the file is not a `.pdb` or is empty
             
             >>> pdb = Pdb(filename)
         """
@@ -47,6 +69,66 @@ class Pdb:
         """
         return self.parse_by_monomers()
 
+    def write(self, filename: str, bonds: list[tuple[int, int]] | None = None, coordinates: list[Coordinates3D] | None = None) -> None :
+        """ Writes the parsed Pdb back to a `.pdb` formatted file.
+            NOTE: Indexing of the bonds is 0-based
+
+            All fields of the ATOM/HETATM records are written to their 
+            PDB v3.3 columns. Without `bonds`, the CONECT records of the 
+            parsed file are written back. The `coordinates` replace those
+            of the parsed file, e.g. after a superposition.
+
+            Raises PuckepyError if the amount of coordinates and atoms are not equal
+
+            >>> pdb = Pdb("adenosine.pdb").parse()
+            >>> pdb.write("adenosine_copy.pdb")
+            >>> pdb.write("adenosine_bonded.pdb", bonds=infer_bonds(pdb))
+            >>> pdb.write("adenosine_moved.pdb", coordinates=[[x + 1., y, z] for x, y, z in pdb.coordinates])
+        """
+        return self.write(filename, bonds, coordinates)
+
+
+class Mmcif:
+
+    def __new__(cls, filename: str) :
+        """ Mmcif Class constructor.
+            ==
+
+            Reads from an mmCIF/PDBx-formatted file, as distributed by the PDB for large structures.
+            Only the `_atom_site` loop is read. Of multi-model files, only the first model is kept.
+            ATOM 2 O "O5'" . DA A 1 1 ? 7.577 2.015 28.826 1.00 0.00 ? 1 DA J "O5'" 1
+            ---------------
+            This class does not have public attributes
+            ---------------
+            Raises FileFormatError if the file is not a `.cif`/`.mmcif` or is empty
+
+            >>> mmcif = Mmcif(filename)
+        """
+        return puckepy.formalism.Mmcif(filename)
+
+    def parse(self) -> Pdb : 
+        """ Parses the `_atom_site` loop of the queried Mmcif file.
+
+            Returns a Pdb() object, with its attributes populated from the `auth_` items
+            (atomnames, residue names and numbers, chains) so it can be passed to 
+            every `from_atomnames()` method.
+
+            >>> pdb = Mmcif("ribosome.cif").parse()
+            >>> amplitude, phase_angle = CP5().from_atomnames(pdb, ["O4'", "C1'", "C2'", "C3'", "C4'"])
+        """
+        return self.parse()
+
+    def parse_by_monomers(self) -> list[Pdb] : 
+        """ Parses the `_atom_site` loop of the queried Mmcif file, by monomer.
+            A new monomer starts at every change of chain or residue number.
+
+            Returns a list[] of populated Pdb() objects
+
+            >>> monomers = Mmcif("duplex.cif").parse_by_monomers()
+            >>> [CP5().from_atomnames(pdb, ["O4'", "C1'", "C2'", "C3'", "C4'"]) for pdb in monomers]
+        """
+        return self.parse_by_monomers()
+
 
 class Xyz:
 
@@ -76,6 +158,61 @@ class Xyz:
         return self.parse()
 
 
+class CP4:
+
+    def __new__(cls, amplitude: float = 0. ):
+        """ Cremer-Pople Class constructor for four-membered ring systems.
+            ==
+            A four-membered ring has a single puckering coordinate, the signed amplitude q2.
+            It is positive when the atoms 1 and 3 lie above the mean plane, negative when the atoms 2 and 4 do.
+            ---------------
+            amplitude: float [ -1. <= amplitude <= 1. ] `angstrom`
+            ---------------
+            This class does not have public attributes
+
+            >>> cp4 = CP4(0.25) # => Puckered cyclobutane
+
+            >>> cp4 = CP4() # => Defaults to CP4(0.), the planar ring
+        """
+        return puckepy.formalism.CP4(amplitude)
+
+    def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> float : 
+        """ Get the Cremer-Pople coordinate by querying from the atom names of the prompted Pdb(). 
+
+            Returns the signed `amplitude`
+
+            >>> pdb = Pdb("oxetanocin.pdb").parse()
+            >>> amplitude = CP4().from_atomnames(pdb=pdb, query_names=["O1'", "C2'", "C3'", "C4'"])
+        """
+        return self.from_atomnames(pdb, query_names)
+
+    def from_indices(self, coordinates: list[Coordinates3D], indices: list[int]) -> float : 
+        """ Get the Cremer-Pople coordinate by querying from the indices of the prompted coordinates. 
+            NOTE: Indexing is 0-based
+
+            Returns the signed `amplitude`
+
+            >>> xyz = Xyz("cyclobutane.xyz").parse()
+            >>> amplitude = CP4().from_indices(coordinates=xyz, indices=[0, 1, 2, 3])
+        """
+        return self.from_indices(coordinates, indices)
+
+    def invert(self, bondlengths: list[float] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]:
+        """ Perform an inversion of the Cremer-Pople coordinate and get returned the 
+            molecular conformation the four-membered ring results in.
+
+            `bondlengths` : atom j -> atom j+1, the last one closing the ring. Defaults to 1.54 Angstrom
+            `elements` : look up the bondlengths by the elements of the ring (C-C 1.54, others the sum of the covalent radii)
+            The endocyclic angles follow from the amplitude, as the ring has a single degree of freedom
+
+            Returns the coordinates of the queried fourring conformation
+
+            >>> cyclobutane = CP4(0.25).invert()
+            >>> oxetane = CP4(0.1).invert(elements=["O", "C", "C", "C"])
+        """
+        return self.invert(bondlengths, elements)
+
+
 class CP5:
 
     def __new__(cls, amplitude: float = 0. , phase_angle: float = 0. ):
@@ -120,15 +257,87 @@ class CP5:
         """
         return self.from_indices(coordinates, indices)
 
-    def invert(self) -> list[Coordinates3D]:
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int]) -> tuple[NDArray[np.float64], NDArray[np.float64]] : 
+        """ Get Cremer-Pople coordinates of every frame of a trajectory, computed in parallel. 
+            NOTE: Indexing is 0-based
+
+            The `coordinates` is an array of shape (n_frames, n_atoms, 3)
+            Returns the `amplitude` and `phase angle` arrays, of shape (n_frames,)
+
+            >>> trajectory = np.load("adenosine_md.npy") # (n_frames, n_atoms, 3)
+            >>> amplitudes, phaseAngles = CP5().from_trajectory(coordinates=trajectory, indices=[7, 8, 26, 24, 5])
+        """
+        return self.from_trajectory(coordinates, indices)
+
+    def from_rings(self, pdb: Pdb) -> list[tuple[list[str], tuple[float, float]]] : 
+        """ Find every five-membered ring in the Pdb and get their Cremer-Pople coordinates. 
+
+            The rings are perceived from the CONECT records, else from the interatomic distances.
+            Every ring is ordered from its heteroatom onwards (O4' -> C1' -> C2' -> C3' -> C4')
+
+            Returns a list of (atomnames, (amplitude, phase angle)), one for every ring
+
+            >>> pdb = Pdb("adenosine.pdb").parse()
+            >>> for atomnames, (amplitude, phaseAngle) in CP5().from_rings(pdb):
+            >>>     print(atomnames, amplitude, phaseAngle)
+        """
+        return self.from_rings(pdb)
+
+    def invert(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]:
         """ Perform an inversion of the Cremer-Pople coordinates and get returned the 
             molecular conformation the five-membered ring results in.
 
+            `bondlengths` : atom j -> atom j+1, the last one closing the ring. Defaults to 1.54 Angstrom
+            `angles` : the endocyclic angle centered on atom j, in degrees, or None to leave it free. Defaults to 109.5
+            `elements` : look up the defaults by the elements of the ring (C-C 1.54, others the sum of the covalent radii)
+            The ring closure only enforces 2 angles, of non-adjacent atoms. The others follow from the puckering
+            Of the angles looked up by `elements`, those of the heteroatoms (O, S, Se, P) are enforced first
+
+            Raises OutOfRangeError when the queried angles cannot all be enforced
+
             Returns the coordinates of the queried fivering conformation
 
             >>> endoC3 = CP5(0.35, 288.).invert()
+            >>> furanose = CP5(0.35, 288.).invert(elements=["O", "C", "C", "C", "C"])
+        """
+        return self.invert(bondlengths, angles, elements)
+
+    def to_as(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None, exact: bool = True) -> tuple[float, float]:
+        """ Convert the Cremer-Pople coordinates to Altona-Sundaralingam coordinates.
+
+            `exact` : invert the ring with the geometry of `bondlengths`, `angles` and `elements` (as invert()),
+                and measure its Altona-Sundaralingam coordinates
+            otherwise : approximate P = phase_angle + 90 and nu_max = 2 sqrt(2) amplitude / (the mean bondlength),
+                which only depends on the geometry through the mean bondlength.
+                Against the exact conversion, P is within 0.7 degrees and nu_max within 1 % for amplitudes up to 0.2
+                of a tetrahedral ring, and within 4.5 degrees and 7.5 % for amplitudes up to 0.45 of a furanose ring
+
+            Returns the `amplitude` (radians) and `phase angle` of AS
+
+            >>> amplitude, phaseAngle = CP5(0.35, 288.).to_as(elements=["O", "C", "C", "C", "C"])
+            >>> amplitude, phaseAngle = CP5(0.35, 288.).to_as(exact=False)
+        """
+        return self.to_as(bondlengths, angles, elements, exact)
+
+    def conformer(self, atomnames: list[str] | None = None) -> puckepy.formalism.Conformer:
+        """ Assign the IUPAC canonical conformer (E, T) nearest to the Cremer-Pople coordinates.
+
+            The `atomnames` are those of the ring, in the queried order. Defaults to O4', C1', C2', C3', C4'
+            Returns a Conformer object :
+            ---------------
+            self.label : str               # 3T2, 2E, E3, ...
+            self.description : str         # C3'-endo, C2'-exo
+            self.phase_angle : float       # of the canonical conformer
+            self.theta : None
+            self.distance : float          # degrees along the pseudorotation wheel
+            self.sector : str              # North, East, South, West
+            ---------------
+
+            >>> amplitude, phaseAngle = CP5().from_atomnames(pdb=pdb, query_names=["O4'", "C1'", "C2'", "C3'", "C4'"])
+            >>> CP5(amplitude, phaseAngle).conformer().label
+            >>> '2T3'
         """
-        return self.invert()
+        return self.conformer(atomnames)
 
 
 class CP6:
@@ -176,15 +385,118 @@ class CP6:
         """
         return self.from_indices(coordinates, indices)
 
-    def invert(self) -> list[list[float]]:
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int]) -> tuple[NDArray[np.float64], NDArray[np.float64], NDArray[np.float64]] : 
+        """ Get Cremer-Pople coordinates of every frame of a trajectory, computed in parallel. 
+            NOTE: Indexing is 0-based
+
+            The `coordinates` is an array of shape (n_frames, n_atoms, 3)
+            Returns the `amplitude`, `phase angle` and `theta` arrays, of shape (n_frames,)
+
+            >>> trajectory = np.load("homodna_adenosine_md.npy") # (n_frames, n_atoms, 3)
+            >>> amplitudes, phaseAngles, thetas = CP6().from_trajectory(coordinates=trajectory, indices=[7, 8, 26, 24, 5, 6])
+        """
+        return self.from_trajectory(coordinates, indices)
+
+    def from_rings(self, pdb: Pdb) -> list[tuple[list[str], tuple[float, float, float]]] : 
+        """ Find every six-membered ring in the Pdb and get their Cremer-Pople coordinates. 
+
+            The rings are perceived from the CONECT records, else from the interatomic distances.
+            Every ring is ordered from its heteroatom onwards (O5' -> C1' -> C2' -> C3' -> C4' -> C5')
+
+            Returns a list of (atomnames, (amplitude, phase angle, theta)), one for every ring
+
+            >>> pdb = Pdb("glucose.pdb").parse()
+            >>> rings = CP6().from_rings(pdb)
+        """
+        return self.from_rings(pdb)
+
+    def invert(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> list[list[float]]:
         """ Perform an inversion of the Cremer-Pople coordinates and get returned the 
             molecular conformation the six-membered ring results in.
 
+            `bondlengths` : atom j -> atom j+1, the last one closing the ring. Defaults to 1.54 Angstrom
+            `angles` : the endocyclic angle centered on atom j, in degrees, or None to leave it free. Defaults to 109.5
+            `elements` : look up the defaults by the elements of the ring (C-C 1.54, others the sum of the covalent radii)
+            The ring closure only enforces 3 angles, of every other atom. The others follow from the puckering
+            Of the angles looked up by `elements`, those of the heteroatoms (O, S, Se, P) are enforced first
+
+            Raises OutOfRangeError when the queried angles cannot all be enforced
+
             Returns the coordinates of the queried sixring conformation
 
             >>> boat3O = CP6(0.35, 90., 90.).invert()
+            >>> morpholino = CP6(0.35, 90., 90.).invert(elements=["O", "C", "C", "N", "C", "C"])
+        """
+        return self.invert(bondlengths, angles, elements)
+
+    def conformer(self, atomnames: list[str] | None = None) -> puckepy.formalism.Conformer:
+        """ Assign the IUPAC canonical conformer (C, B, S, H, E) nearest to the Cremer-Pople coordinates.
+
+            The `atomnames` are those of the ring, in the queried order. Defaults to O5, C1, C2, C3, C4, C5
+            Returns a Conformer object :
+            ---------------
+            self.label : str               # 4C1, 1S3, B2,5, OH5, ...
+            self.description : str         # C4-endo, C1-exo
+            self.phase_angle : float       # of the canonical conformer
+            self.theta : float             # of the canonical conformer
+            self.distance : float          # degrees over the Cremer-Pople sphere
+            self.sector : None
+            ---------------
+
+            >>> CP6(0.6, 300., 88.).conformer().label
+            >>> 'B2,5'
         """
-        return self.invert()
+        return self.conformer(atomnames)
+
+class CPN:
+
+    ring_size: int
+    amplitude: float
+    amplitudes: list[float]
+    phase_angles: list[float]
+    q_half: float | None
+
+    def __new__(cls, ring_size: int):
+        """ Cremer-Pople Class constructor for N-membered ring systems.
+            ==
+            Create a class to calculate the generalised Cremer-Pople coordinates.
+            ---------------
+            ring_size: int [ 4 <= ring_size ]
+            ---------------
+            self.ring_size : int
+            self.amplitude : float, the total puckering amplitude Q
+            self.amplitudes : list[float], q_m for m = 2 -> (N-1)/2
+            self.phase_angles : list[float], phi_m for m = 2 -> (N-1)/2 `degrees`
+            self.q_half : float | None, q_{N/2}. Only exists for even-membered rings
+
+            >>> cpn = CPN(7) # => seven-membered ring
+        """
+        return puckepy.formalism.CPN(ring_size)
+
+    def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> puckepy.formalism.CPN : 
+        """ Get Cremer-Pople coordinates by querying from the atom names of the prompted Pdb(). 
+
+            Returns a populated CPN() object
+
+            >>> pdb = Pdb("azepane.pdb")
+            >>> cpn = CPN(7).from_atomnames(pdb=pdb, query_names=["N1", "C2", "C3", "C4", "C5", "C6", "C7"])
+            >>> print(cpn.amplitudes, cpn.phase_angles)
+        """
+        return self.from_atomnames(pdb, query_names)
+
+    def from_indices(self, coordinates: list[Coordinates3D], indices: list[int]) -> puckepy.formalism.CPN : 
+        """ Get Cremer-Pople coordinates by querying from the indices of the prompted coordinates. 
+            NOTE: Indexing is 0-based
+
+            Returns a populated CPN() object
+
+            >>> xyz = Xyz("cyclooctane.xyz")
+            >>> coordinates = xyz.parse()
+            >>> cpn = CPN(8).from_indices(coordinates=coordinates, indices=[0, 1, 2, 3, 4, 5, 6, 7])
+            >>> print(cpn.amplitudes, cpn.phase_angles, cpn.q_half)
+        """
+        return self.from_indices(coordinates, indices)
+
 
 class AS:
 
@@ -228,6 +540,58 @@ class AS:
 
         return self.from_indices(coordinates, indices)
 
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int]) -> tuple[NDArray[np.float64], NDArray[np.float64]] : 
+        """ Get Altona-Sundaralingam coordinates of every frame of a trajectory, computed in parallel. 
+            NOTE: Indexing is 0-based
+
+            The `coordinates` is an array of shape (n_frames, n_atoms, 3)
+            Returns the `amplitude` and `phase angle` arrays, of shape (n_frames,)
+
+            >>> trajectory = np.load("adenosine_md.npy") # (n_frames, n_atoms, 3)
+            >>> amplitudes, phaseAngles = AS().from_trajectory(coordinates=trajectory, indices=[7, 8, 26, 24, 5])
+        """
+        return self.from_trajectory(coordinates, indices)
+
+    def from_rings(self, pdb: Pdb) -> list[tuple[list[str], tuple[float, float]]] : 
+        """ Find every five-membered ring in the Pdb and get their Altona-Sundaralingam coordinates. 
+
+            Every ring is ordered from its heteroatom onwards (O4' -> C1' -> C2' -> C3' -> C4')
+
+            Returns a list of (atomnames, (amplitude, phase angle)), one for every ring
+
+            >>> pdb = Pdb("adenosine.pdb").parse()
+            >>> rings = AS().from_rings(pdb)
+        """
+        return self.from_rings(pdb)
+
+    def conformer(self, atomnames: list[str] | None = None) -> puckepy.formalism.Conformer:
+        """ Assign the IUPAC canonical conformer (E, T) nearest to the Altona-Sundaralingam coordinates.
+
+            The `atomnames` are those of the ring, in the queried order. Defaults to O4', C1', C2', C3', C4'
+            Returns a Conformer object, with the `sector` being North, East, South or West 
+            on the pseudorotation wheel
+
+            >>> AS(0.6, 18.).conformer().description
+            >>> "C3'-endo"
+        """
+        return self.conformer(atomnames)
+
+    def to_cp5(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None, exact: bool = True) -> tuple[float, float]:
+        """ Convert the Altona-Sundaralingam coordinates to Cremer-Pople coordinates.
+
+            `exact` : solve for the Cremer-Pople coordinates whose ring, inverted with the geometry of
+                `bondlengths`, `angles` and `elements` (as CP5().invert()), has these Altona-Sundaralingam coordinates
+            otherwise : approximate phase_angle = P - 90 and amplitude = nu_max (the mean bondlength) / 2 sqrt(2),
+                with the same error bounds as CP5().to_as()
+
+            Returns the `amplitude` and `phase angle` of CP5
+
+            Raises PuckepyError if the exact conversion does not converge
+
+            >>> amplitude, phaseAngle = AS(0.7, 18.).to_cp5(elements=["O", "C", "C", "C", "C"])
+        """
+        return self.to_cp5(bondlengths, angles, elements, exact)
+
 
 
 class SP :
@@ -266,11 +630,133 @@ class SP :
         """
         return self.from_indices(coordinates, indices)
 
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int]) -> tuple[NDArray[np.float64], NDArray[np.float64]] : 
+        """ Get Strauss-Pickett coordinates of every frame of a trajectory, computed in parallel. 
+            NOTE: Indexing is 0-based
+
+            The `coordinates` is an array of shape (n_frames, n_atoms, 3)
+            Returns the `alphas` and `betas` arrays, of shape (n_frames, 3)
+
+            >>> trajectory = np.load("homodna_adenosine_md.npy") # (n_frames, n_atoms, 3)
+            >>> alphas, betas = SP().from_trajectory(coordinates=trajectory, indices=[7, 8, 26, 24, 5, 6])
+        """
+        return self.from_trajectory(coordinates, indices)
+
+    def from_rings(self, pdb: Pdb) -> list[tuple[list[str], tuple[Coordinates3D, Coordinates3D]]] : 
+        """ Find every six-membered ring in the Pdb and get their Strauss-Pickett coordinates. 
+
+            Every ring is ordered from its heteroatom onwards (O5' -> C1' -> C2' -> C3' -> C4' -> C5')
+
+            Returns a list of (atomnames, (alphas, betas)), one for every ring
+
+            >>> pdb = Pdb("glucose.pdb").parse()
+            >>> rings = SP().from_rings(pdb)
+        """
+        return self.from_rings(pdb)
+
+
+
+class NucleicAcid:
+
+    chains: list[str]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    alpha: list[float | None]
+    beta: list[float | None]
+    gamma: list[float | None]
+    delta: list[float | None]
+    epsilon: list[float | None]
+    zeta: list[float | None]
+    chi: list[float | None]
+    glycosidic: list[str | None]
+    amplitude: list[float | None]
+    phase_angle: list[float | None]
+    sugar_conformer: list[str | None]
+
+    def __new__(cls, pdb: Pdb) :
+        """ Backbone torsions, glycosidic torsion and sugar pucker of a DNA/RNA/XNA strand.
+            ==
+            Every attribute holds one value per nucleotide, in the order of the Pdb. 
+            Residues without a sugar (waters, ions, ligands) are left out.
+            ---------------
+            self.alpha   : O3'(i-1) - P - O5' - C5'
+            self.beta    : P - O5' - C5' - C4'
+            self.gamma   : O5' - C5' - C4' - C3'
+            self.delta   : C5' - C4' - C3' - O3'
+            self.epsilon : C4' - C3' - O3' - P(i+1)
+            self.zeta    : C3' - O3' - P(i+1) - O5'(i+1)
+            self.chi     : O4' - C1' - N9 - C4 (purines) or O4' - C1' - N1 - C2 (pyrimidines)
+            self.glycosidic : "syn" (-90 < chi < 90) or "anti"
+            self.amplitude, self.phase_angle : Altona-Sundaralingam pucker of O4', C1', C2', C3', C4'
+            self.sugar_conformer : IUPAC canonical conformer of the sugar, e.g. 3E, 2T3
+            ---------------
+            Torsions are in degrees [-180. -> 180.]. A value is None when one of its atoms is missing
+            (e.g. alpha of the 5'-terminal residue), or when the residues are not bonded (chain break).
+            Atomnames of older files, with `*` instead of `'` (C1*), are recognised.
+
+            Raises AtomNotFoundError if the Pdb holds no nucleotides
+
+            >>> pdb = Pdb("duplex.pdb").parse()
+            >>> na = NucleicAcid(pdb)
+            >>> for row in zip(na.residuenumbers, na.alpha, na.chi, na.glycosidic, na.sugar_conformer):
+            >>>     print(row)
+        """
+        return puckepy.formalism.NucleicAcid(pdb)
+
+
+class Protein:
+
+    chains: list[str]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    phi: list[float | None]
+    psi: list[float | None]
+    omega: list[float | None]
+    cis: list[bool | None]
+    chi1: list[float | None]
+    chi2: list[float | None]
+    chi3: list[float | None]
+    chi4: list[float | None]
+
+    def __new__(cls, pdb: Pdb) :
+        """ Backbone and side-chain dihedrals of a protein.
+            ==
+            Every attribute holds one value per amino acid, in the order of the Pdb. 
+            Residues without an N, CA and C (waters, ions, ligands) are left out.
+            ---------------
+            self.phi   : C(i-1) - N - CA - C
+            self.psi   : N - CA - C - N(i+1)
+            self.omega : CA(i-1) - C(i-1) - N - CA, the peptide bond preceding the residue
+            self.cis   : True when |omega| < 30, e.g. the cis peptide bond of a cis-proline
+            self.chi1 .. self.chi4 : side-chain dihedrals, by the standard atom names (N - CA - CB - OG for SER)
+            ---------------
+            Dihedrals are in degrees [-180. -> 180.]. A value is None when one of its atoms is missing
+            (e.g. phi of the N-terminal residue, chi1 of glycine), or when the residues are not bonded (chain break).
+
+            Raises AtomNotFoundError if the Pdb holds no amino acids
+
+            >>> protein = Protein(Pdb("protein.pdb").parse())
+            >>> for row in zip(protein.residuenumbers, protein.phi, protein.psi, protein.cis):
+            >>>     print(row)
+        """
+        return puckepy.formalism.Protein(pdb)
+
+    def phi_psi(self) -> dict[tuple[str, int], tuple[float, float]] :
+        """ The (phi, psi) of every residue where both are defined, keyed by (chain, residuenumber).
+
+            The Peptide() grid spans [0. -> 360.], so take the dihedrals modulo 360. to overlay them
+
+            >>> phi_psi = Protein(Pdb("protein.pdb").parse()).phi_psi()
+            >>> phi, psi = phi_psi[("A", 42)]
+        """
+        return self.phi_psi()
 
 
 def write_to_pdb(filename: str, coordinates: list[Coordinates3D], residuename: str) -> None :
     """ Write a set of coordinates with their respective atomnames to a `.pdb` 
         formatted file. 
+        A ring of five atoms is named O4', C1' .. C4', of six atoms O5', C1' .. C5',
+        and a ring of any other size C1 .. CN. Raises RingSizeError for less than 3 atoms
 
         >>> conf_coordinates = CP5(0.35, 90.).invert() # 2' endo conformation
         >>> write_to_pdb(filename: "2endo.pdb", 
@@ -284,6 +770,8 @@ def write_to_pdb(filename: str, coordinates: list[Coordinates3D], residuename: s
 
 def write_to_xyz(filename: str, coordinates: list[Coordinates3D]) -> None :
     """ Write a set of coordinates to an `.xyz` formatted file. 
+        A ring of five atoms is named O4', C1' .. C4', of six atoms O5', C1' .. C5',
+        and a ring of any other size C1 .. CN. Raises RingSizeError for less than 3 atoms
 
         >>> conf_coordinates = CP5(0.35, 90.).invert() # 2' endo conformation
         >>> write_to_pdb(filename: "2endo.xyz", 
@@ -291,3 +779,93 @@ def write_to_xyz(filename: str, coordinates: list[Coordinates3D]) -> None :
         >>>              )
     """
     puckepy.formalism.write_to_xyz(filename, coordinates)
+
+
+def write_models_to_pdb(filename: str, conformers: list[list[Coordinates3D]], residuename: str) -> None :
+    """ Write a set of conformers to a single `.pdb` formatted file,
+        every conformer as a MODEL/ENDMDL block. 
+        The conformers are named as by write_to_pdb(), for any ring size
+
+        >>> sixring = Sixring(631, keep_coordinates=True)
+        >>> write_models_to_pdb(filename="sixring_grid.pdb", 
+        >>>                     conformers=sixring.coordinates,
+        >>>                     residuename="SIX"
+        >>>                     )
+    """
+    puckepy.formalism.write_models_to_pdb(filename, conformers, residuename)
+
+
+def write_frames_to_xyz(filename: str, conformers: list[list[Coordinates3D]]) -> None :
+    """ Write a set of conformers to a single, multi-frame `.xyz` formatted file. 
+        The conformers are named as by write_to_xyz(), for any ring size
+
+        >>> sixring = Sixring(631, keep_coordinates=True)
+        >>> write_frames_to_xyz(filename="sixring_grid.xyz", 
+        >>>                     conformers=sixring.coordinates,
+        >>>                     )
+    """
+    puckepy.formalism.write_frames_to_xyz(filename, conformers)
+
+
+def write_pdb(filename: str, 
+              atomnames: list[str], 
+              coordinates: list[Coordinates3D], 
+              elements: list[str] | None = None,
+              residuenames: list[str] | None = None,
+              residuenumbers: list[int] | None = None,
+              chains: list[str] | None = None,
+              occupancies: list[float] | None = None,
+              bfactors: list[float] | None = None,
+              bonds: list[tuple[int, int]] | None = None,
+              ) -> None :
+    """ Write an arbitrary molecule to a `.pdb` formatted file.
+        NOTE: Indexing of the bonds is 0-based
+
+        Every per-atom list must be as long as `atomnames`. Fields left out default to
+        element (guessed from the atomname), residuename `UNK`, residuenumber `1`, 
+        chain `A`, occupancy `1.00` and B-factor `0.00`.
+        The `bonds` are written as CONECT records.
+
+        Raises PuckepyError if a per-atom list does not match the amount of atoms
+        Raises FileFormatError if a field does not fit its PDB column
+        Raises OutOfRangeError if a bond refers to a non-existing atom
+
+        >>> write_pdb(filename="water.pdb",
+        >>>           atomnames=["O", "H1", "H2"],
+        >>>           coordinates=[[0., 0., 0.], [0.957, 0., 0.], [-0.240, 0.927, 0.]],
+        >>>           residuenames=["HOH"] * 3,
+        >>>           bonds=[(0, 1), (0, 2)],
+        >>>           )
+    """
+    puckepy.formalism.write_pdb(filename, atomnames, coordinates, elements, residuenames, 
+                                residuenumbers, chains, occupancies, bfactors, bonds)
+
+
+def infer_bonds(pdb: Pdb) -> list[tuple[int, int]] :
+    """ Infer the covalent bonds of the molecule. Hydrogens are left out.
+        NOTE: Indexing is 0-based
+
+        Atoms listed in the CONECT records are bonded accordingly.
+        All other atoms are bonded when their distance is within the sum of 
+        their covalent radii, plus a tolerance of 0.45 Angstrom.
+
+        >>> pdb = Pdb("adenosine.pdb").parse()
+        >>> bonds = infer_bonds(pdb) # [(i, j), ...] with i < j
+    """
+    return puckepy.formalism.infer_bonds(pdb)
+
+
+def find_rings(pdb: Pdb, max_ring_size: int = 8) -> list[list[int]] :
+    """ Find the smallest set of smallest rings (SSSR) of the molecule.
+        NOTE: Indexing is 0-based
+
+        Every ring is ordered cyclically, starting at its heteroatom 
+        (O > S > Se > Te > N > P > As > Si > B) and proceeding towards the 
+        neighbour whose atomname sorts first. 
+        A furanose then reads O4' -> C1' -> C2' -> C3' -> C4'
+
+        >>> pdb = Pdb("adenosine.pdb").parse()
+        >>> for ring in find_rings(pdb):
+        >>>     print([pdb.atomnames[i] for i in ring])
+    """
+    return puckepy.formalism.find_rings(pdb, max_ring_size)
@@ -2,15 +2,34 @@
 from typing import TypeAlias
 Coordinates3D: TypeAlias = tuple[float, float, float]
 
+from numpy.typing import NDArray
+import numpy as np
+
 __all__: list[str]
 
 class Pdb:
+    filename: str
     coordinates: list[Coordinates3D]
     atomnames: list[str]
+    elements: list[str]
+    hetatm: list[bool]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    chains: list[str]
+    occupancies: list[float]
+    bfactors: list[float]
 
     def __new__(cls, filename: str) -> Pdb : ...
     def parse(self) -> Pdb : ...
     def parse_by_monomers(self) -> list[Pdb]: ...
+    def write(self, filename: str, bonds: list[tuple[int, int]] | None = None, coordinates: list[Coordinates3D] | None = None) -> None : ...
+
+
+class Mmcif:
+
+    def __new__(cls, filename: str) -> Mmcif : ...
+    def parse(self) -> Pdb : ...
+    def parse_by_monomers(self) -> list[Pdb]: ...
 
 
 class Xyz:
@@ -19,12 +38,24 @@ class Xyz:
     def parse(self) -> list[Coordinates3D] : ...
 
 
+class CP4: 
+
+    def __new__(cls, amplitude: float = 0. ): ...
+    def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> float :  ...
+    def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> float :  ...
+    def invert(self, bondlengths: list[float] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]: ...
+
+
 class CP5: 
 
     def __new__(cls, amplitude: float = 0. , phase_angle: float = 0. ): ...
     def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> tuple[float, float] :  ...
     def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> tuple[float, float] :  ...
-    def invert(self) -> list[Coordinates3D]: ...
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int])  -> tuple[NDArray[np.float64], NDArray[np.float64]] :  ...
+    def from_rings(self, pdb: Pdb)  -> list[tuple[list[str], tuple[float, float]]] :  ...
+    def invert(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]: ...
+    def to_as(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None, exact: bool = True) -> tuple[float, float]: ...
+    def conformer(self, atomnames: list[str] | None = None) -> Conformer: ...
 
 
 class CP6: 
@@ -32,7 +63,32 @@ class CP6:
     def __new__(cls, amplitude: float = 0. , phase_angle: float = 0. , theta: float = 0.): ...
     def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> tuple[float, float, float] :  ...
     def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> tuple[float, float, float] :  ...
-    def invert(self) -> list[Coordinates3D]: ...
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int])  -> tuple[NDArray[np.float64], NDArray[np.float64], NDArray[np.float64]] :  ...
+    def from_rings(self, pdb: Pdb)  -> list[tuple[list[str], tuple[float, float, float]]] :  ...
+    def invert(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]: ...
+    def conformer(self, atomnames: list[str] | None = None) -> Conformer: ...
+
+
+class CPN: 
+    ring_size: int
+    amplitude: float
+    amplitudes: list[float]
+    phase_angles: list[float]
+    q_half: float | None
+
+    def __new__(cls, ring_size: int): ...
+    def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> CPN :  ...
+    def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> CPN :  ...
+
+
+class Conformer:
+    """ Nearest IUPAC canonical conformer, returned by CP5().conformer(), CP6().conformer() and AS().conformer() """
+    label: str
+    description: str
+    phase_angle: float
+    theta: float | None
+    distance: float
+    sector: str | None
 
 
 class AS:
@@ -40,14 +96,63 @@ class AS:
     def __new__(cls, amplitude: float = 0. , phase_angle: float = 0. ): ...
     def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> tuple[float, float] : ...
     def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> tuple[float, float] : ...
-#    def invert(self) -> list[Coordinates3D]: ...
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int])  -> tuple[NDArray[np.float64], NDArray[np.float64]] : ...
+    def from_rings(self, pdb: Pdb)  -> list[tuple[list[str], tuple[float, float]]] : ...
+    def conformer(self, atomnames: list[str] | None = None) -> Conformer: ...
+    def to_cp5(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None, exact: bool = True) -> tuple[float, float]: ...
+#    def invert(self, bondlengths: list[float] | None = None, angles: list[float | None] | None = None, elements: list[str] | None = None) -> list[Coordinates3D]: ...
 
 class SP:
 
     def __new__(cls) : ...
     def from_atomnames(self, pdb: Pdb, query_names: list[str]) -> tuple[Coordinates3D, Coordinates3D] : ...
     def from_indices(self, coordinates: list[Coordinates3D], indices: list[int])  -> tuple[Coordinates3D, Coordinates3D] : ...
+    def from_trajectory(self, coordinates: NDArray[np.float64], indices: list[int])  -> tuple[NDArray[np.float64], NDArray[np.float64]] : ...
+    def from_rings(self, pdb: Pdb)  -> list[tuple[list[str], tuple[Coordinates3D, Coordinates3D]]] : ...
+
+
+class NucleicAcid:
+    chains: list[str]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    alpha: list[float | None]
+    beta: list[float | None]
+    gamma: list[float | None]
+    delta: list[float | None]
+    epsilon: list[float | None]
+    zeta: list[float | None]
+    chi: list[float | None]
+    glycosidic: list[str | None]
+    amplitude: list[float | None]
+    phase_angle: list[float | None]
+    sugar_conformer: list[str | None]
+
+    def __new__(cls, pdb: Pdb) -> NucleicAcid : ...
+    def __len__(self) -> int : ...
+
+
+class Protein:
+    chains: list[str]
+    residuenames: list[str]
+    residuenumbers: list[int]
+    phi: list[float | None]
+    psi: list[float | None]
+    omega: list[float | None]
+    cis: list[bool | None]
+    chi1: list[float | None]
+    chi2: list[float | None]
+    chi3: list[float | None]
+    chi4: list[float | None]
+
+    def __new__(cls, pdb: Pdb) -> Protein : ...
+    def __len__(self) -> int : ...
+    def phi_psi(self) -> dict[tuple[str, int], tuple[float, float]] : ...
 
 
 def write_to_pdb(fname: str, coordinates: list[Coordinates3D], residuename: str) -> None : ...
 def write_to_xyz(fname: str, coordinates: list[Coordinates3D]) -> None : ... 
+def write_models_to_pdb(fname: str, conformers: list[list[Coordinates3D]], residuename: str) -> None : ...
+def write_frames_to_xyz(fname: str, conformers: list[list[Coordinates3D]]) -> None : ...
+def write_pdb(filename: str, atomnames: list[str], coordinates: list[Coordinates3D], elements: list[str] | None = None, residuenames: list[str] | None = None, residuenumbers: list[int] | None = None, chains: list[str] | None = None, occupancies: list[float] | None = None, bfactors: list[float] | None = None, bonds: list[tuple[int, int]] | None = None) -> None : ...
+def infer_bonds(pdb: Pdb) -> list[tuple[int, int]] : ...
+def find_rings(pdb: Pdb, max_ring_size: int = 8) -> list[list[int]] : ...
@@ -2,6 +2,10 @@ from puckepy.geometry.geometry import (
         dihedral as dihedral,
         bondangle as bondangle,
         bondlength as bondlength,
+        rmsd as rmsd,
+        rmsd_matrix as rmsd_matrix,
+        masses as masses,
+        Superposition as Superposition,
         )
 
 __all__: list[str]
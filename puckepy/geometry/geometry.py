@@ -3,7 +3,9 @@ from puckepy import puckepy # this imports the puckepy.abi3.so binary
 from typing import TypeAlias
 Coordinates3D: TypeAlias = tuple[float, float, float]
 
-__all__ = ["dihedral", "bondangle", "bondlength"] 
+from puckepy.formalism import Pdb
+
+__all__ = ["dihedral", "bondangle", "bondlength", "rmsd", "rmsd_matrix", "masses", "Superposition"] 
 
 
 def dihedral(p0: Coordinates3D, p1: Coordinates3D, p2: Coordinates3D, p3: Coordinates3D) -> float :
@@ -55,3 +57,85 @@ def bondlength(p0: Coordinates3D, p1: Coordinates3D) -> float :
 
     """
     return puckepy.geometry.bondlength(p0, p1)
+
+def rmsd(a: list[Coordinates3D], b: list[Coordinates3D], weights: list[float] | None = None, fit: bool = True) -> float :
+    """ Calculate the RMSD between two sets of coordinates, atom per atom
+        --------------------
+        With `fit`, the coordinates are optimally superposed first (see Superposition), else they are compared as they are.
+        Every atom is weighted by its `weights`, e.g. the masses of the atoms.
+
+        Raises PuckepyError if the amount of coordinates or weights do not match
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+
+        >>> crystal = Pdb("pyranose.pdb").parse()
+        >>> rebuilt = CP6(0.57, 3.2, 243.).invert()
+        >>> indices = [crystal.atomnames.index(name) for name in ["O5", "C1", "C2", "C3", "C4", "C5"]]
+        >>> rmsd([crystal.coordinates[i] for i in indices], rebuilt)
+        >>> rmsd(a, b, weights=masses(["O", "C", "C", "C", "C", "C"]))
+    """
+    return puckepy.geometry.rmsd(a, b, weights, fit)
+
+def rmsd_matrix(conformers: list[list[Coordinates3D]], weights: list[float] | None = None, fit: bool = True) -> list[list[float]] :
+    """ Calculate the RMSD between every pair of conformers, as a symmetric matrix
+        --------------------
+        Every conformer holds the same atoms in the same order. Pairs are calculated in parallel.
+
+        >>> conformers = [Pdb(f"conformer_{i}.pdb").parse().coordinates for i in range(100)]
+        >>> matrix = rmsd_matrix(conformers)
+        >>> matrix[0][1] == matrix[1][0]
+    """
+    return puckepy.geometry.rmsd_matrix(conformers, weights, fit)
+
+def masses(elements: list[str]) -> list[float] :
+    """ The standard atomic weights (u) of the elements, to weigh the atoms by their mass
+
+        Raises PuckepyError for an element that is not tabulated
+        >>> masses(pdb.elements)
+    """
+    return puckepy.geometry.masses(elements)
+
+
+class Superposition :
+    """ Superpose the `mobile` coordinates onto the `reference` coordinates
+
+        Finds the rotation and translation that minimise the RMSD between the two sets of coordinates, atom per atom,
+        as the Kabsch algorithm does (here by the quaternion method of Horn, which never returns a reflection).
+        Every atom is weighted by its `weights`, e.g. the masses of the atoms.
+
+        A superposed coordinate is `rotation` . coordinate + `translation`
+        ---------------
+        self.rotation : list[list[float]]   # 3x3
+        self.translation : Coordinates3D
+        self.rmsd : float                   # after the superposition
+
+        Raises PuckepyError if the amount of coordinates or weights do not match
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+
+        >>> superposition = Superposition(rebuilt, crystal_ring)
+        >>> print(superposition.rmsd)
+
+        >>> ring = ["O4'", "C1'", "C2'", "C3'", "C4'"]
+        >>> superposition = Superposition.from_atomnames(Pdb("md_frame.pdb").parse(), Pdb("crystal.pdb").parse(), ring, mass_weighted=True)
+        >>> frame_on_crystal = superposition.apply(Pdb("md_frame.pdb").parse().coordinates)
+    """
+    rotation : list[list[float]]
+    translation : Coordinates3D
+    rmsd : float
+
+    def __new__(cls, mobile: list[Coordinates3D], reference: list[Coordinates3D], weights: list[float] | None = None) :
+        return puckepy.geometry.Superposition(mobile, reference, weights)
+
+    @staticmethod
+    def from_atomnames(mobile: Pdb, reference: Pdb, atomnames: list[str], mass_weighted: bool = False) -> "Superposition" :
+        """ Superpose the atoms of `mobile` onto the same atoms of `reference`, selected by their atomnames
+            The atoms are weighted by the masses of their elements if `mass_weighted`
+
+            Raises AtomNotFoundError if an atomname is not found in either Pdb
+        """
+        return puckepy.geometry.Superposition.from_atomnames(mobile, reference, atomnames, mass_weighted)
+
+    def apply(self, coordinates: list[Coordinates3D]) -> list[Coordinates3D] :
+        """ Move any coordinates along the superposition,
+            e.g. every atom of the molecule that was superposed on a subset of its atoms
+        """
+        return self.apply(coordinates)
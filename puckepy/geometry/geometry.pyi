@@ -1,6 +1,8 @@
 from typing import TypeAlias
 Coordinates3D: TypeAlias = tuple[float, float, float]
 
+from puckepy.formalism import Pdb
+
 __all__: list[str]
 
 def dihedral(p0: Coordinates3D, p1: Coordinates3D, p2: Coordinates3D, p3: Coordinates3D) -> float : ...
@@ -8,3 +10,19 @@ def dihedral(p0: Coordinates3D, p1: Coordinates3D, p2: Coordinates3D, p3: Coordi
 def bondangle(p0: Coordinates3D, p1: Coordinates3D, p2: Coordinates3D) -> float : ...
 
 def bondlength(p0: Coordinates3D, p1: Coordinates3D) -> float : ...
+
+def rmsd(a: list[Coordinates3D], b: list[Coordinates3D], weights: list[float] | None = None, fit: bool = True) -> float : ...
+
+def rmsd_matrix(conformers: list[list[Coordinates3D]], weights: list[float] | None = None, fit: bool = True) -> list[list[float]] : ...
+
+def masses(elements: list[str]) -> list[float] : ...
+
+class Superposition :
+    rotation: list[list[float]]
+    translation: Coordinates3D
+    rmsd: float
+
+    def __new__(cls, mobile: list[Coordinates3D], reference: list[Coordinates3D], weights: list[float] | None = None) -> Superposition : ...
+    @staticmethod
+    def from_atomnames(mobile: Pdb, reference: Pdb, atomnames: list[str], mass_weighted: bool = False) -> Superposition : ...
+    def apply(self, coordinates: list[Coordinates3D]) -> list[Coordinates3D] : ...
@@ -0,0 +1,14 @@
+"""
+puckepy.statistics
+==================
+
+Statistics of the angles that the puckering formalisms produce.
+Angles cannot be averaged like ordinary numbers : the mean of 350 and 10 degrees is 0, not 180.
+Every angle is treated as a direction, on the circle (phase angles and torsions) or on the sphere (theta and phi of CP6)
+"""
+
+# To get to sub modules
+from . import statistics
+from .statistics import *
+
+__all__ = statistics.__all__.copy()
@@ -0,0 +1,11 @@
+from puckepy.statistics.statistics import (
+        circular_mean as circular_mean,
+        circular_variance as circular_variance,
+        VonMises as VonMises,
+        spherical_mean as spherical_mean,
+        spherical_variance as spherical_variance,
+        Fisher as Fisher,
+        )
+
+
+__all__: list[str]
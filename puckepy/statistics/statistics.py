@@ -0,0 +1,140 @@
+from puckepy import puckepy
+
+
+__all__ = ["circular_mean", "circular_variance", "VonMises", "spherical_mean", "spherical_variance", "Fisher"]
+
+
+# Every angle is in degrees, as the formalism classes return them :
+#     the phase_angle of AS().from_atomnames() and CP5().from_atomnames(), and the torsions of a peptide, lie on the circle
+#     the (theta, phase_angle) of CP6().from_atomnames() lie on the sphere, as (theta, phi)
+# Every angle counts once, or by its `weights` (e.g. Boltzmann populations or the weights of a reweighted simulation)
+
+def circular_mean(angles: list[float], weights: list[float] | None = None) -> float :
+    """ The circular mean of the `angles`
+        --------------------
+        The direction of the sum of the unit vectors of the angles, so that 350 and 10 degrees average to 0.
+        Returned in [-180, 180) if any of the angles is negative (as peptide torsions), else in [0, 360).
+        If the angles cancel out (a circular variance of 1), their mean direction means nothing
+
+        Raises PuckepyError if no angles are queried, or if the amount of angles and weights are not equal
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+
+        >>> phase_angles = [AS().from_atomnames(pdb, ["O4'", "C1'", "C2'", "C3'", "C4'"])[1] for pdb in trajectory]
+        >>> circular_mean(phase_angles)
+    """
+    return puckepy.statistics.circular_mean(angles, weights)
+
+def circular_variance(angles: list[float], weights: list[float] | None = None) -> float :
+    """ The circular variance of the `angles`, 1 - the length of the mean of their unit vectors
+        --------------------
+        0 if all angles are equal, towards 1 as they spread out over the circle
+
+        Raises PuckepyError if no angles are queried, or if the amount of angles and weights are not equal
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+    """
+    return puckepy.statistics.circular_variance(angles, weights)
+
+
+class VonMises :
+    """ The von Mises distribution of angles on the circle, the circular analogue of the normal distribution
+
+        The density at an angle x is exp(kappa * cos(x - mean)) / (2 pi I0(kappa)), with I0 the modified Bessel function.
+        A `kappa` of 0 is a uniform distribution; a large `kappa` approaches a normal distribution of variance 1 / kappa (radians^2)
+        ---------------
+        self.mean : float       # degrees
+        self.kappa : float
+
+        Raises OutOfRangeError if `kappa` is negative
+
+        >>> fit = VonMises.fit(phase_angles)
+        >>> print(fit.mean, fit.kappa)
+    """
+    mean : float
+    kappa : float
+
+    def __new__(cls, mean: float, kappa: float) :
+        return puckepy.statistics.VonMises(mean, kappa)
+
+    @staticmethod
+    def fit(angles: list[float], weights: list[float] | None = None) -> "VonMises" :
+        """ Fit the distribution to the `angles` by maximum likelihood
+            The mean is the circular mean of the angles. Kappa solves I1(kappa) / I0(kappa) = 1 - the circular variance,
+            which is infinite if all angles are equal
+
+            Raises PuckepyError if no angles are queried, or if the amount of angles and weights are not equal
+            Raises OutOfRangeError if a weight is negative, or all weights are zero
+        """
+        return puckepy.statistics.VonMises.fit(angles, weights)
+
+    def pdf(self, angles: list[float]) -> list[float] :
+        """ The probability density at every angle, per degree
+            An infinite kappa puts all density at the mean : infinite there, and 0 elsewhere
+        """
+        return self.pdf(angles)
+
+
+def spherical_mean(theta: list[float], phi: list[float], weights: list[float] | None = None) -> tuple[float, float] :
+    """ The spherical mean direction of the (`theta`, `phi`), as (theta, phi) with phi in [0, 360)
+        --------------------
+        The direction of the sum of the unit vectors of the points on the Cremer-Pople sphere,
+        so that points around a pole average onto the pole, whatever their phi.
+
+        Raises PuckepyError if no angles are queried, or if the amount of theta, phi and weights are not equal
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+
+        >>> cp6 = [CP6().from_atomnames(pdb, ["O5", "C1", "C2", "C3", "C4", "C5"]) for pdb in trajectory]
+        >>> theta, phi = spherical_mean([c[2] for c in cp6], [c[1] for c in cp6]) # (amplitude, phase_angle, theta)
+    """
+    return puckepy.statistics.spherical_mean(theta, phi, weights)
+
+def spherical_variance(theta: list[float], phi: list[float], weights: list[float] | None = None) -> float :
+    """ The spherical variance of the (`theta`, `phi`), 1 - the length of the mean of their unit vectors
+        --------------------
+        0 if all points are equal, towards 1 as they spread out over the sphere
+
+        Raises PuckepyError if no angles are queried, or if the amount of theta, phi and weights are not equal
+        Raises OutOfRangeError if a weight is negative, or all weights are zero
+    """
+    return puckepy.statistics.spherical_variance(theta, phi, weights)
+
+
+class Fisher :
+    """ The Fisher (von Mises-Fisher) distribution of points on the Cremer-Pople sphere
+
+        The density at a point at an angle psi from the mean direction is kappa * exp(kappa * cos(psi)) / (4 pi sinh(kappa)).
+        A `kappa` of 0 is a uniform distribution; the larger `kappa`, the tighter the points around (`theta`, `phi`)
+        ---------------
+        self.theta : float      # degrees
+        self.phi : float        # degrees
+        self.kappa : float
+
+        Raises OutOfRangeError if `kappa` is negative
+
+        >>> fit = Fisher.fit([c[2] for c in cp6], [c[1] for c in cp6])
+        >>> print(fit.theta, fit.phi, fit.kappa)
+    """
+    theta : float
+    phi : float
+    kappa : float
+
+    def __new__(cls, theta: float, phi: float, kappa: float) :
+        return puckepy.statistics.Fisher(theta, phi, kappa)
+
+    @staticmethod
+    def fit(theta: list[float], phi: list[float], weights: list[float] | None = None) -> "Fisher" :
+        """ Fit the distribution to the (`theta`, `phi`) by maximum likelihood
+            The mean direction is the spherical mean of the points. Kappa solves coth(kappa) - 1 / kappa = 1 - the spherical variance,
+            which is infinite if all points are equal
+
+            Raises PuckepyError if no angles are queried, or if the amount of theta, phi and weights are not equal
+            Raises OutOfRangeError if a weight is negative, or all weights are zero
+        """
+        return puckepy.statistics.Fisher.fit(theta, phi, weights)
+
+    def pdf(self, theta: list[float], phi: list[float]) -> list[float] :
+        """ The probability density at every (theta, phi), per steradian
+            An infinite kappa puts all density at the mean direction : infinite there, and 0 elsewhere
+
+            Raises PuckepyError if the amount of theta and phi are not equal
+        """
+        return self.pdf(theta, phi)
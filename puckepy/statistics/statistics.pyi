@@ -0,0 +1,29 @@
+__all__: list[str]
+
+
+def circular_mean(angles: list[float], weights: list[float] | None = None) -> float : ...
+
+def circular_variance(angles: list[float], weights: list[float] | None = None) -> float : ...
+
+class VonMises :
+    mean: float
+    kappa: float
+
+    def __new__(cls, mean: float, kappa: float) -> VonMises : ...
+    @staticmethod
+    def fit(angles: list[float], weights: list[float] | None = None) -> VonMises : ...
+    def pdf(self, angles: list[float]) -> list[float] : ...
+
+def spherical_mean(theta: list[float], phi: list[float], weights: list[float] | None = None) -> tuple[float, float] : ...
+
+def spherical_variance(theta: list[float], phi: list[float], weights: list[float] | None = None) -> float : ...
+
+class Fisher :
+    theta: float
+    phi: float
+    kappa: float
+
+    def __new__(cls, theta: float, phi: float, kappa: float) -> Fisher : ...
+    @staticmethod
+    def fit(theta: list[float], phi: list[float], weights: list[float] | None = None) -> Fisher : ...
+    def pdf(self, theta: list[float], phi: list[float]) -> list[float] : ...
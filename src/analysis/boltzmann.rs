@@ -0,0 +1,152 @@
+use pyo3::{pyclass, pyfunction, PyResult};
+
+use crate::analysis::fes::thermal_energy;
+use crate::errors::{OutOfRangeError, PuckepyError, RingSizeError};
+use crate::formalism::iupac::{fivering_conformer, sixring_conformer, CP5_TO_AS_OFFSET};
+use crate::statistics::{circular, spherical};
+
+
+/// The thermally averaged pucker of an ensemble of conformers
+/// `populations` are the Boltzmann populations of the conformers, in the order they were queried
+/// `amplitude` is the population-weighted amplitude
+/// `phase_angle` is the circular mean of the phase angles, and `theta` the spherical mean together with the phase angle (CP6 only)
+/// `resultant_length` is the length of the mean direction, 1 for identical and 0 for uniformly spread phase angles
+/// `families` is the population of every IUPAC conformer, from high to low
+/// `sectors` is the population of the North/East/South/West quadrants of the pseudorotation wheel (CP5 and AS only)
+#[pyclass(get_all)]
+pub struct Ensemble {
+    pub populations: Vec<f64>,
+    pub amplitude: f64,
+    pub phase_angle: f64,
+    pub theta: Option<f64>,
+    pub resultant_length: f64,
+    pub families: Vec<(String, f64)>,
+    pub sectors: Option<Vec<(String, f64)>>,
+}
+
+/// Boltzmann-average the puckers of a set of conformers by their energies
+/// The `puckers` are those of the `formalism` : (amplitude, phase_angle) for "CP5" and "AS", (amplitude, phase_angle, theta) for "CP6"
+/// The energies are in `unit` ("kJ/mol" or "kcal/mol"), and only their differences matter
+#[pyfunction]
+#[pyo3(signature = (puckers, energies, formalism, temperature=298.15, unit="kJ/mol", atomnames=None))]
+pub fn boltzmann(puckers: Vec<Vec<f64>>, energies: Vec<f64>, formalism: &str, temperature: f64, unit: &str,
+                 atomnames: Option<Vec<String>>) -> PyResult<Ensemble> {
+
+    let size = match formalism {
+        "CP5" | "AS" => 2,
+        "CP6" => 3,
+        f => return Err(PuckepyError::new_err(format!("Formalism \"{}\" is not one of \"CP5\", \"AS\" or \"CP6\".", f)))
+    };
+
+    if puckers.is_empty() || puckers.len() != energies.len() {
+        return Err(PuckepyError::new_err(format!("An amount of {} puckers and {} energies has been queried. Expected an energy for every pucker.", puckers.len(), energies.len())))
+    };
+
+    if let Some(p) = puckers.iter().find(|p| p.len() != size) {
+        return Err(RingSizeError::new_err(format!("A pucker of {} values, not equal to {}, has been queried. Expected the coordinates of {}.", p.len(), size, formalism)))
+    };
+
+    if let Some(e) = energies.iter().find(|e| !e.is_finite()) {
+        return Err(OutOfRangeError::new_err(format!("The energy ({}) of a conformer is not finite.", e)))
+    };
+
+    if let Some(p) = puckers.iter().find(|p| p.iter().any(|v| !v.is_finite())) {
+        return Err(OutOfRangeError::new_err(format!("The pucker {:?} of a conformer is not finite.", p)))
+    };
+
+    let populations = populations(&energies, thermal_energy(temperature, unit)?);
+    let amplitude = puckers.iter().zip(populations.iter()).map(|(p, w)| p[0] * w).sum();
+
+    let (phase_angle, theta, resultant_length) = match formalism {
+        "CP6" => {
+            let theta: Vec<f64> = puckers.iter().map(|p| p[2]).collect();
+            let phi: Vec<f64> = puckers.iter().map(|p| p[1]).collect();
+            let (theta, phase_angle, length) = spherical::mean_direction(&theta, &phi, &populations);
+            (phase_angle, Some(theta), length)
+        },
+        _ => {
+            let (phase_angle, length) = circular::mean_direction(&puckers.iter().map(|p| p[1]).collect::<Vec<f64>>(), &populations);
+            (phase_angle.rem_euclid(360.), None, length)
+        },
+    };
+
+    let mut families = vec![];
+    let mut sectors = vec![];
+    for (p, w) in puckers.iter().zip(populations.iter()) {
+        let conformer = match formalism {
+            "CP5" => fivering_conformer(p[1] + CP5_TO_AS_OFFSET, atomnames.clone())?,
+            "AS" => fivering_conformer(p[1], atomnames.clone())?,
+            _ => sixring_conformer(p[1], p[2], atomnames.clone())?,
+        };
+
+        add_to(&mut families, conformer.label, *w);
+        if let Some(sector) = conformer.sector {
+            add_to(&mut sectors, sector, *w)
+        };
+    }
+    families.sort_by(|a, b| b.1.total_cmp(&a.1));
+    sectors.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    Ok(Ensemble {
+        populations,
+        amplitude,
+        phase_angle,
+        theta,
+        resultant_length,
+        families,
+        sectors: if formalism == "CP6" { None } else { Some(sectors) },
+    })
+}
+
+
+// exp(-(E - E_min) / RT), normalised to 1
+fn populations(energies: &[f64], rt: f64) -> Vec<f64> {
+    let minimum = energies.iter().cloned().fold(f64::INFINITY, f64::min);
+    let factors: Vec<f64> = energies.iter().map(|e| (-(e - minimum) / rt).exp()).collect();
+    let total: f64 = factors.iter().sum();
+    factors.iter().map(|f| f / total).collect()
+}
+
+fn add_to(populations: &mut Vec<(String, f64)>, key: String, weight: f64) {
+    match populations.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, p)) => *p += weight,
+        None => populations.push((key, weight)),
+    }
+}
+
+
+#[cfg(test)]
+mod test_boltzmann {
+
+    use super::*;
+
+    #[test]
+    fn populations_across_zero() {
+        // Two equal conformers on either side of P = 0, and a third one far higher in energy
+        let puckers = vec![vec![0.4, 350.], vec![0.4, 10.], vec![0.3, 180.]];
+        let ensemble = boltzmann(puckers, vec![0., 0., 100.], "AS", 298.15, "kJ/mol", None).unwrap();
+
+        assert!((ensemble.populations[0] - 0.5).abs() < 1e-12);
+        assert!(ensemble.phase_angle.min(360. - ensemble.phase_angle) < 1e-6);
+        assert!((ensemble.amplitude - 0.4).abs() < 1e-6);
+        assert_eq!(ensemble.sectors.unwrap()[0].0, "North");
+        assert!((ensemble.families.iter().map(|f| f.1).sum::<f64>() - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rt_ln_ten() {
+        // RT ln(10) apart is a population ratio of 10
+        let rt = thermal_energy(300., "kcal/mol").unwrap();
+        let ensemble = boltzmann(vec![vec![0.6, 0., 0.], vec![0.6, 0., 180.]], vec![0., rt * 10_f64.ln()], "CP6", 300., "kcal/mol", None).unwrap();
+        assert!((ensemble.populations[0] / ensemble.populations[1] - 10.).abs() < 1e-9);
+        assert_eq!(ensemble.theta, Some(0.));
+        assert_eq!(ensemble.families[0].0, "4C1");
+    }
+
+    #[test]
+    fn non_finite_conformers() {
+        assert!(boltzmann(vec![vec![0.4, 1.]], vec![f64::NAN], "AS", 298.15, "kJ/mol", None).is_err());
+        assert!(boltzmann(vec![vec![0.4, 1.], vec![0.4, 2.]], vec![f64::INFINITY; 2], "AS", 298.15, "kJ/mol", None).is_err());
+        assert!(boltzmann(vec![vec![0.4, f64::NAN]], vec![0.], "AS", 298.15, "kJ/mol", None).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use pyo3::{pyclass, pyfunction, PyResult};
+use rayon::prelude::*;
+
+use crate::errors::{OutOfRangeError, PuckepyError};
+
+
+/// The distance between two puckering coordinates, as returned by the formalisms
+///     "polar"  : (amplitude, phase_angle) of CP5 and AS, as points (amplitude cos P, amplitude sin P) on the pseudorotation wheel
+///     "sphere" : (amplitude, phase_angle, theta) of CP6, as points on the sphere of radius amplitude
+///     "torus"  : (phi, psi) of a peptide, on the periodic torus
+/// The angles are in degrees, the distances in the units of the amplitude or in degrees on the torus
+#[derive(Clone, Copy)]
+enum Metric {
+    Polar,
+    Sphere,
+    Torus,
+}
+
+impl Metric {
+
+    fn new(metric: &str) -> PyResult<Metric> {
+        match metric {
+            "polar" => Ok(Metric::Polar),
+            "sphere" => Ok(Metric::Sphere),
+            "torus" => Ok(Metric::Torus),
+            m => Err(PuckepyError::new_err(format!("Metric \"{}\" is not one of \"polar\", \"sphere\" or \"torus\".", m)))
+        }
+    }
+
+    fn dimension(&self) -> usize {
+        match self {
+            Metric::Sphere => 3,
+            _ => 2,
+        }
+    }
+
+    fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Metric::Polar => {
+                let [p, q] = [a, b].map(|x| {
+                    let (sin, cos) = x[1].to_radians().sin_cos();
+                    [x[0] * cos, x[0] * sin]
+                });
+                (p[0] - q[0]).hypot(p[1] - q[1])
+            },
+            Metric::Sphere => {
+                let [p, q] = [a, b].map(|x| {
+                    let (sin_p, cos_p) = x[1].to_radians().sin_cos();
+                    let (sin_t, cos_t) = x[2].to_radians().sin_cos();
+                    [x[0] * sin_t * cos_p, x[0] * sin_t * sin_p, x[0] * cos_t]
+                });
+                ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)).sqrt()
+            },
+            Metric::Torus => {
+                let [dx, dy] = [0, 1].map(|i| ((a[i] - b[i]) + 180.).rem_euclid(360.) - 180.);
+                dx.hypot(dy)
+            },
+        }
+    }
+
+    // The distance between every pair of points, after checking that every point has the dimension of the metric
+    fn distances(&self, points: &[Vec<f64>]) -> PyResult<Vec<Vec<f64>>> {
+
+        if points.is_empty() {
+            return Err(PuckepyError::new_err("No points have been queried."))
+        };
+
+        if let Some(p) = points.iter().find(|p| p.len() != self.dimension()) {
+            return Err(PuckepyError::new_err(format!("A point of {} values, not equal to {}, has been queried. Expected the coordinates of the formalism of the metric.", p.len(), self.dimension())))
+        };
+
+        Ok(points.par_iter().map(|a| points.iter().map(|b| self.distance(a, b)).collect()).collect())
+    }
+}
+
+
+/// The clusters of a set of points
+/// Clusters are numbered from large to small; a label of -1 is noise (DBSCAN)
+/// The representative of every cluster is its medoid, the point with the smallest sum of distances to the rest of its cluster
+#[pyclass(get_all)]
+pub struct Clusters {
+    pub labels: Vec<i64>,
+    pub sizes: Vec<usize>,
+    pub representatives: Vec<usize>,
+}
+
+impl Clusters {
+
+    fn from_labels(labels: &[i64], distances: &[Vec<f64>]) -> Clusters {
+
+        let n_clusters = labels.iter().cloned().max().map_or(0, |m| (m + 1).max(0) as usize);
+        let members: Vec<Vec<usize>> = (0..n_clusters).map(|c| {
+            (0..labels.len()).filter(|i| labels[*i] == c as i64).collect()
+        }).collect();
+
+        // From large to small, and in order of the first point of every cluster
+        let mut order: Vec<usize> = (0..n_clusters).filter(|c| !members[*c].is_empty()).collect();
+        order.sort_by(|a, b| members[*b].len().cmp(&members[*a].len()).then(members[*a][0].cmp(&members[*b][0])));
+
+        let mut relabelled = vec![-1; labels.len()];
+        for (new, old) in order.iter().enumerate() {
+            for i in members[*old].iter() {
+                relabelled[*i] = new as i64
+            }
+        }
+
+        Clusters {
+            labels: relabelled,
+            sizes: order.iter().map(|c| members[*c].len()).collect(),
+            representatives: order.iter().map(|c| medoid(&members[*c], distances)).collect(),
+        }
+    }
+}
+
+
+/// Cluster the points around `k` medoids
+/// The medoids are initialised by the greedy BUILD step of PAM, and updated until every point
+/// is assigned to its nearest medoid and every medoid is the medoid of its cluster. This is deterministic
+#[pyfunction]
+#[pyo3(signature = (points, k, metric, max_iterations=100))]
+pub fn kmedoids(points: Vec<Vec<f64>>, k: usize, metric: &str, max_iterations: usize) -> PyResult<Clusters> {
+
+    let distances = Metric::new(metric)?.distances(&points)?;
+    let n = points.len();
+    if k == 0 || k > n {
+        return Err(OutOfRangeError::new_err(format!("The amount of clusters ({}) should be within the range of 1 -> {}.", k, n)))
+    };
+
+    // Duplicate points cannot be split over clusters
+    let distinct = (0..n).filter(|i| (0..*i).all(|j| distances[*i][j] > 0.)).count();
+    if k > distinct {
+        return Err(OutOfRangeError::new_err(format!("The amount of clusters ({}) should not exceed the amount of distinct points ({}).", k, distinct)))
+    };
+
+    // BUILD : add the point that lowers the total distance to the nearest medoid the most
+    let mut nearest = vec![f64::INFINITY; n];
+    let mut medoids: Vec<usize> = vec![];
+    while medoids.len() < k {
+        let best = (0..n).filter(|c| !medoids.contains(c))
+            .map(|c| (c, (0..n).map(|i| nearest[i].min(distances[c][i])).sum::<f64>()))
+            .fold((0, f64::INFINITY), |best, (c, cost)| if cost < best.1 { (c, cost) } else { best });
+
+        medoids.push(best.0);
+        for i in 0..n {
+            nearest[i] = nearest[i].min(distances[best.0][i])
+        }
+    }
+
+    let mut labels = assign(&medoids, &distances);
+    for _ in 0..max_iterations {
+        // A medoid without members keeps its place
+        let updated: Vec<usize> = (0..k).map(|c| {
+            let members: Vec<usize> = (0..n).filter(|i| labels[*i] == c as i64).collect();
+            match members.is_empty() {
+                true => medoids[c],
+                false => medoid(&members, &distances),
+            }
+        }).collect();
+
+        if updated == medoids {
+            break
+        };
+
+        medoids = updated;
+        labels = assign(&medoids, &distances);
+    }
+
+    Ok(Clusters::from_labels(&labels, &distances))
+}
+
+/// Cluster the points by density (DBSCAN)
+/// A point with at least `min_points` points (itself included) within `epsilon` is a core point.
+/// Clusters grow from the core points over every point within `epsilon`; the other points are noise
+#[pyfunction]
+#[pyo3(signature = (points, epsilon, min_points, metric))]
+pub fn dbscan(points: Vec<Vec<f64>>, epsilon: f64, min_points: usize, metric: &str) -> PyResult<Clusters> {
+
+    let distances = Metric::new(metric)?.distances(&points)?;
+    if epsilon.is_nan() || epsilon <= 0. {
+        return Err(OutOfRangeError::new_err(format!("The epsilon ({}) should be positive.", epsilon)))
+    };
+
+    let n = points.len();
+    let neighbours: Vec<Vec<usize>> = distances.iter().map(|row| (0..n).filter(|j| row[*j] <= epsilon).collect()).collect();
+    let core: Vec<bool> = neighbours.iter().map(|nb| nb.len() >= min_points).collect();
+
+    let mut labels = vec![-1_i64; n];
+    let mut cluster = 0;
+    for start in 0..n {
+        if !core[start] || labels[start] != -1 {
+            continue
+        };
+
+        labels[start] = cluster;
+        let mut queue = vec![start];
+        while let Some(i) = queue.pop() {
+            if !core[i] {
+                continue
+            };
+            for j in neighbours[i].iter() {
+                if labels[*j] == -1 {
+                    labels[*j] = cluster;
+                    queue.push(*j);
+                };
+            }
+        }
+        cluster += 1;
+    }
+
+    Ok(Clusters::from_labels(&labels, &distances))
+}
+
+/// Cluster the points hierarchically, by merging the two nearest clusters until
+/// `n_clusters` remain, or until the nearest clusters lie further apart than the `threshold`
+/// The distance between two clusters is their `linkage` : "single" (nearest points), "complete" (furthest points)
+/// or "average" (mean distance between their points)
+#[pyfunction]
+#[pyo3(signature = (points, metric, n_clusters=None, threshold=None, linkage="average"))]
+pub fn hierarchical(points: Vec<Vec<f64>>, metric: &str, n_clusters: Option<usize>, threshold: Option<f64>, linkage: &str) -> PyResult<Clusters> {
+
+    let distances = Metric::new(metric)?.distances(&points)?;
+    let n = points.len();
+
+    let linkage = match linkage {
+        "single" | "complete" | "average" => linkage,
+        l => return Err(PuckepyError::new_err(format!("Linkage \"{}\" is not one of \"single\", \"complete\" or \"average\".", l)))
+    };
+
+    let stop = match (n_clusters, threshold) {
+        (Some(k), None) if (1..=n).contains(&k) => Stop::Clusters(k),
+        (None, Some(t)) if t >= 0. => Stop::Threshold(t),
+        (Some(k), None) => return Err(OutOfRangeError::new_err(format!("The amount of clusters ({}) should be within the range of 1 -> {}.", k, n))),
+        (None, Some(t)) => return Err(OutOfRangeError::new_err(format!("The threshold ({}) should not be negative.", t))),
+        _ => return Err(PuckepyError::new_err("Query either an amount of clusters or a threshold.")),
+    };
+
+    // Replay the merges from near to far, as a union-find of the points
+    let mut merges = nearest_neighbour_chain(distances.clone(), linkage);
+    merges.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut remaining = n;
+    for (a, b, height) in merges {
+        let done = match stop {
+            Stop::Clusters(k) => remaining <= k,
+            Stop::Threshold(t) => height > t,
+        };
+        if done {
+            break
+        };
+
+        let (root_a, root_b) = (root(&parent, a), root(&parent, b));
+        parent[root_b] = root_a;
+        remaining -= 1;
+    }
+
+    let roots: Vec<usize> = (0..n).map(|i| root(&parent, i)).collect();
+    let labels: Vec<i64> = roots.iter().map(|r| roots.iter().position(|x| x == r).unwrap() as i64).collect();
+
+    Ok(Clusters::from_labels(&labels, &distances))
+}
+
+
+enum Stop {
+    Clusters(usize),
+    Threshold(f64),
+}
+
+// The merges (cluster a, cluster b, distance) of agglomerative clustering, by the nearest-neighbour chain
+// Every merge is named by a point of either cluster. The merges are not ordered by their distance
+fn nearest_neighbour_chain(mut d: Vec<Vec<f64>>, linkage: &str) -> Vec<(usize, usize, f64)> {
+
+    let n = d.len();
+    let mut size = vec![1_usize; n];
+    let mut active: Vec<bool> = vec![true; n];
+    let mut chain: Vec<usize> = vec![];
+    let mut merges = vec![];
+
+    while merges.len() + 1 < n {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|a| *a).unwrap())
+        };
+
+        let a = *chain.last().unwrap();
+        let previous = if chain.len() > 1 { Some(chain[chain.len() - 2]) } else { None };
+
+        // The nearest active cluster, preferring the previous link of the chain on a tie
+        let mut nearest = previous.unwrap_or(usize::MAX);
+        let mut nearest_distance = previous.map_or(f64::INFINITY, |p| d[a][p]);
+        for c in (0..n).filter(|c| active[*c] && *c != a) {
+            if d[a][c] < nearest_distance {
+                nearest = c;
+                nearest_distance = d[a][c];
+            };
+        }
+
+        if Some(nearest) != previous {
+            chain.push(nearest);
+            continue
+        };
+
+        // a and its previous link are reciprocal nearest neighbours; merge b into a (Lance-Williams)
+        let b = nearest;
+        chain.truncate(chain.len() - 2);
+        merges.push((a, b, nearest_distance));
+
+        for c in (0..n).filter(|c| active[*c] && *c != a && *c != b) {
+            let merged = match linkage {
+                "single" => d[a][c].min(d[b][c]),
+                "complete" => d[a][c].max(d[b][c]),
+                _ => ((size[a] as f64 * d[a][c]) + (size[b] as f64 * d[b][c])) / (size[a] + size[b]) as f64,
+            };
+            d[a][c] = merged;
+            d[c][a] = merged;
+        }
+        size[a] += size[b];
+        active[b] = false;
+    }
+
+    merges
+}
+
+fn root(parent: &[usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        i = parent[i]
+    }
+    i
+}
+
+fn assign(medoids: &[usize], distances: &[Vec<f64>]) -> Vec<i64> {
+    (0..distances.len()).map(|i| {
+        (0..medoids.len()).fold(0, |best, c| if distances[medoids[c]][i] < distances[medoids[best]][i] { c } else { best }) as i64
+    }).collect()
+}
+
+// The member with the smallest sum of distances to the other members
+fn medoid(members: &[usize], distances: &[Vec<f64>]) -> usize {
+    members.iter()
+        .map(|m| (*m, members.iter().map(|o| distances[*m][*o]).sum::<f64>()))
+        .fold((members[0], f64::INFINITY), |best, (m, cost)| if cost < best.1 { (m, cost) } else { best })
+        .0
+}
+
+
+#[cfg(test)]
+mod test_clustering {
+
+    use super::*;
+
+    // Two groups of phase angles on either side of 0/360, and one around 180
+    fn pseudorotation() -> Vec<Vec<f64>> {
+        vec![vec![0.4, 355.], vec![0.4, 5.], vec![0.38, 2.], vec![0.4, 180.], vec![0.41, 175.]]
+    }
+
+    #[test]
+    fn periodic_metrics() {
+        assert!((Metric::Torus.distance(&[-179., 179.], &[179., -179.]) - 8_f64.sqrt()).abs() < 1e-12);
+        assert!((Metric::Polar.distance(&[1., 359.], &[1., 1.]) - (2. * 1_f64.to_radians().sin())).abs() < 1e-12);
+        assert!(Metric::Sphere.distance(&[0.6, 0., 0.], &[0.6, 180., 0.]) < 1e-12);
+    }
+
+    #[test]
+    fn every_method_agrees() {
+        let expected = vec![0, 0, 0, 1, 1];
+        assert_eq!(kmedoids(pseudorotation(), 2, "polar", 100).unwrap().labels, expected);
+        assert_eq!(dbscan(pseudorotation(), 0.1, 2, "polar").unwrap().labels, expected);
+        for linkage in ["single", "complete", "average"] {
+            assert_eq!(hierarchical(pseudorotation(), "polar", Some(2), None, linkage).unwrap().labels, expected);
+            assert_eq!(hierarchical(pseudorotation(), "polar", None, Some(0.1), linkage).unwrap().labels, expected);
+        }
+
+        let clusters = kmedoids(pseudorotation(), 2, "polar", 100).unwrap();
+        assert_eq!(clusters.sizes, vec![3, 2]);
+        assert_eq!(clusters.representatives[0], 2);
+    }
+
+    #[test]
+    fn duplicate_points() {
+        let duplicates = vec![vec![0.4, 5.], vec![0.4, 5.], vec![0.3, 180.]];
+        assert!(kmedoids(duplicates[..2].to_vec(), 2, "polar", 100).is_err());
+
+        let clusters = kmedoids(duplicates, 2, "polar", 100).unwrap();
+        assert_eq!(clusters.labels, vec![0, 0, 1]);
+        assert_eq!(clusters.sizes, vec![2, 1]);
+    }
+
+    #[test]
+    fn noise() {
+        let mut points = pseudorotation();
+        points.push(vec![0.4, 90.]);
+        let clusters = dbscan(points, 0.1, 2, "polar").unwrap();
+        assert_eq!(clusters.labels[5], -1);
+        assert_eq!(clusters.sizes.iter().sum::<usize>(), 5);
+    }
+}
@@ -0,0 +1,303 @@
+use pyo3::{pyclass, pymethods, PyResult};
+use rayon::prelude::*;
+
+use crate::conf_sampling::sixring::{equidistance_partition, TWOPI};
+use crate::errors::{OutOfRangeError, PuckepyError};
+
+
+/// The free energy surface on the Cremer-Pople sphere, from the (theta, phi) of every frame, in radians
+/// The sphere is binned by the equal-area partition of the grid of SixringAxes(amount) : every bin is centered on a point
+/// of that grid, and ring m of the grid spans theta [m, m + 1] * pi / rings.
+/// Without a bandwidth, the frames are histogrammed. With a bandwidth (radians), every frame is spread over the bins
+/// by a von Mises-Fisher kernel of concentration 1 / bandwidth^2
+/// The free energy is -RT ln of the density of every bin, relative to the densest bin. Empty bins are infinite
+#[pyclass(get_all)]
+pub struct SphereFES {
+    pub theta: Vec<f64>,
+    pub phi: Vec<f64>,
+    pub population: Vec<f64>,
+    pub free_energy: Vec<f64>,
+}
+
+#[pymethods]
+impl SphereFES {
+
+    #[new]
+    #[pyo3(signature = (theta, phi, amount=630, weights=None, bandwidth=None, temperature=298.15, unit="kJ/mol"))]
+    #[allow(clippy::too_many_arguments)] // every option of the surface is a keyword argument in Python
+    fn new(theta: Vec<f64>, phi: Vec<f64>, amount: usize, weights: Option<Vec<f64>>, bandwidth: Option<f64>,
+           temperature: f64, unit: &str) -> PyResult<Self> {
+
+        let weights = frame_weights(&theta, &phi, weights)?;
+        let rt = thermal_energy(temperature, unit)?;
+        check_bandwidth(bandwidth)?;
+        if amount == 0 {
+            return Err(OutOfRangeError::new_err("The amount of bins (0) should be at least 1."))
+        };
+
+        let rings = equidistance_partition(amount);
+        let d_theta = std::f64::consts::PI / rings.len() as f64;
+
+        let mut centers: Vec<[f64; 2]> = vec![];
+        let mut area: Vec<f64> = vec![];
+        for (m, points) in rings.iter().enumerate() {
+            let band = (m as f64 * d_theta).cos() - ((m + 1) as f64 * d_theta).cos();
+            for n in 0..*points {
+                centers.push([(m as f64 + 0.5) * d_theta, TWOPI * n as f64 / *points as f64]);
+                area.push(band * TWOPI / *points as f64);
+            }
+        }
+
+        let population = match bandwidth {
+            None => {
+                // The first bin of every ring
+                let offsets: Vec<usize> = rings.iter().scan(0, |sum, points| { *sum += points; Some(*sum - points) }).collect();
+
+                let mut histogram = vec![0.; centers.len()];
+                for ((t, p), w) in theta.iter().zip(phi.iter()).zip(weights.iter()) {
+                    let m = ((t / d_theta).floor().max(0.) as usize).min(rings.len() - 1);
+                    let n = (p.rem_euclid(TWOPI) * rings[m] as f64 / TWOPI).round() as usize % rings[m];
+                    histogram[offsets[m] + n] += w;
+                }
+                histogram
+            },
+            Some(h) => {
+                let kappa = h.powi(-2);
+                let frames: Vec<[f64; 3]> = theta.iter().zip(phi.iter()).map(|(t, p)| unit_vector(*t, *p)).collect();
+
+                // exp(kappa (cos(angle) - 1)) keeps the kernel finite for large concentrations
+                centers.par_iter().zip(area.par_iter()).map(|(c, a)| {
+                    let u = unit_vector(c[0], c[1]);
+                    let density: f64 = frames.iter().zip(weights.iter())
+                        .map(|(f, w)| w * (kappa * ((u[0] * f[0]) + (u[1] * f[1]) + (u[2] * f[2]) - 1.)).exp())
+                        .sum();
+                    density * a
+                }).collect()
+            },
+        };
+
+        let population = normalise(population)?;
+        let free_energy = free_energies(&population, &area, rt);
+        let (theta, phi) = centers.iter().map(|c| (c[0], c[1])).unzip();
+
+        Ok(SphereFES { theta, phi, population, free_energy })
+    }
+}
+
+
+/// The free energy surface on a plane of two angles, from the (x, y) of every frame
+/// e.g. the (phi, psi) of a peptide, or the (phase angle, amplitude) of AS and CP5
+/// The plane is binned in `bins` x `y_bins` bins over the `x_range` and `y_range`, for every x bin all y bins.
+/// A periodic axis wraps the frames onto its range; a non-periodic axis drops the frames outside of its range
+/// Without a bandwidth, the frames are histogrammed. With a bandwidth (in the units of the axes), every frame is spread
+/// over the bins by a Gaussian kernel, which wraps around the periodic axes
+/// The free energy is -RT ln of the population of every bin, relative to the most populated bin. Empty bins are infinite
+#[pyclass(get_all)]
+pub struct PlaneFES {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub population: Vec<f64>,
+    pub free_energy: Vec<f64>,
+}
+
+#[pymethods]
+impl PlaneFES {
+
+    #[new]
+    #[pyo3(signature = (x, y, bins=36, y_bins=None, x_range=(-180., 180.), y_range=None, periodic=(true, true),
+                        weights=None, bandwidth=None, temperature=298.15, unit="kJ/mol"))]
+    #[allow(clippy::too_many_arguments)] // every option of the surface is a keyword argument in Python
+    fn new(x: Vec<f64>, y: Vec<f64>, bins: usize, y_bins: Option<usize>, x_range: (f64, f64), y_range: Option<(f64, f64)>,
+           periodic: (bool, bool), weights: Option<Vec<f64>>, bandwidth: Option<f64>, temperature: f64, unit: &str) -> PyResult<Self> {
+
+        let weights = frame_weights(&x, &y, weights)?;
+        let rt = thermal_energy(temperature, unit)?;
+        check_bandwidth(bandwidth)?;
+
+        let axes = [
+            BinnedAxis::new(bins, x_range, periodic.0, "x")?,
+            BinnedAxis::new(y_bins.unwrap_or(bins), y_range.unwrap_or(x_range), periodic.1, "y")?,
+        ];
+
+        // Wrap the frames onto the periodic axes, and drop the frames outside of the other axes
+        let frames: Vec<([f64; 2], f64)> = x.iter().zip(y.iter()).zip(weights.iter())
+            .filter_map(|((x, y), w)| Some(([axes[0].wrap(*x)?, axes[1].wrap(*y)?], *w)))
+            .collect();
+
+        let centers: Vec<[f64; 2]> = axes[0].centers().into_iter()
+            .flat_map(|cx| axes[1].centers().into_iter().map(move |cy| [cx, cy]))
+            .collect();
+
+        let population = match bandwidth {
+            None => {
+                let mut histogram = vec![0.; centers.len()];
+                for (f, w) in frames.iter() {
+                    histogram[(axes[0].bin(f[0]) * axes[1].bins) + axes[1].bin(f[1])] += w;
+                }
+                histogram
+            },
+            Some(h) => centers.par_iter().map(|c| {
+                frames.iter()
+                    .map(|(f, w)| w * (-(axes[0].difference(c[0], f[0]).powi(2) + axes[1].difference(c[1], f[1]).powi(2)) / (2. * h.powi(2))).exp())
+                    .sum()
+            }).collect(),
+        };
+
+        let population = normalise(population)?;
+        let free_energy = free_energies(&population, &vec![1.; centers.len()], rt);
+        let (x, y) = centers.iter().map(|c| (c[0], c[1])).unzip();
+
+        Ok(PlaneFES { x, y, population, free_energy })
+    }
+}
+
+
+/// An axis of the plane, divided in bins of equal width
+struct BinnedAxis {
+    bins: usize,
+    range: (f64, f64),
+    periodic: bool,
+}
+
+impl BinnedAxis {
+
+    fn new(bins: usize, range: (f64, f64), periodic: bool, name: &str) -> PyResult<BinnedAxis> {
+        if !(bins > 0 && range.0 < range.1) {
+            return Err(OutOfRangeError::new_err(format!("The {} axis should have at least one bin ({}) and an increasing range ({}, {}).", name, bins, range.0, range.1)))
+        };
+        Ok(BinnedAxis { bins, range, periodic })
+    }
+
+    fn width(&self) -> f64 {
+        (self.range.1 - self.range.0) / self.bins as f64
+    }
+
+    fn centers(&self) -> Vec<f64> {
+        (0..self.bins).map(|i| self.range.0 + ((i as f64 + 0.5) * self.width())).collect()
+    }
+
+    // None if the value lies outside of a non-periodic axis
+    fn wrap(&self, value: f64) -> Option<f64> {
+        match self.periodic {
+            true => Some(self.range.0 + (value - self.range.0).rem_euclid(self.range.1 - self.range.0)),
+            false => (self.range.0..=self.range.1).contains(&value).then_some(value),
+        }
+    }
+
+    fn bin(&self, value: f64) -> usize {
+        (((value - self.range.0) / self.width()).floor() as usize).min(self.bins - 1)
+    }
+
+    // The shortest difference between two values, around the axis if it is periodic
+    fn difference(&self, a: f64, b: f64) -> f64 {
+        let period = self.range.1 - self.range.0;
+        match self.periodic {
+            true => ((a - b) + (period / 2.)).rem_euclid(period) - (period / 2.),
+            false => a - b,
+        }
+    }
+}
+
+
+/// The gas constant in the queried unit of energy, per Kelvin
+pub fn gas_constant(unit: &str) -> PyResult<f64> {
+    match unit {
+        "kJ/mol" => Ok(8.314_462_618e-3),
+        "kcal/mol" => Ok(1.987_204_259e-3),
+        u => Err(PuckepyError::new_err(format!("Unit \"{}\" is not one of \"kJ/mol\" or \"kcal/mol\".", u))),
+    }
+}
+
+/// RT in the queried unit of energy
+pub fn thermal_energy(temperature: f64, unit: &str) -> PyResult<f64> {
+    if temperature.is_nan() || temperature <= 0. {
+        return Err(OutOfRangeError::new_err(format!("The temperature ({} K) should be positive.", temperature)))
+    };
+    Ok(gas_constant(unit)? * temperature)
+}
+
+// The weight of every frame, which defaults to 1
+// Non-finite frames would otherwise be counted in the first bin
+fn frame_weights(x: &[f64], y: &[f64], weights: Option<Vec<f64>>) -> PyResult<Vec<f64>> {
+
+    let weights = weights.unwrap_or(vec![1.; x.len()]);
+    if x.len() != y.len() || x.len() != weights.len() {
+        return Err(PuckepyError::new_err(format!("The amount of x ({}), y ({}) and weights ({}) are not equal. Expected a value for every frame.", x.len(), y.len(), weights.len())))
+    };
+
+    if let Some(i) = (0..x.len()).find(|i| !x[*i].is_finite() || !y[*i].is_finite()) {
+        return Err(OutOfRangeError::new_err(format!("The frame ({}, {}) at index {} is not finite.", x[i], y[i], i)))
+    };
+
+    if weights.iter().any(|w| w.is_nan() || *w < 0.) {
+        return Err(OutOfRangeError::new_err("The weights of the frames should not be negative."))
+    };
+
+    Ok(weights)
+}
+
+fn check_bandwidth(bandwidth: Option<f64>) -> PyResult<()> {
+    match bandwidth {
+        Some(h) if h.is_nan() || h <= 0. => Err(OutOfRangeError::new_err(format!("The bandwidth ({}) should be positive.", h))),
+        _ => Ok(()),
+    }
+}
+
+fn normalise(population: Vec<f64>) -> PyResult<Vec<f64>> {
+    let total: f64 = population.iter().sum();
+    if total <= 0. {
+        return Err(PuckepyError::new_err("No frame with a weight lies within the bins."))
+    };
+    Ok(population.iter().map(|p| p / total).collect())
+}
+
+// -RT ln(density / maximal density), where the density is the population per area. Empty bins are infinite
+fn free_energies(population: &[f64], area: &[f64], rt: f64) -> Vec<f64> {
+    let density: Vec<f64> = population.iter().zip(area.iter()).map(|(p, a)| p / a).collect();
+    let maximum = density.iter().cloned().fold(0., f64::max);
+    density.iter().map(|d| rt * (maximum / d).ln()).collect()
+}
+
+fn unit_vector(theta: f64, phi: f64) -> [f64; 3] {
+    let (sin_t, cos_t) = theta.sin_cos();
+    let (sin_p, cos_p) = phi.sin_cos();
+    [sin_t * cos_p, sin_t * sin_p, cos_t]
+}
+
+
+#[cfg(test)]
+mod test_fes {
+
+    use super::*;
+
+    #[test]
+    fn equal_area_bins() {
+        let fes = SphereFES::new(vec![0.3, 2.], vec![1., 4.], 630, None, None, 300., "kJ/mol").unwrap();
+        assert_eq!(fes.theta.len(), fes.free_energy.len());
+        assert_eq!(fes.population.iter().filter(|p| **p > 0.).count(), 2);
+
+        // A uniform kernel density over the sphere is flat
+        let (theta, phi): (Vec<f64>, Vec<f64>) = crate::conf_sampling::sequences::Sequence::Fibonacci.unit_sphere(2000).iter().map(|p| (p[0], p[1])).unzip();
+        let fes = SphereFES::new(theta, phi, 100, None, Some(0.3), 300., "kJ/mol").unwrap();
+        assert!(fes.free_energy.iter().all(|g| *g < 0.1));
+
+        assert!(SphereFES::new(vec![0.3], vec![1.], 0, None, None, 300., "kJ/mol").is_err());
+        assert!(SphereFES::new(vec![f64::NAN], vec![0.], 630, None, None, 300., "kJ/mol").is_err());
+    }
+
+    #[test]
+    fn periodic_plane() {
+        // -179 degrees wraps onto 181, the same bin as 189
+        let fes = PlaneFES::new(vec![181., 189., -179.], vec![0., 0., 0.], 36, Some(1), (0., 360.), Some((-10., 10.)), (true, false),
+                                None, None, 300., "kcal/mol").unwrap();
+        assert_eq!(fes.population.iter().cloned().fold(0., f64::max), 1.);
+
+        // Twice the population is RT ln(2) lower in free energy
+        let fes = PlaneFES::new(vec![5., 5., 15.], vec![0., 0., 0.], 2, Some(1), (0., 20.), None, (false, false),
+                                None, None, 300., "kJ/mol").unwrap();
+        assert!((fes.free_energy[1] - (gas_constant("kJ/mol").unwrap() * 300. * 2_f64.ln())).abs() < 1e-12);
+
+        assert!(PlaneFES::new(vec![f64::NAN], vec![0.], 36, None, (-180., 180.), None, (true, true), None, None, 300., "kJ/mol").is_err());
+        assert!(PlaneFES::new(vec![0.], vec![f64::INFINITY], 36, None, (-180., 180.), None, (true, true), None, None, 300., "kJ/mol").is_err());
+    }
+}
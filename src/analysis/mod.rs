@@ -0,0 +1,8 @@
+//! Analysis of the energies and populations that come back from a conformational sampling
+
+
+pub mod pes; // interpolate potential energy surfaces on the sphere and the torus
+pub mod fes; // free energy surfaces from the populations of the frames of a trajectory
+pub mod clustering; // cluster conformers by their puckering coordinates
+pub mod boltzmann; // thermally average the puckers of a set of conformers
+mod stationary; // locate the minima and saddle points of a smooth surface
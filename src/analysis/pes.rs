@@ -0,0 +1,451 @@
+use pyo3::{pyclass, pymethods, PyResult};
+use nalgebra::{DMatrix, DVector};
+
+use crate::analysis::stationary::{classify_discrete, newton, Stationary};
+use crate::conf_sampling::{sequences::Sequence, sixring::TWOPI};
+use crate::errors::{OutOfRangeError, PuckepyError};
+use crate::geometry::fundamental_ops::{cross_product, dot_product, normalise_vector};
+use crate::statistics::circular::wrap_degrees;
+
+// The Fibonacci points, and the amount of nearest neighbours of every point, that seed the stationary points on the sphere
+const SPHERE_SEEDS: usize = 2000;
+const SPHERE_NEIGHBOURS: usize = 6;
+// The grid that seeds the stationary points on the torus, every 2 degrees
+const TORUS_SEEDS: usize = 180;
+// The step of the finite differences on the sphere (radians)
+const STEP: f64 = 1e-4;
+// Stationary points closer than this (radians) are the same point
+const SAME_POINT: f64 = 1e-3;
+
+
+/// A potential energy surface on the Cremer-Pople sphere, interpolated from the energies at scattered (theta, phi)
+/// Radial basis functions on the chord between two points of the unit sphere, the inverse multiquadric 1 / sqrt(r^2 + shape^2),
+/// plus a constant. A `smoothing` of 0 interpolates the energies exactly; a larger smoothing trades exactness for a smoother surface.
+/// The `shape` defaults to the median distance of every point to its nearest neighbour
+/// theta and phi are in radians, as SixringAxes
+#[pyclass]
+pub struct SpherePES {
+    nodes: Vec<[f64; 3]>,
+    weights: Vec<f64>,
+    constant: f64,
+    #[pyo3(get)]
+    shape: f64,
+}
+
+#[pymethods]
+impl SpherePES {
+
+    #[new]
+    #[pyo3(signature = (theta, phi, energies, shape=None, smoothing=0.))]
+    fn new(theta: Vec<f64>, phi: Vec<f64>, energies: Vec<f64>, shape: Option<f64>, smoothing: f64) -> PyResult<Self> {
+
+        check_points(theta.len(), phi.len(), energies.len(), 3)?;
+        if smoothing.is_nan() || smoothing < 0. || shape.is_some_and(|s| s.is_nan() || s <= 0.) {
+            return Err(OutOfRangeError::new_err(format!("The shape ({:?}) should be positive and the smoothing ({}) should not be negative.", shape, smoothing)))
+        };
+
+        let nodes: Vec<[f64; 3]> = theta.iter().zip(phi.iter()).map(|(t, p)| unit_vector(*t, *p)).collect();
+        let shape = shape.unwrap_or_else(|| nearest_neighbour_chord(&nodes));
+
+        // [ Phi + smoothing * I   1 ] [ weights  ]   [ energies ]
+        // [ 1^T                   0 ] [ constant ] = [ 0        ]
+        let n = nodes.len();
+        let system = DMatrix::from_fn(n + 1, n + 1, |i, j| match (i < n, j < n) {
+            (true, true) => kernel(chord(nodes[i], nodes[j]), shape) + if i == j { smoothing } else { 0. },
+            (false, false) => 0.,
+            _ => 1.,
+        });
+        let rhs = DVector::from_iterator(n + 1, energies.iter().cloned().chain([0.]));
+
+        let solution = match system.lu().solve(&rhs) {
+            Some(s) if s.iter().all(|x| x.is_finite()) => s,
+            _ => return Err(PuckepyError::new_err("The interpolation of the energies is singular. Remove the points that coincide, or set a smoothing.")),
+        };
+
+        Ok(SpherePES { nodes, weights: solution.rows(0, n).iter().cloned().collect(), constant: solution[n], shape })
+    }
+
+    /// The interpolated energy at (theta, phi)
+    fn energy(&self, theta: f64, phi: f64) -> f64 {
+        self.at(unit_vector(theta, phi))
+    }
+
+    /// The interpolated energies at every (theta, phi)
+    fn evaluate(&self, theta: Vec<f64>, phi: Vec<f64>) -> PyResult<Vec<f64>> {
+        check_points(theta.len(), phi.len(), theta.len(), 0)?;
+        Ok(theta.iter().zip(phi.iter()).map(|(t, p)| self.energy(*t, *p)).collect())
+    }
+
+    /// The derivatives (dE/dtheta, dE/dphi) of the energy at (theta, phi), per radian
+    fn gradient(&self, theta: f64, phi: f64) -> (f64, f64) {
+        let (sin_t, cos_t) = theta.sin_cos();
+        let (sin_p, cos_p) = phi.sin_cos();
+
+        let g = self.gradient_3d(unit_vector(theta, phi));
+        let e_theta = [cos_t * cos_p, cos_t * sin_p, -sin_t];
+        let e_phi = [-sin_t * sin_p, sin_t * cos_p, 0.];
+
+        (dot_product(g, e_theta), dot_product(g, e_phi))
+    }
+
+    /// The local minima of the surface, as (theta, phi, energy) from low to high energy
+    fn minima(&self) -> Vec<(f64, f64, f64)> {
+        self.stationary_points(Stationary::Minimum)
+    }
+
+    /// The saddle points of the surface, as (theta, phi, energy) from low to high energy
+    fn saddle_points(&self) -> Vec<(f64, f64, f64)> {
+        self.stationary_points(Stationary::Saddle)
+    }
+}
+
+impl SpherePES {
+
+    fn at(&self, u: [f64; 3]) -> f64 {
+        self.nodes.iter().zip(self.weights.iter())
+            .map(|(x, w)| w * kernel(chord(u, *x), self.shape))
+            .sum::<f64>() + self.constant
+    }
+
+    // The gradient in 3D, of the interpolation as a function of u
+    fn gradient_3d(&self, u: [f64; 3]) -> [f64; 3] {
+        let mut g = [0.; 3];
+        for (x, w) in self.nodes.iter().zip(self.weights.iter()) {
+            let factor = -w / (chord(u, *x).powi(2) + self.shape.powi(2)).powf(1.5);
+            for i in 0..3 {
+                g[i] += factor * (u[i] - x[i])
+            }
+        }
+        g
+    }
+
+    // The gradient in the tangent plane of u, in the tangent basis of u
+    fn tangent_gradient(&self, u: [f64; 3], basis: [[f64; 3]; 2]) -> [f64; 2] {
+        let g = self.gradient_3d(u);
+        let radial = dot_product(g, u);
+        let tangent = [0, 1, 2].map(|i| g[i] - (radial * u[i]));
+        basis.map(|e| dot_product(tangent, e))
+    }
+
+    // The gradient and the Hessian in the tangent plane of u, the latter by central differences along great circles
+    fn derivatives(&self, u: [f64; 3]) -> ([f64; 2], [[f64; 2]; 2]) {
+        let basis = tangent_basis(u);
+        let gradient = self.tangent_gradient(u, basis);
+
+        let mut hessian = [[0.; 2]; 2];
+        for (j, e) in basis.iter().enumerate() {
+            let forward = self.tangent_gradient(exponential_map(u, e.map(|x| x * STEP)), basis);
+            let backward = self.tangent_gradient(exponential_map(u, e.map(|x| -x * STEP)), basis);
+            for i in 0..2 {
+                hessian[i][j] = (forward[i] - backward[i]) / (2. * STEP)
+            }
+        }
+        let mixed = (hessian[0][1] + hessian[1][0]) / 2.;
+        hessian[0][1] = mixed;
+        hessian[1][0] = mixed;
+
+        (gradient, hessian)
+    }
+
+    fn stationary_points(&self, kind: Stationary) -> Vec<(f64, f64, f64)> {
+
+        let seeds: Vec<[f64; 3]> = Sequence::Fibonacci.unit_sphere(SPHERE_SEEDS).iter().map(|[t, p]| unit_vector(*t, *p)).collect();
+        let energies: Vec<f64> = seeds.iter().map(|u| self.at(*u)).collect();
+
+        let mut found: Vec<[f64; 3]> = vec![];
+        for (i, u) in seeds.iter().enumerate() {
+            // The nearest neighbours, ordered around u in its tangent plane
+            let mut neighbours: Vec<usize> = (0..seeds.len()).filter(|j| *j != i).collect();
+            neighbours.select_nth_unstable_by(SPHERE_NEIGHBOURS, |a, b| chord(*u, seeds[*a]).total_cmp(&chord(*u, seeds[*b])));
+            neighbours.truncate(SPHERE_NEIGHBOURS);
+
+            let basis = tangent_basis(*u);
+            neighbours.sort_by(|a, b| {
+                let [angle_a, angle_b] = [a, b].map(|n| dot_product(seeds[*n], basis[1]).atan2(dot_product(seeds[*n], basis[0])));
+                angle_a.total_cmp(&angle_b)
+            });
+
+            let ring: Vec<f64> = neighbours.iter().map(|n| energies[*n]).collect();
+            if classify_discrete(energies[i], &ring) != Some(kind) {
+                continue
+            };
+
+            if let Some((point, k)) = newton(*u, |p| self.derivatives(p), exponential_map_in_basis, 0.1) {
+                if k == kind && !found.iter().any(|f| chord(*f, point) < SAME_POINT) {
+                    found.push(point)
+                };
+            };
+        }
+
+        let mut points: Vec<(f64, f64, f64)> = found.iter().map(|u| {
+            (u[2].clamp(-1., 1.).acos(), u[1].atan2(u[0]).rem_euclid(TWOPI), self.at(*u))
+        }).collect();
+        points.sort_by(|a, b| a.2.total_cmp(&b.2));
+        points
+    }
+}
+
+
+/// A potential energy surface on the periodic (phi, psi) torus, fitted to the energies at scattered (phi, psi)
+/// A two-dimensional Fourier series, of every wave (m, n) with |m|, |n| <= `order`, fitted by least squares.
+/// This takes (2 * order + 1)^2 coefficients, so at least as many points are needed
+/// phi and psi are in degrees, as PeptideAxes. The stationary points are returned in [-180, 180) if any
+/// of the points is negative, else in [0, 360)
+#[pyclass]
+pub struct TorusPES {
+    waves: Vec<(f64, f64)>,
+    cosines: Vec<f64>,
+    sines: Vec<f64>,
+    #[pyo3(get)]
+    order: usize,
+    signed: bool,
+}
+
+#[pymethods]
+impl TorusPES {
+
+    #[new]
+    #[pyo3(signature = (phi, psi, energies, order=4))]
+    fn new(phi: Vec<f64>, psi: Vec<f64>, energies: Vec<f64>, order: usize) -> PyResult<Self> {
+
+        // The constant, and half of the waves, as (m, n) and (-m, -n) are the same wave
+        let o = order as i64;
+        let waves: Vec<(f64, f64)> = (0..=o).flat_map(|m| (-o..=o).map(move |n| (m, n)))
+                                        .filter(|(m, n)| *m > 0 || *n >= 0)
+                                        .map(|(m, n)| (m as f64, n as f64))
+                                        .collect();
+
+        let coefficients = (2 * waves.len()) - 1;
+        check_points(phi.len(), psi.len(), energies.len(), coefficients)?;
+
+        // Every row holds the cosine of every wave, followed by the sine of every wave but the constant
+        let design = DMatrix::from_fn(phi.len(), coefficients, |i, j| {
+            let (x, y) = (phi[i].to_radians(), psi[i].to_radians());
+            match j < waves.len() {
+                true => ((waves[j].0 * x) + (waves[j].1 * y)).cos(),
+                false => ((waves[j - waves.len() + 1].0 * x) + (waves[j - waves.len() + 1].1 * y)).sin(),
+            }
+        });
+
+        let solution = match design.svd(true, true).solve(&DVector::from_vec(energies), 1e-12) {
+            Ok(s) if s.iter().all(|x| x.is_finite()) => s,
+            _ => return Err(PuckepyError::new_err("The fit of the energies did not converge.")),
+        };
+
+        let cosines = solution.rows(0, waves.len()).iter().cloned().collect();
+        let sines = [0.].into_iter().chain(solution.rows(waves.len(), waves.len() - 1).iter().cloned()).collect();
+        let signed = phi.iter().chain(psi.iter()).any(|a| *a < 0.);
+
+        Ok(TorusPES { waves, cosines, sines, order, signed })
+    }
+
+    /// The fitted energy at (phi, psi)
+    fn energy(&self, phi: f64, psi: f64) -> f64 {
+        self.at([phi.to_radians(), psi.to_radians()])
+    }
+
+    /// The fitted energies at every (phi, psi)
+    fn evaluate(&self, phi: Vec<f64>, psi: Vec<f64>) -> PyResult<Vec<f64>> {
+        check_points(phi.len(), psi.len(), phi.len(), 0)?;
+        Ok(phi.iter().zip(psi.iter()).map(|(x, y)| self.energy(*x, *y)).collect())
+    }
+
+    /// The derivatives (dE/dphi, dE/dpsi) of the energy at (phi, psi), per degree
+    fn gradient(&self, phi: f64, psi: f64) -> (f64, f64) {
+        let (g, _) = self.derivatives([phi.to_radians(), psi.to_radians()]);
+        (g[0].to_radians(), g[1].to_radians())
+    }
+
+    /// The local minima of the surface, as (phi, psi, energy) from low to high energy
+    fn minima(&self) -> Vec<(f64, f64, f64)> {
+        self.stationary_points(Stationary::Minimum)
+    }
+
+    /// The saddle points of the surface, as (phi, psi, energy) from low to high energy
+    fn saddle_points(&self) -> Vec<(f64, f64, f64)> {
+        self.stationary_points(Stationary::Saddle)
+    }
+}
+
+impl TorusPES {
+
+    // p in radians
+    fn at(&self, p: [f64; 2]) -> f64 {
+        self.waves.iter().zip(self.cosines.iter().zip(self.sines.iter()))
+            .map(|((m, n), (a, b))| {
+                let (sin, cos) = ((m * p[0]) + (n * p[1])).sin_cos();
+                (a * cos) + (b * sin)
+            }).sum()
+    }
+
+    // The analytical gradient and Hessian, per radian
+    fn derivatives(&self, p: [f64; 2]) -> ([f64; 2], [[f64; 2]; 2]) {
+        let mut gradient = [0.; 2];
+        let mut hessian = [[0.; 2]; 2];
+
+        for ((m, n), (a, b)) in self.waves.iter().zip(self.cosines.iter().zip(self.sines.iter())) {
+            let (sin, cos) = ((m * p[0]) + (n * p[1])).sin_cos();
+            let first = (b * cos) - (a * sin);
+            let second = -((a * cos) + (b * sin));
+            let k = [*m, *n];
+            for i in 0..2 {
+                gradient[i] += k[i] * first;
+                for j in 0..2 {
+                    hessian[i][j] += k[i] * k[j] * second
+                }
+            }
+        }
+
+        (gradient, hessian)
+    }
+
+    fn stationary_points(&self, kind: Stationary) -> Vec<(f64, f64, f64)> {
+
+        let spacing = TWOPI / TORUS_SEEDS as f64;
+        let grid: Vec<Vec<f64>> = (0..TORUS_SEEDS).map(|i| {
+            (0..TORUS_SEEDS).map(|j| self.at([i as f64 * spacing, j as f64 * spacing])).collect()
+        }).collect();
+
+        // The eight neighbours on the grid, counterclockwise
+        let ring = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+        let wrap = |i: usize, d: i64| (i as i64 + d).rem_euclid(TORUS_SEEDS as i64) as usize;
+
+        let mut found: Vec<[f64; 2]> = vec![];
+        for i in 0..TORUS_SEEDS {
+            for j in 0..TORUS_SEEDS {
+                let neighbours: Vec<f64> = ring.iter().map(|(di, dj)| grid[wrap(i, *di)][wrap(j, *dj)]).collect();
+                if classify_discrete(grid[i][j], &neighbours) != Some(kind) {
+                    continue
+                };
+
+                let start = [i as f64 * spacing, j as f64 * spacing];
+                if let Some((point, k)) = newton(start, |p| self.derivatives(p), |p, d| [p[0] + d[0], p[1] + d[1]], 0.1) {
+                    let point = point.map(|x| x.rem_euclid(TWOPI));
+                    if k == kind && !found.iter().any(|f| torus_distance(*f, point) < SAME_POINT) {
+                        found.push(point)
+                    };
+                };
+            }
+        }
+
+        let mut points: Vec<(f64, f64, f64)> = found.iter().map(|p| {
+            let [x, y] = p.map(|a| wrap_degrees(a.to_degrees(), self.signed));
+            (x, y, self.at(*p))
+        }).collect();
+        points.sort_by(|a, b| a.2.total_cmp(&b.2));
+        points
+    }
+}
+
+
+fn check_points(x: usize, y: usize, energies: usize, minimum: usize) -> PyResult<()> {
+
+    if x != y || x != energies {
+        return Err(PuckepyError::new_err(format!("The amount of x ({}), y ({}) and energies ({}) are not equal. Expected an energy for every point.", x, y, energies)))
+    };
+
+    if x < minimum {
+        return Err(PuckepyError::new_err(format!("An amount of {} points has been queried. Expected at least {} points.", x, minimum)))
+    };
+
+    Ok(())
+}
+
+fn kernel(r: f64, shape: f64) -> f64 {
+    1. / (r.powi(2) + shape.powi(2)).sqrt()
+}
+
+fn unit_vector(theta: f64, phi: f64) -> [f64; 3] {
+    let (sin_t, cos_t) = theta.sin_cos();
+    let (sin_p, cos_p) = phi.sin_cos();
+    [sin_t * cos_p, sin_t * sin_p, cos_t]
+}
+
+fn chord(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn torus_distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let [dx, dy] = [0, 1].map(|i| ((a[i] - b[i]) + std::f64::consts::PI).rem_euclid(TWOPI) - std::f64::consts::PI);
+    dx.hypot(dy)
+}
+
+// The median distance of every point to its nearest neighbour
+fn nearest_neighbour_chord(nodes: &[[f64; 3]]) -> f64 {
+
+    let mut nearest: Vec<f64> = nodes.iter().enumerate().map(|(i, a)| {
+        nodes.iter().enumerate()
+            .filter(|(j, b)| *j != i && chord(*a, **b) > 1e-9)
+            .map(|(_, b)| chord(*a, *b))
+            .fold(f64::INFINITY, f64::min)
+    }).collect();
+
+    nearest.sort_by(|a, b| a.total_cmp(b));
+    nearest[nearest.len() / 2]
+}
+
+// Two orthonormal vectors in the tangent plane of u, that do not degenerate at the poles
+fn tangent_basis(u: [f64; 3]) -> [[f64; 3]; 2] {
+    let axis = if u[2].abs() < 0.9 { [0., 0., 1.] } else { [1., 0., 0.] };
+    let e1 = normalise_vector(cross_product(axis, u));
+    [e1, cross_product(u, e1)]
+}
+
+// Walk from u along the great circle of the tangent vector v, over the length of v
+fn exponential_map(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    let length = (v[0].powi(2) + v[1].powi(2) + v[2].powi(2)).sqrt();
+    if length == 0. {
+        return u
+    };
+    let (sin, cos) = length.sin_cos();
+    normalise_vector([0, 1, 2].map(|i| (cos * u[i]) + (sin * v[i] / length)))
+}
+
+fn exponential_map_in_basis(u: [f64; 3], delta: [f64; 2]) -> [f64; 3] {
+    let [e1, e2] = tangent_basis(u);
+    exponential_map(u, [0, 1, 2].map(|i| (delta[0] * e1[i]) + (delta[1] * e2[i])))
+}
+
+
+#[cfg(test)]
+mod test_pes {
+
+    use std::f64::consts::PI;
+    use super::*;
+
+    #[test]
+    fn sphere_interpolates_height() {
+        // E = cos(theta), the height above the equator, has its single minimum at the south pole
+        let points = Sequence::Fibonacci.unit_sphere(300);
+        let (theta, phi): (Vec<f64>, Vec<f64>) = points.iter().map(|p| (p[0], p[1])).unzip();
+        let energies = theta.iter().map(|t| t.cos()).collect();
+        let pes = SpherePES::new(theta, phi, energies, None, 0.).unwrap();
+
+        assert!((pes.energy(1., 2.) - 1_f64.cos()).abs() < 1e-2);
+        assert!((pes.gradient(1., 2.).0 + 1_f64.sin()).abs() < 5e-2);
+
+        let minima = pes.minima();
+        assert_eq!(minima.len(), 1);
+        assert!((minima[0].0 - PI).abs() < 5e-2 && (minima[0].2 + 1.).abs() < 1e-2);
+        assert!(pes.saddle_points().is_empty());
+    }
+
+    #[test]
+    fn torus_fits_fourier() {
+        // cos(phi) + cos(psi) has a minimum at (180, 180), saddles at (0, 180) and (180, 0), and a maximum at (0, 0)
+        let (phi, psi): (Vec<f64>, Vec<f64>) = (0..24).flat_map(|i| (0..24).map(move |j| (i as f64 * 15., j as f64 * 15.))).unzip();
+        let energies = phi.iter().zip(psi.iter()).map(|(x, y)| x.to_radians().cos() + y.to_radians().cos()).collect();
+        let pes = TorusPES::new(phi, psi, energies, 2).unwrap();
+
+        assert!((pes.energy(30., 60.) - (30_f64.to_radians().cos() + 60_f64.to_radians().cos())).abs() < 1e-9);
+        assert!((pes.gradient(90., 0.).0 + 1_f64.to_radians()).abs() < 1e-9);
+
+        let minima = pes.minima();
+        assert_eq!(minima.len(), 1);
+        assert!((minima[0].0 - 180.).abs() < 1e-6 && (minima[0].1 - 180.).abs() < 1e-6);
+
+        let mut saddles: Vec<(i64, i64)> = pes.saddle_points().iter().map(|(x, y, _)| (x.round() as i64, y.round() as i64)).collect();
+        saddles.sort();
+        assert_eq!(saddles, vec![(0, 180), (180, 0)]);
+    }
+}
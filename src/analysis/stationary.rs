@@ -0,0 +1,112 @@
+// Locate the stationary points of a smooth two-dimensional surface
+// Candidates are found on a discrete set of points, by comparing every point to the ring of its neighbours,
+// and are then refined with Newton steps on the gradient
+
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-10;
+
+
+/// The kind of a stationary point, from the signs of the eigenvalues of its Hessian
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stationary {
+    Minimum,
+    Saddle,
+    Maximum,
+}
+
+impl Stationary {
+
+    fn from_hessian(h: [[f64; 2]; 2]) -> Option<Stationary> {
+        let det = (h[0][0] * h[1][1]) - (h[0][1] * h[1][0]);
+        let trace = h[0][0] + h[1][1];
+
+        if det < 0. {
+            Some(Stationary::Saddle)
+        } else if det > 0. && trace > 0. {
+            Some(Stationary::Minimum)
+        } else if det > 0. && trace < 0. {
+            Some(Stationary::Maximum)
+        } else {
+            None
+        }
+    }
+}
+
+/// Classify a point by the energies of its neighbours, ordered around it
+/// A minimum lies below every neighbour. Around a saddle point, the neighbours alternate
+/// between higher and lower at least twice
+pub fn classify_discrete(energy: f64, ring: &[f64]) -> Option<Stationary> {
+
+    let higher: Vec<bool> = ring.iter().map(|e| *e > energy).collect();
+    if higher.iter().all(|h| *h) {
+        return Some(Stationary::Minimum)
+    };
+    if higher.iter().all(|h| !*h) {
+        return Some(Stationary::Maximum)
+    };
+
+    let changes = (0..higher.len()).filter(|i| higher[*i] != higher[(i + 1) % higher.len()]).count();
+    match changes >= 4 {
+        true => Some(Stationary::Saddle),
+        false => None,
+    }
+}
+
+/// Refine a candidate into the nearest stationary point with Newton steps
+/// `derivatives` returns the gradient and the Hessian in local coordinates around a point,
+/// and `step` moves a point along those local coordinates. Steps are capped at `max_step`
+/// Returns None if the Newton steps do not converge
+pub fn newton<P: Copy>(start: P,
+                       derivatives: impl Fn(P) -> ([f64; 2], [[f64; 2]; 2]),
+                       step: impl Fn(P, [f64; 2]) -> P,
+                       max_step: f64) -> Option<(P, Stationary)> {
+
+    let mut point = start;
+    for _ in 0..MAX_ITERATIONS {
+        let (g, h) = derivatives(point);
+        let det = (h[0][0] * h[1][1]) - (h[0][1] * h[1][0]);
+        if det == 0. || !det.is_finite() {
+            return None
+        };
+
+        let mut delta = [-((h[1][1] * g[0]) - (h[0][1] * g[1])) / det,
+                         -((h[0][0] * g[1]) - (h[1][0] * g[0])) / det];
+        let length = delta[0].hypot(delta[1]);
+        if length > max_step {
+            delta = delta.map(|d| d * max_step / length)
+        };
+
+        point = step(point, delta);
+        if length < TOLERANCE {
+            return Stationary::from_hessian(derivatives(point).1).map(|kind| (point, kind))
+        };
+    }
+
+    None
+}
+
+
+#[cfg(test)]
+mod test_stationary {
+
+    use super::*;
+
+    #[test]
+    fn discrete_saddle() {
+        // x^2 - y^2 around the origin, starting at +x and going counterclockwise
+        let ring = [1., 0., -1., 0., 1., 0., -1., 0.];
+        assert_eq!(classify_discrete(0., &ring), Some(Stationary::Saddle));
+        assert_eq!(classify_discrete(-1., &ring[..4]), None);
+    }
+
+    #[test]
+    fn newton_on_a_quadratic() {
+        // (x - 1)^2 - 2 (y + 3)^2
+        let derivatives = |p: [f64; 2]| ([2. * (p[0] - 1.), -4. * (p[1] + 3.)], [[2., 0.], [0., -4.]]);
+        let step = |p: [f64; 2], d: [f64; 2]| [p[0] + d[0], p[1] + d[1]];
+
+        let (point, kind) = newton([0., 0.], derivatives, step, 0.5).unwrap();
+        assert!((point[0] - 1.).abs() < 1e-9 && (point[1] + 3.).abs() < 1e-9);
+        assert_eq!(kind, Stationary::Saddle);
+    }
+}
@@ -1,6 +1,11 @@
-use pyo3::{pyclass, pymethods};
+use pyo3::{pyclass, pymethods, PyResult};
 use ndarray::Array1; // not public, useful for the linspace function
 
+use crate::conf_sampling::sequences::{amount_of_points, Sequence};
+use crate::errors::OutOfRangeError;
+use crate::geometry::molecule_ops::dihedral;
+use crate::formalism::inversion::{fivering::invert_fivering, RingGeometry};
+
 const FOURPIOVERFIVE : f64 = (4. * PI) / 5.;
 use std::f64::consts::PI;
 
@@ -18,40 +23,36 @@ pub struct Fivering {
 impl Fivering {
     
     #[new]
-    fn new(interval: u16) -> Self {
+    #[pyo3(signature = (interval, sampling="grid", amount=None, seed=0))]
+    fn new(interval: u16, sampling: &str, amount: Option<usize>, seed: u64) -> PyResult<Self> {
         
         // Derive torsion angles from the given axes
-        let polars = FAxes::new(interval as usize);
+        let (zx, zy) = FAxes::new(interval as usize).points(Sequence::new(sampling, seed)?, amount)?;
+        Ok(Fivering::from_points(&zx, &zy))
+    }
+
+    /// The constraint torsions of the points of a FiveringAxes, e.g. those of a Refinement
+    #[staticmethod]
+    fn from_axes(axes: &FiveringAxes) -> Self {
+        Fivering::from_points(&axes.zx, &axes.zy)
+    }
+}
 
-        // Setup variable
-        let amount : u16 = interval * interval;
-        let interval_f64 : f64 = interval as f64;
+impl Fivering {
+
+    fn from_points(zx: &[f64], zy: &[f64]) -> Self {
 
         // Initialise equation-specific constants
         let denominator_x : f64 = FOURPIOVERFIVE.cos();
         let denominator_y : f64 = FOURPIOVERFIVE.sin();
 
-        // Instance Fivering struct
-        let mut nu1: Vec<f64> = Vec::with_capacity(amount as usize);
-        let mut nu3: Vec<f64> = Vec::with_capacity(amount as usize);
-
-        let mut x : f64;
-        let mut y : f64;
-
-        for i in 0..amount as usize {
-            // Calculate indexes for the array axises
-            x = (i as f64 / interval_f64).floor(); // X axis, returns with floor
-            y = i as f64 % interval_f64; // Y axis, return with modulo
-
-            // fill out the array
-            nu1.push((polars.zx[x as usize] * denominator_x ) + ( polars.zy[y as usize] * denominator_y));
-            nu3.push((polars.zx[x as usize] * denominator_x ) - ( polars.zy[y as usize] * denominator_y));
-        }
+        let nu1 = zx.iter().zip(zy.iter()).map(|(x, y)| (x * denominator_x) + (y * denominator_y));
+        let nu3 = zx.iter().zip(zy.iter()).map(|(x, y)| (x * denominator_x) - (y * denominator_y));
 
         // Make values ORCA-ready
         Self {
-            nu1 : nu1.iter().map(|x| if x < &0. { x + 360.} else {*x}).collect(),
-            nu3 : nu3.iter().map(|x| if x < &0. { x + 360.} else {*x}).collect()
+            nu1 : nu1.map(|x| if x < 0. { x + 360.} else {x}).collect(),
+            nu3 : nu3.map(|x| if x < 0. { x + 360.} else {x}).collect()
         }
 
     }
@@ -59,44 +60,23 @@ impl Fivering {
 
 #[pyclass(get_all)]
 pub struct FiveringAxes {
-    zx : Vec<f64>,
-    zy : Vec<f64>,
+    pub zx : Vec<f64>,
+    pub zy : Vec<f64>,
 }
 
 #[pymethods]
 impl FiveringAxes {
 
     #[new]
-    fn new(interval: u16) -> Self {
+    #[pyo3(signature = (interval, sampling="grid", amount=None, seed=0))]
+    fn new(interval: u16, sampling: &str, amount: Option<usize>, seed: u64) -> PyResult<Self> {
 
-        // Derive torsion angles from the given axes
-        let polars = FAxes::new(interval as usize);
-
-        // Setup variable
-        let amount : u16 = interval * interval;
-        let interval_f64 : f64 = interval as f64;
-
-        // Instance Fivering struct
-        let mut zx: Vec<f64> = Vec::with_capacity(amount as usize);
-        let mut zy: Vec<f64> = Vec::with_capacity(amount as usize);
-        let mut x : f64;
-        let mut y : f64;
-
-        for i in 0..amount as usize {
-            // Calculate indexes for the array axises
-            x = (i as f64 / interval_f64).floor(); // X axis, returns with floor
-            y = i as f64 % interval_f64; // Y axis, return with modulo
-
-            // fill out the array
-            zx.push(polars.zx[x as usize]);
-            zy.push(polars.zy[y as usize]);
-        }
+        let (zx, zy) = FAxes::new(interval as usize).points(Sequence::new(sampling, seed)?, amount)?;
 
-        // Make values ORCA-ready
-        Self {
+        Ok(Self {
             zx,
             zy
-        }
+        })
         
     }
     
@@ -104,6 +84,78 @@ impl FiveringAxes {
 
 
 
+/// Struct to keep the conformers sampled on the (amplitude, phase_angle) of the Cremer-Pople formalism
+/// Samples every `amplitudes` shell at `phase_interval` evenly spaced phase angles [0, 360)
+/// The planar shell (amplitude == 0.) is sampled once
+/// public `amplitude` and `phase_angle` fields : Vec<f64>, the Cremer-Pople coordinates of every conformer
+/// public `nu1` and `nu3` fields : Vec<f64>, the constraint torsions O4'-C1'-C2'-C3' and C2'-C3'-C4'-O4' [0, 360)
+/// public `coordinates` field : Vec<[[f64;3];5]>, the Cartesian coordinates of every conformer, by invert_fivering
+#[pyclass(get_all)]
+pub struct FiveringPolar {
+    pub amplitude: Vec<f64>,
+    pub phase_angle: Vec<f64>,
+    pub nu1: Vec<f64>,
+    pub nu3: Vec<f64>,
+    pub coordinates: Vec<[[f64;3];5]>,
+}
+
+#[pymethods]
+impl FiveringPolar {
+
+    #[new]
+    #[pyo3(signature = (amplitudes, phase_interval, bondlengths=None, angles=None, elements=None))]
+    fn new(amplitudes: Vec<f64>, phase_interval: u16, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>) -> PyResult<Self> {
+
+        if let Some(a) = amplitudes.iter().find(|a| !(a.is_finite() && **a >= 0.)) {
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) should be a positive number.", a)))
+        };
+
+        if let Some(a) = amplitudes.iter().find(|a| **a > 1.) {
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) is larger than 1.", a)))
+        };
+
+        if phase_interval == 0 {
+            return Err(OutOfRangeError::new_err("phase_interval value (0) should be at least 1."))
+        };
+
+        let geometry = RingGeometry::new(5, bondlengths, angles, elements)?;
+        let phase_step = 360. / phase_interval as f64;
+
+        let amount = amplitudes.len() * phase_interval as usize;
+        let mut polar = FiveringPolar {
+            amplitude: Vec::with_capacity(amount),
+            phase_angle: Vec::with_capacity(amount),
+            nu1: Vec::with_capacity(amount),
+            nu3: Vec::with_capacity(amount),
+            coordinates: Vec::with_capacity(amount),
+        };
+
+        for amplitude in amplitudes {
+            // Every phase angle of the planar ring is the same conformer
+            let phases = if amplitude == 0. { 1 } else { phase_interval };
+
+            for i in 0..phases {
+                let phase_angle = i as f64 * phase_step;
+                let fivering = invert_fivering(amplitude, phase_angle, &geometry);
+
+                // Make values ORCA-ready
+                let nu1 = dihedral(fivering[0], fivering[1], fivering[2], fivering[3]);
+                let nu3 = dihedral(fivering[2], fivering[3], fivering[4], fivering[0]);
+
+                polar.amplitude.push(amplitude);
+                polar.phase_angle.push(phase_angle);
+                polar.nu1.push(if nu1 < 0. { nu1 + 360. } else { nu1 });
+                polar.nu3.push(if nu3 < 0. { nu3 + 360. } else { nu3 });
+                polar.coordinates.push(fivering);
+            }
+        }
+
+        Ok(polar)
+    }
+}
+
+
+
 struct FAxes {
     zx : Vec<f64>,
     zy : Vec<f64>,
@@ -121,5 +173,27 @@ impl FAxes {
         }
         
     }
+
+    // The (zx, zy) of every point. On the grid, every zx value is paired with all zy values
+    // A sequence samples `amount` points over the same extent, which defaults to the size of the grid
+    fn points(&self, sequence: Option<Sequence>, amount: Option<usize>) -> PyResult<(Vec<f64>, Vec<f64>)> {
+
+        let amount = amount_of_points(&sequence, amount, self.zx.len() * self.zy.len())?;
+
+        if let Some(sequence) = sequence {
+            return Ok(sequence.rectangle(amount, (-60., 60.), (-60., 60.)))
+        };
+
+        let mut zx = Vec::with_capacity(amount);
+        let mut zy = Vec::with_capacity(amount);
+        for x in self.zx.iter() {
+            for y in self.zy.iter() {
+                zx.push(*x);
+                zy.push(*y);
+            }
+        }
+
+        Ok((zx, zy))
+    }
     
 }
@@ -0,0 +1,43 @@
+use pyo3::{pyclass, pymethods, PyResult};
+use ndarray::Array1;
+
+use crate::errors::OutOfRangeError;
+use crate::geometry::molecule_ops::dihedral;
+use crate::formalism::inversion::{fourring::invert_fourring, RingGeometry};
+
+
+/// Struct to keep the scan along the single puckering coordinate of a four-membered ring
+/// amplitude : [-max_amplitude, max_amplitude] (Angstrom), passing through the planar ring
+/// nu1 : 1-2-3-4 (1-based atoms). The torsions of a four-membered ring only alternate in sign, so one constraint suffices
+#[pyclass(get_all)]
+pub struct Fourring {
+    pub amplitude: Vec<f64>,
+    pub nu1: Vec<f64>,
+    pub coordinates: Vec<[[f64;3];4]>,
+}
+
+#[pymethods]
+impl Fourring {
+
+    #[new]
+    #[pyo3(signature = (interval, max_amplitude=0.3, bondlengths=None, elements=None))]
+    fn new(interval: u16, max_amplitude: f64, bondlengths: Option<Vec<f64>>, elements: Option<Vec<String>>) -> PyResult<Self> {
+
+        if !(max_amplitude > 0. && max_amplitude <= 1.) {
+            return Err(OutOfRangeError::new_err(format!("max_amplitude value ({}) should be within the range of 0 -> 1", max_amplitude)))
+        };
+
+        let geometry = RingGeometry::new(4, bondlengths, None, elements)?;
+        let amplitude = Array1::linspace(-max_amplitude, max_amplitude, interval as usize).into_raw_vec();
+
+        let coordinates: Vec<[[f64;3];4]> = amplitude.iter().map(|q| invert_fourring(*q, &geometry)).collect();
+
+        // Make values ORCA-ready
+        let nu1 = coordinates.iter().map(|ring| {
+            let t = dihedral(ring[0], ring[1], ring[2], ring[3]);
+            if t < 0. { t + 360. } else { t }
+        }).collect();
+
+        Ok(Self { amplitude, nu1, coordinates })
+    }
+}
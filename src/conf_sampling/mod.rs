@@ -1,3 +1,8 @@
 pub mod peptide;
+pub mod fourring;
 pub mod fivering;
 pub mod sixring;
+pub mod sevenring;
+pub mod sequences; // quasi-random and random alternatives to the grids of the samplers
+pub mod refinement; // refine the grids around their low-energy regions
+pub mod ramachandran; // classify (phi, psi) into the regions of the Ramachandran plot
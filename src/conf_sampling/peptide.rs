@@ -1,5 +1,8 @@
 use ndarray::Array1;
-use pyo3::{pyclass, pymethods};
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::conf_sampling::sequences::{amount_of_points, Sequence};
+use crate::errors::{OutOfRangeError, PuckepyError};
 
 
 /// the `phi-psi` dihedrals, which are the peptide backbone dihedrals in proteins
@@ -7,40 +10,26 @@ use pyo3::{pyclass, pymethods};
 /// public `psi` field : Vec<f64>
 #[pyclass(get_all)]
 pub struct Peptide {
-    phi : Vec<f64>,
-    psi : Vec<f64>,
+    pub phi : Vec<f64>,
+    pub psi : Vec<f64>,
 }
 
 #[pymethods]
 impl Peptide {
 
     #[new]
-    fn new(interval: u16) -> Self {
-
-        let amount = (interval * interval) as usize;
-
-        let axes = PAxes::new(interval as usize);
-
-        let mut phi = Vec::with_capacity(amount);
-        let mut psi = Vec::with_capacity(amount);
-        
-        let mut xi : f64;
-        let mut yi : f64;
-        for i in 0..amount as usize {
+    #[pyo3(signature = (interval, psi_interval=None, phi_range=None, psi_range=None, periodic=false, convention=None, sampling="grid", amount=None, seed=0))]
+    #[allow(clippy::too_many_arguments)] // every option of the axes is a keyword argument in Python
+    fn new(interval: u16, psi_interval: Option<u16>, phi_range: Option<(f64, f64)>, psi_range: Option<(f64, f64)>,
+           periodic: bool, convention: Option<&str>, sampling: &str, amount: Option<usize>, seed: u64) -> PyResult<Self> {
 
-            // For every x value, return all y values
-            xi = (i as f64 / interval as f64).floor(); // floor, to return x axis value
-            yi = i as f64 % interval as f64; // return with modulo, to return y axis value
+        let (phi, psi) = PAxes::new(interval, psi_interval, phi_range, psi_range, periodic, convention)?
+                            .points(Sequence::new(sampling, seed)?, amount)?;
 
-            // fill out the array
-            phi.push(axes.x[xi as usize]); 
-            psi.push(axes.y[yi as usize]); 
-        }
-
-        Self {
+        Ok(Self {
             phi,
             psi,
-        }
+        })
     }
 }
 
@@ -49,61 +38,204 @@ impl Peptide {
 
 #[pyclass(get_all)]
 pub struct PeptideAxes {
-    x : Vec<f64>,
-    y : Vec<f64>,
+    pub x : Vec<f64>,
+    pub y : Vec<f64>,
 }
 
 #[pymethods]
 impl PeptideAxes {
 
     #[new]
-    fn new(interval: u16) -> Self {
+    #[pyo3(signature = (interval, psi_interval=None, phi_range=None, psi_range=None, periodic=false, convention=None, sampling="grid", amount=None, seed=0))]
+    #[allow(clippy::too_many_arguments)] // every option of the axes is a keyword argument in Python
+    fn new(interval: u16, psi_interval: Option<u16>, phi_range: Option<(f64, f64)>, psi_range: Option<(f64, f64)>,
+           periodic: bool, convention: Option<&str>, sampling: &str, amount: Option<usize>, seed: u64) -> PyResult<Self> {
         
-        let amount = (interval * interval) as usize;
+        let (phi, psi) = PAxes::new(interval, psi_interval, phi_range, psi_range, periodic, convention)?
+                            .points(Sequence::new(sampling, seed)?, amount)?;
+
+        Ok(Self {
+            x: phi,
+            y: psi,
+        })
+    }
+}
 
-        let axes = PAxes::new(interval as usize);
+/// The convention in which the dihedrals are returned
+///     None       : as spanned by the range, e.g. [0, 360] 
+///     "positive" : wrapped onto [0, 360)
+///     "signed"   : wrapped onto [-180, 180), as used by force fields and plotting tools
+#[derive(Clone, Copy, PartialEq)]
+enum Convention {
+    Unwrapped,
+    Positive,
+    Signed,
+}
 
-        let mut phi = Vec::with_capacity(amount);
-        let mut psi = Vec::with_capacity(amount);
-        
-        let mut xi : f64;
-        let mut yi : f64;
-        for i in 0..amount as usize {
+impl Convention {
 
-            // For every x value, return all y values
-            xi = (i as f64 / interval as f64).floor(); // floor, to return x axis value
-            yi = i as f64 % interval as f64; // return with modulo, to return y axis value
+    fn new(convention: Option<&str>) -> PyResult<Convention> {
+        match convention {
+            None => Ok(Convention::Unwrapped),
+            Some("positive") => Ok(Convention::Positive),
+            Some("signed") => Ok(Convention::Signed),
+            Some(c) => Err(PuckepyError::new_err(format!("Convention \"{}\" is not one of \"positive\" ([0, 360)) or \"signed\" ([-180, 180)).", c)))
+        }
+    }
 
-            // fill out the array
-            phi.push(axes.x[xi as usize]); 
-            psi.push(axes.y[yi as usize]); 
+    // The range spanned when none is queried
+    fn default_range(&self) -> (f64, f64) {
+        match self {
+            Convention::Signed => (-180., 180.),
+            _ => (0., 360.),
         }
+    }
 
-        Self {
-            x: phi,
-            y: psi,
+    fn apply(&self, angle: f64) -> f64 {
+        match self {
+            Convention::Unwrapped => angle,
+            Convention::Positive => angle.rem_euclid(360.),
+            Convention::Signed => (angle + 180.).rem_euclid(360.) - 180.,
         }
     }
 }
-//
+
+// Span `interval` values over [start, stop]
+// A periodic axis excludes the endpoint, so that 0 and 360 are not both sampled
+// A convention wraps the endpoint of a full turn onto its start, so that axis is periodic too
+fn axis(interval: u16, range: (f64, f64), periodic: bool, convention: Convention, name: &str) -> PyResult<Vec<f64>> {
+
+    let (start, stop) = range;
+    if !(start < stop && stop - start <= 360.) {
+        return Err(OutOfRangeError::new_err(format!("The {} range ({}, {}) should be increasing and span at most 360 degrees.", name, start, stop)))
+    };
+
+    let full_turn = convention != Convention::Unwrapped && stop - start == 360.;
+    let values = if periodic || full_turn {
+        let step = (stop - start) / interval as f64;
+        (0..interval).map(|i| start + (i as f64 * step)).collect()
+    } else {
+        Array1::linspace(start, stop, interval as usize).into_raw_vec()
+    };
+
+    Ok(values.into_iter().map(|v| convention.apply(v)).collect())
+}
+
 /// The axes to iterate over for peptide-like molecules : 
-/// Its extent is : [0 , 2pi] (rad)
-/// Its extent is : [0 , 360] (degrees)
+/// Its extent is : [0 , 2pi] (rad), unless queried otherwise
+/// Its extent is : [0 , 360] (degrees), unless queried otherwise
 /// public `x` field : Vec<f64>
 /// public `y` field : Vec<f64>
 /// Can remain a private struct, as this only is required to build the Peptide struct
 struct PAxes {
     x : Vec<f64>,
     y : Vec<f64>,
+    x_range : (f64, f64),
+    y_range : (f64, f64),
+    convention : Convention,
 }
 
 impl PAxes {
-    /// Initialise the struct with an array of zeroes
-    fn new(interval: usize) -> PAxes {
-        PAxes {
-            x: Array1::linspace(0., 360., interval).into_raw_vec(),
-            y: Array1::linspace(0., 360., interval).into_raw_vec(),
+    /// The `x` (phi) and `y` (psi) axes can differ in their range and resolution
+    fn new(interval: u16, psi_interval: Option<u16>, phi_range: Option<(f64, f64)>, psi_range: Option<(f64, f64)>,
+           periodic: bool, convention: Option<&str>) -> PyResult<PAxes> {
+
+        let convention = Convention::new(convention)?;
+        let x_range = phi_range.unwrap_or(convention.default_range());
+        let y_range = psi_range.unwrap_or(convention.default_range());
+
+        Ok(PAxes {
+            x: axis(interval, x_range, periodic, convention, "phi")?,
+            y: axis(psi_interval.unwrap_or(interval), y_range, periodic, convention, "psi")?,
+            x_range,
+            y_range,
+            convention,
+        })
+    }
+
+    // The (phi, psi) of every point, on the grid or by a sequence
+    // A sequence samples `amount` points over the same ranges, which defaults to the size of the grid
+    fn points(&self, sequence: Option<Sequence>, amount: Option<usize>) -> PyResult<(Vec<f64>, Vec<f64>)> {
+
+        let amount = amount_of_points(&sequence, amount, self.x.len() * self.y.len())?;
+
+        match sequence {
+            None => Ok(self.grid()),
+            Some(sequence) => {
+                let (phi, psi) = sequence.rectangle(amount, self.x_range, self.y_range);
+                Ok((
+                    phi.into_iter().map(|v| self.convention.apply(v)).collect(),
+                    psi.into_iter().map(|v| self.convention.apply(v)).collect(),
+                ))
+            }
+        }
+    }
+
+    // For every x value, return all y values
+    fn grid(&self) -> (Vec<f64>, Vec<f64>) {
+
+        let amount = self.x.len() * self.y.len();
+
+        let mut phi = Vec::with_capacity(amount);
+        let mut psi = Vec::with_capacity(amount);
+
+        for x in self.x.iter() {
+            for y in self.y.iter() {
+                phi.push(*x);
+                psi.push(*y);
+            }
         }
+
+        (phi, psi)
     }
 }
 
+
+#[cfg(test)]
+mod test_peptide {
+
+    use super::*;
+
+    #[test]
+    fn periodic_signed_axes() {
+        let axes = PAxes::new(36, Some(18), None, None, true, Some("signed")).unwrap();
+
+        assert_eq!(axes.x.len(), 36);
+        assert_eq!(axes.x[0], -180.);
+        assert_eq!(*axes.x.last().unwrap(), 170.);
+        assert_eq!(axes.y[1] - axes.y[0], 20.);
+        assert_eq!(axes.grid().0.len(), 36 * 18);
+    }
+
+    #[test]
+    fn wrapped_full_turn() {
+        // -180 and 180 are the same signed angle, so it is sampled once
+        let axes = PAxes::new(24, None, None, None, false, Some("signed")).unwrap();
+        assert_eq!(axes.x.len(), 24);
+        assert!(axes.x.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(*axes.x.last().unwrap(), 165.);
+
+        let axes = PAxes::new(10, None, Some((-180., 0.)), None, false, Some("signed")).unwrap();
+        assert_eq!(*axes.x.last().unwrap(), 0.);
+    }
+
+    #[test]
+    fn legacy_axes() {
+        let axes = PAxes::new(37, None, None, None, false, None).unwrap();
+
+        assert_eq!(axes.x[0], 0.);
+        assert_eq!(axes.x[36], 360.);
+        assert_eq!(axes.x, axes.y);
+    }
+
+    #[test]
+    fn sequence_within_range() {
+        let axes = PAxes::new(10, None, Some((-180., 0.)), None, false, Some("signed")).unwrap();
+        let (phi, psi) = axes.points(Some(Sequence::Halton), Some(250)).unwrap();
+
+        assert_eq!(phi.len(), 250);
+        assert!(phi.iter().all(|v| (-180. ..0.).contains(v)));
+        assert!(psi.iter().all(|v| (-180. ..180.).contains(v)));
+        assert!(axes.points(None, Some(250)).is_err());
+    }
+}
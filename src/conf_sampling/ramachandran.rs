@@ -0,0 +1,294 @@
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::errors::{OutOfRangeError, PuckepyError};
+use crate::conf_sampling::peptide::Peptide;
+use crate::formalism::protein::Protein;
+
+/// A closed polygon on the Ramachandran plot, as (phi, psi) vertices in degrees [-180. -> 180.]
+pub type Polygon = Vec<[f64;2]>;
+
+pub const DISALLOWED: &str = "disallowed";
+pub const ALLOWED: &str = "allowed";
+
+// Coarse default boundaries of the regions, per residue type
+// Regions crossing the psi = 180 | -180 border are split into two polygons
+fn rectangle(phi: [f64;2], psi: [f64;2]) -> Polygon {
+    vec![[phi[0], psi[0]], [phi[1], psi[0]], [phi[1], psi[1]], [phi[0], psi[1]]]
+}
+
+fn general_regions() -> Vec<(String, Vec<Polygon>)> {
+    vec![
+        ("alpha".into(), vec![rectangle([-180., -30.], [-100., 50.])]),
+        ("ppii".into(), vec![rectangle([-90., -45.], [100., 180.]), rectangle([-90., -45.], [-180., -170.])]),
+        ("beta".into(), vec![rectangle([-180., -90.], [90., 180.]), rectangle([-180., -90.], [-180., -165.])]),
+        ("alpha_L".into(), vec![rectangle([40., 100.], [-20., 90.])]),
+    ]
+}
+
+// Glycine has no side chain, and its regions are mirrored through the origin
+fn glycine_regions() -> Vec<(String, Vec<Polygon>)> {
+    vec![
+        ("alpha".into(), vec![rectangle([-180., -30.], [-100., 50.])]),
+        ("ppii".into(), vec![rectangle([-90., -45.], [100., 180.]), rectangle([-90., -45.], [-180., -170.])]),
+        ("beta".into(), vec![rectangle([-180., -90.], [90., 180.]), rectangle([-180., -90.], [-180., -150.]),
+                             rectangle([90., 180.], [-180., -90.]), rectangle([90., 180.], [150., 180.])]),
+        ("alpha_L".into(), vec![rectangle([30., 180.], [-50., 100.])]),
+    ]
+}
+
+// The pyrrolidine ring of proline locks phi around -65
+fn proline_regions() -> Vec<(String, Vec<Polygon>)> {
+    vec![
+        ("alpha".into(), vec![rectangle([-100., -40.], [-70., 20.])]),
+        ("ppii".into(), vec![rectangle([-100., -40.], [100., 180.]), rectangle([-100., -40.], [-180., -170.])]),
+    ]
+}
+
+// The residue preceding a proline disfavours the alpha region and the left-handed helix
+fn preproline_regions() -> Vec<(String, Vec<Polygon>)> {
+    vec![
+        ("alpha".into(), vec![rectangle([-180., -40.], [-70., 0.])]),
+        ("ppii".into(), vec![rectangle([-90., -45.], [100., 180.]), rectangle([-90., -45.], [-180., -170.])]),
+        ("beta".into(), vec![rectangle([-180., -90.], [90., 180.]), rectangle([-180., -90.], [-180., -165.])]),
+        ("alpha_L".into(), vec![rectangle([45., 90.], [30., 90.])]),
+    ]
+}
+
+// A density (e.g. a histogram of high-resolution structures) over [-180, 180) x [-180, 180)
+// Rows run over phi, columns over psi
+struct DensityGrid {
+    values: Vec<Vec<f64>>,
+    threshold: f64,
+}
+
+impl DensityGrid {
+    fn at(&self, phi: f64, psi: f64) -> f64 {
+        let bin = |angle: f64, n: usize| (((angle + 180.) / 360. * n as f64).floor() as usize).min(n - 1);
+        let row = &self.values[bin(phi, self.values.len())];
+        row[bin(psi, row.len())]
+    }
+}
+
+struct RegionSet {
+    regions: Vec<(String, Vec<Polygon>)>,
+    density: Option<DensityGrid>,
+}
+
+impl RegionSet {
+
+    // With a density, points below its threshold are disallowed, whichever polygon they lie in
+    // Points that pass the density, but lie outside every polygon, are allowed
+    fn classify(&self, phi: f64, psi: f64) -> &str {
+
+        if let Some(density) = &self.density {
+            if density.at(phi, psi) < density.threshold {
+                return DISALLOWED
+            }
+        };
+
+        for (name, polygons) in self.regions.iter() {
+            if polygons.iter().any(|polygon| contains(polygon, phi, psi)) {
+                return name
+            }
+        }
+
+        match self.density {
+            Some(_) => ALLOWED,
+            None => DISALLOWED,
+        }
+    }
+}
+
+// Even-odd rule : a ray cast from the point crosses the edges of the polygon an odd amount of times when inside
+// Points on the lower/left edges are inside, so that adjacent rectangles tile the plane
+fn contains(polygon: &Polygon, phi: f64, psi: f64) -> bool {
+
+    let n = polygon.len();
+    let mut inside = false;
+
+    for i in 0..n {
+        let [xi, yi] = polygon[i];
+        let [xj, yj] = polygon[(i + n - 1) % n];
+        if (yi > psi) != (yj > psi) && phi < (xj - xi) * (psi - yi) / (yj - yi) + xi {
+            inside = !inside
+        }
+    }
+
+    inside
+}
+
+// Map a dihedral onto [-180, 180), e.g. the [0, 360] range of the Peptide grid
+fn wrap(angle: f64) -> f64 {
+    (angle + 180.).rem_euclid(360.) - 180.
+}
+
+/// The Ramachandran struct classifies (phi, psi) into the regions of the Ramachandran plot
+/// One set of regions is kept per residue type : general, glycine, proline and pre-proline
+/// Points outside of every region are "disallowed"
+#[pyclass]
+pub struct Ramachandran {
+    regionsets: [RegionSet; 4],
+}
+
+const RESIDUE_TYPES: [&str; 4] = ["general", "glycine", "proline", "pre-proline"];
+
+#[pymethods]
+impl Ramachandran {
+
+    // Starts from the default boundaries of every residue type
+    #[new]
+    fn new() -> Ramachandran {
+        Ramachandran {
+            regionsets: [general_regions(), glycine_regions(), proline_regions(), preproline_regions()]
+                .map(|regions| RegionSet { regions, density: None }),
+        }
+    }
+
+    // Replace the boundaries of a region, or add a new region. An empty list of polygons removes the region
+    fn set_region(&mut self, residue_type: &str, region: String, polygons: Vec<Polygon>) -> PyResult<()> {
+
+        if polygons.iter().any(|p| p.len() < 3) {
+            return Err(PuckepyError::new_err(format!("Every polygon of the {} region needs at least 3 vertices.", region)))
+        };
+
+        let regions = &mut self.regionsets[residue_index(residue_type)?].regions;
+        match regions.iter().position(|(name, _)| *name == region) {
+            Some(i) if polygons.is_empty() => { regions.remove(i); },
+            Some(i) => regions[i].1 = polygons,
+            None if polygons.is_empty() => (),
+            None => regions.push((region, polygons)),
+        };
+
+        Ok(())
+    }
+
+    // Points where the density lies below the threshold are disallowed
+    fn set_density(&mut self, residue_type: &str, density: Vec<Vec<f64>>, threshold: f64) -> PyResult<()> {
+
+        if density.is_empty() || density.iter().any(|row| row.len() != density[0].len() || row.is_empty()) {
+            return Err(PuckepyError::new_err("The density should be a non-empty, rectangular grid of (phi, psi)."))
+        };
+
+        self.regionsets[residue_index(residue_type)?].density = Some(DensityGrid { values: density, threshold });
+        Ok(())
+    }
+
+    #[pyo3(signature = (phi, psi, residue_type="general"))]
+    fn classify(&self, phi: f64, psi: f64, residue_type: &str) -> PyResult<String> {
+
+        if !phi.is_finite() || !psi.is_finite() {
+            return Err(OutOfRangeError::new_err(format!("The (phi, psi) of ({}, {}) is not a finite dihedral.", phi, psi)))
+        };
+
+        Ok(self.regionsets[residue_index(residue_type)?].classify(wrap(phi), wrap(psi)).to_string())
+    }
+
+    // Label every point of the Peptide grid, to skip the disallowed points before a scan
+    #[pyo3(signature = (peptide, residue_type="general"))]
+    fn classify_peptide(&self, peptide: &Peptide, residue_type: &str) -> PyResult<Vec<String>> {
+
+        let regionset = &self.regionsets[residue_index(residue_type)?];
+
+        Ok(peptide.phi.iter()
+            .zip(peptide.psi.iter())
+            .map(|(phi, psi)| regionset.classify(wrap(*phi), wrap(*psi)).to_string())
+            .collect())
+    }
+
+    // Label every residue of the Protein, by the regions of its residue type
+    // Residues without a phi or psi (termini, chain breaks) are None
+    fn classify_protein(&self, protein: &Protein) -> PyResult<Vec<Option<String>>> {
+
+        let n = protein.residuenames.len();
+        let mut labels: Vec<Option<String>> = Vec::with_capacity(n);
+
+        for i in 0..n {
+            let precedes_proline = i + 1 < n 
+                && protein.residuenames[i + 1] == "PRO" 
+                && protein.chains[i + 1] == protein.chains[i];
+
+            let residue_type = match protein.residuenames[i].as_str() {
+                "GLY" => "glycine",
+                "PRO" => "proline",
+                _ if precedes_proline => "pre-proline",
+                _ => "general",
+            };
+            let regionset = &self.regionsets[residue_index(residue_type)?];
+
+            labels.push(match (protein.phi[i], protein.psi[i]) {
+                (Some(phi), Some(psi)) => Some(regionset.classify(wrap(phi), wrap(psi)).to_string()),
+                _ => None,
+            });
+        }
+
+        Ok(labels)
+    }
+}
+
+fn residue_index(residue_type: &str) -> PyResult<usize> {
+    match RESIDUE_TYPES.iter().position(|r| *r == residue_type) {
+        Some(i) => Ok(i),
+        None => Err(PuckepyError::new_err(format!("Residue type \"{}\" is not one of general, glycine, proline or pre-proline.", residue_type)))
+    }
+}
+
+
+#[cfg(test)]
+mod test_ramachandran {
+
+    use super::*;
+
+    #[test]
+    fn default_regions() {
+        let rama = Ramachandran::new();
+
+        assert_eq!(rama.classify(-60., -45., "general").unwrap(), "alpha");
+        assert_eq!(rama.classify(-120., 130., "general").unwrap(), "beta");
+        assert_eq!(rama.classify(-120., -175., "general").unwrap(), "beta");
+        assert_eq!(rama.classify(-70., 145., "general").unwrap(), "ppii");
+        // psi = 180 wraps onto -180, which lies in the wrapped piece of the region
+        for residue_type in ["general", "glycine", "proline", "pre-proline"] {
+            for psi in [180., -180., -175.] {
+                assert_eq!(rama.classify(-65., psi, residue_type).unwrap(), "ppii");
+            }
+        }
+        assert_eq!(rama.classify(60., 45., "general").unwrap(), "alpha_L");
+        assert_eq!(rama.classify(60., -150., "general").unwrap(), DISALLOWED);
+        // mirrored beta region of glycine, from the [0, 360] range of the Peptide grid
+        assert_eq!(rama.classify(120., 200., "glycine").unwrap(), "beta");
+        assert_eq!(rama.classify(-120., 130., "proline").unwrap(), DISALLOWED);
+    }
+
+    #[test]
+    fn protein_wraps_like_classify() {
+        // A measured psi of 180 lies on the excluded upper edge of the polygons, unless wrapped onto -180
+        let protein = Protein {
+            chains: vec!["A".into()],
+            residuenames: vec!["ALA".into()],
+            residuenumbers: vec![1],
+            phi: vec![Some(-65.)],
+            psi: vec![Some(180.)],
+            omega: vec![None],
+            cis: vec![None],
+            chi1: vec![None],
+            chi2: vec![None],
+            chi3: vec![None],
+            chi4: vec![None],
+        };
+        let rama = Ramachandran::new();
+
+        assert_eq!(rama.classify_protein(&protein).unwrap(), vec![Some(rama.classify(-65., 180., "general").unwrap())]);
+        assert_eq!(rama.classify_protein(&protein).unwrap(), vec![Some("ppii".to_string())]);
+    }
+
+    #[test]
+    fn density_overrides_polygons() {
+        let mut rama = Ramachandran::new();
+        // only the lower-left quadrant of the plot is populated
+        rama.set_density("general", vec![vec![1., 0.], vec![0., 0.]], 0.5).unwrap();
+
+        assert_eq!(rama.classify(-60., -45., "general").unwrap(), "alpha");
+        assert_eq!(rama.classify(-60., 145., "general").unwrap(), DISALLOWED);
+        assert_eq!(rama.classify(-10., -10., "general").unwrap(), ALLOWED);
+    }
+}
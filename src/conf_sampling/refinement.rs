@@ -0,0 +1,271 @@
+use pyo3::prelude::*;
+
+use crate::conf_sampling::{fivering::FiveringAxes, peptide::PeptideAxes, sixring::{SixringAxes, RHO}};
+use crate::errors::{OutOfRangeError, PuckepyError};
+
+
+/// The axes of a conformational space, as queried by the user
+#[derive(FromPyObject)]
+enum Axes<'py> {
+    Sixring(PyRef<'py, SixringAxes>),
+    Fivering(PyRef<'py, FiveringAxes>),
+    Peptide(PyRef<'py, PeptideAxes>),
+}
+
+/// The geometry of the space the points are refined in
+///     Sphere : the Cremer-Pople sphere of SixringAxes, (theta, phi) in radians
+///     Plane  : the (zx, zy) of FiveringAxes, bounded by the initial grid
+///     Torus  : the (phi, psi) of PeptideAxes, periodic over 360 degrees and bounded by the initial grid
+#[derive(Clone, Copy, PartialEq)]
+enum Space {
+    Sphere,
+    Plane,
+    Torus,
+}
+
+/// Refine a conformational grid around its low-energy regions
+/// Every refine() halves the spacing of the points, and proposes new points around every point
+/// that lies within `threshold` of the lowest energy, until the spacing reaches the `resolution`
+/// The spacing and the resolution are in degrees; on the sphere, as the arc between two points
+#[pyclass]
+pub struct Refinement {
+    space: Space,
+    points: Vec<[f64; 2]>,
+    #[pyo3(get)]
+    energies: Vec<f64>,
+    threshold: f64,
+    resolution: f64,
+    #[pyo3(get)]
+    spacing: f64,
+    #[pyo3(get)]
+    converged: bool,
+    pending: usize,
+    bounds: [(f64, f64); 2],
+}
+
+#[pymethods]
+impl Refinement {
+
+    #[new]
+    fn new(axes: Axes, energies: Vec<f64>, threshold: f64, resolution: f64) -> PyResult<Self> {
+
+        let (space, points) = match axes {
+            Axes::Sixring(a) => (Space::Sphere, zip_points(&a.theta, &a.phi)),
+            Axes::Fivering(a) => (Space::Plane, zip_points(&a.zx, &a.zy)),
+            Axes::Peptide(a) => (Space::Torus, zip_points(&a.x, &a.y)),
+        };
+
+        if points.len() < 2 {
+            return Err(PuckepyError::new_err(format!("The initial grid holds {} points. Expected at least 2 points.", points.len())))
+        };
+
+        if energies.len() != points.len() {
+            return Err(PuckepyError::new_err(format!("An amount of {} energies, not equal to {}, has been queried. Expected an energy for every point.", energies.len(), points.len())))
+        };
+
+        if !(resolution > 0. && threshold >= 0.) {
+            return Err(OutOfRangeError::new_err(format!("The resolution ({}) should be positive and the threshold ({}) should not be negative.", resolution, threshold)))
+        };
+
+        let bounds = [0, 1].map(|i| points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[i]), hi.max(p[i]))));
+        let spacing = median_spacing(space, &points);
+
+        Ok(Refinement { space, points, energies, threshold, resolution, spacing, converged: spacing <= resolution, pending: 0, bounds })
+    }
+
+    /// Propose the points of the next refinement, as the Axes class the Refinement was made with
+    /// Returns empty Axes once converged
+    fn refine(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+
+        if self.pending > 0 {
+            return Err(PuckepyError::new_err(format!("The energies of the {} points of the previous refine() have not been added yet.", self.pending)))
+        };
+
+        if self.converged {
+            return self.to_axes(py, &[])
+        };
+
+        self.spacing = (self.spacing / 2.).max(self.resolution);
+        self.converged = self.spacing <= self.resolution;
+
+        let minimum = self.energies.iter().cloned().fold(f64::INFINITY, f64::min);
+        let low_energy: Vec<[f64; 2]> = self.points.iter().zip(self.energies.iter())
+                            .filter(|(_, e)| **e - minimum <= self.threshold)
+                            .map(|(p, _)| *p)
+                            .collect();
+
+        // Skip the candidates that lie within half of the spacing of an existing point
+        let mut new_points: Vec<[f64; 2]> = vec![];
+        for point in low_energy {
+            for candidate in self.neighbours(point) {
+                if !self.within_bounds(candidate) {
+                    continue
+                };
+
+                let taken = self.points.iter().chain(new_points.iter())
+                                .any(|p| distance(self.space, *p, candidate) < self.spacing / 2.);
+                if !taken {
+                    new_points.push(candidate)
+                };
+            }
+        }
+
+        if new_points.is_empty() {
+            self.converged = true
+        };
+
+        self.pending = new_points.len();
+        self.points.extend_from_slice(&new_points);
+        self.to_axes(py, &new_points)
+    }
+
+    /// Add the energies of the points of the last refine(), in the same order
+    fn add(&mut self, energies: Vec<f64>) -> PyResult<()> {
+
+        if energies.len() != self.pending {
+            return Err(PuckepyError::new_err(format!("An amount of {} energies, not equal to {}, has been queried. Expected an energy for every point of the last refine().", energies.len(), self.pending)))
+        };
+
+        self.energies.extend(energies);
+        self.pending = 0;
+        Ok(())
+    }
+
+    /// Every point sampled so far, as the Axes class the Refinement was made with
+    #[getter]
+    fn axes(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.to_axes(py, &self.points)
+    }
+}
+
+impl Refinement {
+
+    fn to_axes(&self, py: Python<'_>, points: &[[f64; 2]]) -> PyResult<PyObject> {
+
+        let (x, y): (Vec<f64>, Vec<f64>) = points.iter().map(|p| (p[0], p[1])).unzip();
+        Ok(match self.space {
+            Space::Sphere => Py::new(py, SixringAxes { rho: RHO, theta: x, phi: y })?.into_py(py),
+            Space::Plane => Py::new(py, FiveringAxes { zx: x, zy: y })?.into_py(py),
+            Space::Torus => Py::new(py, PeptideAxes { x, y })?.into_py(py),
+        })
+    }
+
+    // The points at the current spacing around `point`
+    // On the sphere, a hexagon in the tangent plane. On the plane and the torus, the eight points of a square grid
+    fn neighbours(&self, point: [f64; 2]) -> Vec<[f64; 2]> {
+
+        match self.space {
+            Space::Sphere => {
+                let arc = self.spacing.to_radians();
+                let (sin_t, cos_t) = point[0].sin_cos();
+                let (sin_p, cos_p) = point[1].sin_cos();
+
+                let p = [sin_t * cos_p, sin_t * sin_p, cos_t];
+                let e_theta = [cos_t * cos_p, cos_t * sin_p, -sin_t];
+                let e_phi = [-sin_p, cos_p, 0.];
+
+                (0..6).map(|k| {
+                    let (sin_a, cos_a) = (k as f64 * 60_f64).to_radians().sin_cos();
+                    let q = [0, 1, 2].map(|i| (arc.cos() * p[i]) + (arc.sin() * ((cos_a * e_theta[i]) + (sin_a * e_phi[i]))));
+                    [q[2].clamp(-1., 1.).acos(), q[1].atan2(q[0]).rem_euclid(std::f64::consts::TAU)]
+                }).collect()
+            },
+            Space::Plane | Space::Torus => {
+                let s = self.spacing;
+                let mut square = vec![];
+                for dx in [-s, 0., s] {
+                    for dy in [-s, 0., s] {
+                        if dx != 0. || dy != 0. {
+                            square.push(self.wrap([point[0] + dx, point[1] + dy]))
+                        };
+                    }
+                }
+                square
+            },
+        }
+    }
+
+    // Wrap a point of the torus onto the convention of the initial grid, [-180, 180) if it holds negative angles, else [0, 360)
+    fn wrap(&self, point: [f64; 2]) -> [f64; 2] {
+
+        if self.space != Space::Torus {
+            return point
+        };
+
+        [0, 1].map(|i| match self.bounds[i].0 < 0. {
+            true => (point[i] + 180.).rem_euclid(360.) - 180.,
+            false => point[i].rem_euclid(360.),
+        })
+    }
+
+    fn within_bounds(&self, point: [f64; 2]) -> bool {
+        match self.space {
+            Space::Sphere => true,
+            _ => (0..2).all(|i| (self.bounds[i].0 - 1e-9..=self.bounds[i].1 + 1e-9).contains(&point[i])),
+        }
+    }
+}
+
+fn zip_points(x: &[f64], y: &[f64]) -> Vec<[f64; 2]> {
+    x.iter().zip(y.iter()).map(|(x, y)| [*x, *y]).collect()
+}
+
+// The distance between two points in degrees; on the sphere, the arc between them
+fn distance(space: Space, a: [f64; 2], b: [f64; 2]) -> f64 {
+
+    match space {
+        Space::Sphere => {
+            let cos_arc = (a[0].cos() * b[0].cos()) + (a[0].sin() * b[0].sin() * (a[1] - b[1]).cos());
+            cos_arc.clamp(-1., 1.).acos().to_degrees()
+        },
+        Space::Plane => ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt(),
+        Space::Torus => {
+            let [dx, dy] = [0, 1].map(|i| ((a[i] - b[i]) + 180.).rem_euclid(360.) - 180.);
+            (dx.powi(2) + dy.powi(2)).sqrt()
+        },
+    }
+}
+
+// The median distance of every point to its nearest neighbour, as the spacing of the initial grid
+// Points that coincide (e.g. 0 and 360 degrees of a periodic axis) are not neighbours
+fn median_spacing(space: Space, points: &[[f64; 2]]) -> f64 {
+
+    let mut nearest: Vec<f64> = points.iter().enumerate().map(|(i, a)| {
+        points.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, b)| distance(space, *a, *b))
+            .filter(|d| *d > 1e-9)
+            .fold(f64::INFINITY, f64::min)
+    }).collect();
+
+    nearest.sort_by(|a, b| a.total_cmp(b));
+    nearest[nearest.len() / 2]
+}
+
+
+#[cfg(test)]
+mod test_refinement {
+
+    use super::*;
+
+    #[test]
+    fn grid_spacing() {
+        let points: Vec<[f64; 2]> = (0..5).flat_map(|x| (0..5).map(move |y| [x as f64 * 10., y as f64 * 10.])).collect();
+        assert_eq!(median_spacing(Space::Plane, &points), 10.);
+
+        // 0 and 360 coincide on the torus
+        let points: Vec<[f64; 2]> = (0..=36).map(|x| [x as f64 * 10., 0.]).collect();
+        assert_eq!(median_spacing(Space::Torus, &points), 10.);
+    }
+
+    #[test]
+    fn sphere_neighbours_at_spacing() {
+        let refinement = Refinement { space: Space::Sphere, points: vec![], energies: vec![], threshold: 0., resolution: 1.,
+                                      spacing: 5., converged: false, pending: 0, bounds: [(0., 0.); 2] };
+
+        let point = [1.2, 4.];
+        for neighbour in refinement.neighbours(point) {
+            assert!((distance(Space::Sphere, point, neighbour) - 5.).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use pyo3::PyResult;
+
+use crate::conf_sampling::sixring::TWOPI;
+use crate::errors::PuckepyError;
+
+// 1 / golden ratio
+const INV_GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+const TWO_POW_32: f64 = 4_294_967_296.;
+
+
+/// The alternatives to the deterministic grid of a sampler. Every sequence returns exactly the queried amount of points
+///     "fibonacci" : the Fibonacci lattice
+///     "sobol"     : the Sobol low-discrepancy sequence
+///     "halton"    : the Halton low-discrepancy sequence, in bases 2 and 3
+///     "random"    : uniform random points, reproducible by their seed
+pub enum Sequence {
+    Fibonacci,
+    Sobol,
+    Halton,
+    Random(u64),
+}
+
+impl Sequence {
+
+    /// Returns None for "grid", the deterministic grid of the sampler
+    pub fn new(sampling: &str, seed: u64) -> PyResult<Option<Sequence>> {
+        match sampling {
+            "grid" => Ok(None),
+            "fibonacci" => Ok(Some(Sequence::Fibonacci)),
+            "sobol" => Ok(Some(Sequence::Sobol)),
+            "halton" => Ok(Some(Sequence::Halton)),
+            "random" => Ok(Some(Sequence::Random(seed))),
+            s => Err(PuckepyError::new_err(format!("Sampling \"{}\" is not one of \"grid\", \"fibonacci\", \"sobol\", \"halton\" or \"random\".", s)))
+        }
+    }
+
+    /// `amount` points in the unit square [0, 1) x [0, 1)
+    pub fn unit_square(&self, amount: usize) -> Vec<[f64; 2]> {
+        match self {
+            Sequence::Fibonacci => (0..amount).map(|i| {
+                [(i as f64 + 0.5) / amount as f64, (i as f64 * INV_GOLDEN_RATIO).fract()]
+            }).collect(),
+            Sequence::Sobol => (0..amount).map(|i| [sobol(i as u32, 0), sobol(i as u32, 1)]).collect(),
+            // Skip the origin, which both radical inverses share
+            Sequence::Halton => (1..=amount).map(|i| [radical_inverse(i, 2), radical_inverse(i, 3)]).collect(),
+            Sequence::Random(seed) => {
+                let mut rng = SplitMix64(*seed);
+                (0..amount).map(|_| [rng.next_f64(), rng.next_f64()]).collect()
+            },
+        }
+    }
+
+    /// `amount` points on the rectangle [x_range) x [y_range), as the x and y values of every point
+    pub fn rectangle(&self, amount: usize, x_range: (f64, f64), y_range: (f64, f64)) -> (Vec<f64>, Vec<f64>) {
+        self.unit_square(amount).iter()
+            .map(|[u, v]| (x_range.0 + u * (x_range.1 - x_range.0), y_range.0 + v * (y_range.1 - y_range.0)))
+            .unzip()
+    }
+
+    /// `amount` points on the unit sphere, as the spherical (theta [0, pi], phi [0, 2pi)) in radians
+    /// The unit square is mapped uniformly in cos(theta), so that equal areas of the sphere are sampled equally.
+    /// For the Fibonacci lattice, this results in the Fibonacci sphere
+    pub fn unit_sphere(&self, amount: usize) -> Vec<[f64; 2]> {
+        self.unit_square(amount).iter()
+            .map(|[u, v]| [(1. - (2. * u)).acos(), TWOPI * v])
+            .collect()
+    }
+}
+
+/// The amount of points a sequence samples, which defaults to the amount of the grid it replaces
+/// The grid is set by its interval, so it does not take an `amount`
+pub fn amount_of_points(sequence: &Option<Sequence>, amount: Option<usize>, grid_amount: usize) -> PyResult<usize> {
+    match (sequence, amount) {
+        (None, Some(_)) => Err(PuckepyError::new_err("An amount of points is only queried by the \"fibonacci\", \"sobol\", \"halton\" or \"random\" sampling. The grid is set by its interval.")),
+        (_, amount) => Ok(amount.unwrap_or(grid_amount)),
+    }
+}
+
+// The Sobol sequence in 32-bit fixed point, for the first two dimensions, in natural (not Gray code) order
+// Dimension 0 is the van der Corput sequence (v_k+1 = v_k / 2)
+// Dimension 1 is generated by the primitive polynomial x + 1 (m_k = 2 m_k-1 XOR m_k-1 -> 1, 3, 5, 15, ...)
+fn sobol(index: u32, dimension: usize) -> f64 {
+
+    let mut direction: u32 = 1 << 31;
+    let mut x: u32 = 0;
+    let mut i = index;
+
+    while i != 0 {
+        if i & 1 == 1 { x ^= direction };
+        i >>= 1;
+        direction = match dimension {
+            0 => direction >> 1,
+            _ => direction ^ (direction >> 1),
+        };
+    }
+
+    x as f64 / TWO_POW_32
+}
+
+// Mirror the digits of `index` in `base` around the decimal point
+fn radical_inverse(mut index: usize, base: usize) -> f64 {
+
+    let mut inverse = 0.;
+    let mut digit_weight = 1. / base as f64;
+
+    while index > 0 {
+        inverse += (index % base) as f64 * digit_weight;
+        index /= base;
+        digit_weight /= base as f64;
+    }
+
+    inverse
+}
+
+// Steele, Lea & Flood, OOPSLA 2014. Small and fast, and every seed gives a well mixed stream
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // The upper 53 bits fill the mantissa, for a uniform value in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+
+#[cfg(test)]
+mod test_sequences {
+
+    use std::f64::consts::PI;
+    use super::*;
+
+    #[test]
+    fn sobol_first_points() {
+        let points = Sequence::Sobol.unit_square(4);
+        assert_eq!(points, vec![[0., 0.], [0.5, 0.5], [0.25, 0.75], [0.75, 0.25]]);
+    }
+
+    #[test]
+    fn halton_first_points() {
+        let points = Sequence::Halton.unit_square(2);
+        assert_eq!(points[0], [0.5, 1. / 3.]);
+        assert_eq!(points[1], [0.25, 2. / 3.]);
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        let points = Sequence::Random(7).unit_square(100);
+        assert_eq!(points, Sequence::Random(7).unit_square(100));
+        assert_ne!(points, Sequence::Random(8).unit_square(100));
+        assert!(points.iter().flatten().all(|x| (0. ..1.).contains(x)));
+    }
+
+    #[test]
+    fn exact_amount_on_sphere() {
+        for sequence in [Sequence::Fibonacci, Sequence::Sobol, Sequence::Halton, Sequence::Random(0)] {
+            let points = sequence.unit_sphere(631);
+            assert_eq!(points.len(), 631);
+            assert!(points.iter().all(|[theta, phi]| (0. ..=PI).contains(theta) && (0. ..TWOPI).contains(phi)));
+        }
+    }
+}
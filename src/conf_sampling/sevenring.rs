@@ -0,0 +1,142 @@
+use pyo3::{pyclass, pymethods, PyResult};
+use ndarray::Array1;
+
+use crate::errors::OutOfRangeError;
+use crate::geometry::molecule_ops::dihedral;
+use crate::formalism::inversion::{sevenring::invert_sevenring, RingGeometry};
+
+
+/// Struct to keep the nu1 .. nu4 constraint torsions of the sampled seven-membered rings
+/// nu1 : 1-2-3-4, nu2 : 2-3-4-5, nu3 : 3-4-5-6, nu4 : 4-5-6-7 (1-based atoms, as in Fivering's nu1 and nu3)
+/// With the bondlengths and angles fixed, these four consecutive torsions fix the conformation of the ring
+/// public `coordinates` field : Vec<[[f64;3];7]>. Only kept if queried, else empty
+#[pyclass(get_all)]
+pub struct Sevenring {
+    pub nu1 : Vec<f64>,
+    pub nu2 : Vec<f64>,
+    pub nu3 : Vec<f64>,
+    pub nu4 : Vec<f64>,
+    pub coordinates : Vec<[[f64;3];7]>,
+}
+
+#[pymethods]
+impl Sevenring {
+
+    #[new]
+    #[pyo3(signature = (amplitude_interval, phase_interval, max_amplitude=1.0, keep_coordinates=false))]
+    fn new(amplitude_interval: u16, phase_interval: u16, max_amplitude: f64, keep_coordinates: bool) -> PyResult<Self> {
+
+        let conformers = SAxes::new(amplitude_interval, phase_interval, max_amplitude)?.conformers();
+
+        let mut nu = [(); 4].map(|_| Vec::with_capacity(conformers.len()));
+        for (_, ring) in conformers.iter() {
+            for (k, torsions) in nu.iter_mut().enumerate() {
+                let t = dihedral(ring[k], ring[k + 1], ring[k + 2], ring[k + 3]);
+                torsions.push(if t < 0. { t + 360. } else { t }); // Make values ORCA-ready
+            }
+        }
+
+        let coordinates = match keep_coordinates {
+            true => conformers.iter().map(|(_, ring)| *ring).collect(),
+            false => vec![],
+        };
+
+        let [nu1, nu2, nu3, nu4] = nu;
+        Ok(Self { nu1, nu2, nu3, nu4, coordinates })
+    }
+}
+
+/// The Cremer-Pople coordinates (q2, phi2, q3, phi3) of the conformers sampled by Sevenring, in the same order
+#[pyclass(get_all)]
+pub struct SevenringAxes {
+    pub q2 : Vec<f64>,
+    pub phi2 : Vec<f64>,
+    pub q3 : Vec<f64>,
+    pub phi3 : Vec<f64>,
+}
+
+#[pymethods]
+impl SevenringAxes {
+
+    #[new]
+    #[pyo3(signature = (amplitude_interval, phase_interval, max_amplitude=1.0))]
+    fn new(amplitude_interval: u16, phase_interval: u16, max_amplitude: f64) -> PyResult<Self> {
+
+        let conformers = SAxes::new(amplitude_interval, phase_interval, max_amplitude)?.conformers();
+
+        let mut axes = SevenringAxes {
+            q2: Vec::with_capacity(conformers.len()),
+            phi2: Vec::with_capacity(conformers.len()),
+            q3: Vec::with_capacity(conformers.len()),
+            phi3: Vec::with_capacity(conformers.len()),
+        };
+
+        for ([q2, phi2, q3, phi3], _) in conformers {
+            axes.q2.push(q2);
+            axes.phi2.push(phi2);
+            axes.q3.push(q3);
+            axes.phi3.push(phi3);
+        }
+
+        Ok(axes)
+    }
+}
+
+
+/// The 4D grid of seven-membered ring space : 
+/// q2 and q3 : [0, max_amplitude] (Angstrom), kept where the total amplitude sqrt(q2² + q3²) <= max_amplitude
+/// phi2 and phi3 : [0, 360) (degrees). Sampled once where its amplitude is 0., as every phase is the same conformer
+/// Can remain a private struct, as this only is required to build the Sevenring struct
+struct SAxes {
+    amplitudes : Vec<f64>,
+    phases : Vec<f64>,
+    max_amplitude : f64,
+}
+
+impl SAxes {
+
+    fn new(amplitude_interval: u16, phase_interval: u16, max_amplitude: f64) -> PyResult<SAxes> {
+
+        if !(max_amplitude.is_finite() && max_amplitude > 0.) {
+            return Err(OutOfRangeError::new_err(format!("max_amplitude value ({}) should be a positive number.", max_amplitude)))
+        };
+
+        let phase_step = 360. / phase_interval as f64;
+
+        Ok(SAxes {
+            amplitudes: Array1::linspace(0., max_amplitude, amplitude_interval as usize).into_raw_vec(),
+            phases: (0..phase_interval).map(|i| i as f64 * phase_step).collect(),
+            max_amplitude,
+        })
+    }
+
+    // Invert every grid point with tetrahedral bondlengths and angles. Only the angles on atoms 2, 3, 5 and 6
+    // are enforced, the ones on atoms 1, 4 and 7 follow from the closure of the ring
+    // Conformers that cannot close with this geometry are left out
+    fn conformers(&self) -> Vec<([f64; 4], [[f64; 3]; 7])> {
+
+        let geometry = RingGeometry::tetrahedral(7);
+        let phases_of = |q: f64| if q == 0. { &self.phases[..1.min(self.phases.len())] } else { &self.phases[..] };
+
+        let mut conformers = vec![];
+        for &q2 in self.amplitudes.iter() {
+            for &q3 in self.amplitudes.iter() {
+                // allow for the rounding of linspace on the boundary
+                if (q2.powi(2) + q3.powi(2)).sqrt() > self.max_amplitude + 1e-9 {
+                    continue
+                };
+
+                for &phi2 in phases_of(q2) {
+                    for &phi3 in phases_of(q3) {
+                        let ring = invert_sevenring(q2, phi2, q3, phi3, &geometry);
+                        if ring.iter().flatten().all(|x| x.is_finite()) {
+                            conformers.push(([q2, phi2, q3, phi3], ring));
+                        };
+                    }
+                }
+            }
+        }
+
+        conformers
+    }
+}
@@ -1,7 +1,8 @@
-use pyo3::{pyclass, pymethods};
+use pyo3::{pyclass, pymethods, PyResult};
 use ndarray::{Array1, Array2, ArrayBase, DataOwned, Ix2};
 
 // Crate imports
+use crate::conf_sampling::sequences::Sequence;
 use crate::geometry::fundamental_ops::{subtract_arr, RotationMatrix, Coordinate, RotMatrix, LinAlg};
 use crate::geometry::molecule_ops::dihedral;
 
@@ -15,21 +16,39 @@ pub const RHO : f64 = 0.67; // radius of the sphere; constant
 /// public `alpha1` field : Vec<f64>
 /// public `alpha2` field : Vec<f64>
 /// public `alpha3` field : Vec<f64>
+/// public `coordinates` field : Vec<[[f64;3];6]>. Only kept if queried, else empty
 #[pyclass(get_all)]
 pub struct Sixring {
     pub alpha1 : Vec<f64>,
     pub alpha2 : Vec<f64>,
     pub alpha3 : Vec<f64>,
+    pub coordinates : Vec<[[f64;3];6]>,
 }
 
 #[pymethods]
 impl Sixring {
 
     #[new]
-    pub fn new(amount : usize) -> Self {
-        let (globe_points, corrected_amount) = equidistance_sphere(amount as u16);
+    #[pyo3(signature = (amount, keep_coordinates=false, sampling="grid", seed=0))]
+    pub fn new(amount : usize, keep_coordinates: bool, sampling: &str, seed: u64) -> PyResult<Self> {
+        let (theta, phi) = sphere_points(amount, Sequence::new(sampling, seed)?);
+        Ok(Sixring::from_points(&theta, &phi, keep_coordinates))
+    }
+
+    /// The constraint torsions of the points of a SixringAxes, e.g. those of a Refinement
+    #[staticmethod]
+    #[pyo3(signature = (axes, keep_coordinates=false))]
+    fn from_axes(axes: &SixringAxes, keep_coordinates: bool) -> Self {
+        Sixring::from_points(&axes.theta, &axes.phi, keep_coordinates)
+    }
+}
 
-        let zj = cremerpople_evelation(&globe_points, corrected_amount);
+impl Sixring {
+
+    fn from_points(theta: &[f64], phi: &[f64], keep_coordinates: bool) -> Self {
+        let corrected_amount = phi.len();
+
+        let zj = cremerpople_evelation(theta, phi);
 
         let projection = zj.projection_and_partition(corrected_amount);
 
@@ -49,10 +68,17 @@ impl Sixring {
             a3.push(dihedral(pyr.p3, pyr.p5, pyr.p1, pyr.p6));
         };
 
+        // The Cartesian coordinates of every conformer, to write out afterwards
+        let coordinates = match keep_coordinates {
+            true => vec_of_pyranoses.iter().map(|pyr| [pyr.p1, pyr.p2, pyr.p3, pyr.p4, pyr.p5, pyr.p6]).collect(),
+            false => vec![],
+        };
+
         Self { 
             alpha1: a1.iter().map(|x| if x < &0. {x + 360.} else {*x}).collect(),
             alpha2: a2.iter().map(|x| if x < &0. {x + 360.} else {*x}).collect(),
             alpha3: a3.iter().map(|x| if x < &0. {x + 360.} else {*x}).collect(),
+            coordinates,
         }
 
     }
@@ -69,47 +95,63 @@ pub struct SixringAxes {
 impl SixringAxes {
 
     #[new]
-    fn new(amount : usize) -> Self {
+    #[pyo3(signature = (amount, sampling="grid", seed=0))]
+    fn new(amount : usize, sampling: &str, seed: u64) -> PyResult<Self> {
 
-        let (globe_points, corrected_amount) = equidistance_sphere(amount as u16);
-        let mut theta_vec: Vec<f64> = Vec::with_capacity(corrected_amount);
-        let mut phi_vec: Vec<f64> = Vec::with_capacity(corrected_amount);
+        let (theta, phi) = sphere_points(amount, Sequence::new(sampling, seed)?);
 
-        let mut idx_theta: usize = 0;
-        for i in 0..corrected_amount { 
-            if (globe_points.phi[i] == 0.0) && i != 0 {
-                idx_theta += 1 
-            };
+        Ok(Self {
+            rho: RHO,
+            theta,
+            phi,
+        })
+    }
+}
 
-            theta_vec.push(globe_points.theta[idx_theta]);
-            phi_vec.push(globe_points.phi[i]);
-        }
-        
-        Self {
-            rho: globe_points.rho,
-            theta: theta_vec,
-            phi: phi_vec,
-        }
+
+/// The spherical (theta, phi) of every point on the Cremer-Pople sphere
+/// Without a sequence, the points lie on the equidistant grid, and their amount is approximated
+/// A sequence returns exactly `amount` points
+fn sphere_points(amount: usize, sequence: Option<Sequence>) -> (Vec<f64>, Vec<f64>) {
+
+    if let Some(sequence) = sequence {
+        return sequence.unit_sphere(amount).into_iter().map(|[theta, phi]| (theta, phi)).unzip()
+    };
+
+    let (globe_points, corrected_amount) = equidistance_sphere(amount);
+    let mut theta_vec: Vec<f64> = Vec::with_capacity(corrected_amount);
+    let mut phi_vec: Vec<f64> = Vec::with_capacity(corrected_amount);
+
+    // the way we generate the sphere is in layered circles.
+    // every new circle, we start off again at phi == 0.0
+    // if we move to a new layer; we have to the next theta value
+    let mut idx_theta: usize = 0;
+    for i in 0..corrected_amount { 
+        if (globe_points.phi[i] == 0.0) && i != 0 {
+            idx_theta += 1 
+        };
+
+        theta_vec.push(globe_points.theta[idx_theta]);
+        phi_vec.push(globe_points.phi[i]);
     }
+
+    (theta_vec, phi_vec)
 }
 
 
 
-/// The axes to iterate over for sixring molecules : 
-/// public `rho` field : f64 . Standard value of 0.67
+/// The axes to iterate over for sixring molecules, on a sphere of radius RHO : 
 /// public `theta` field : Array1<f64>. [0, pi] or [0, 180]
 /// public `phi` field : Array1<f64>. [0, 2pi] or [0, 360]
 struct SAxes {
-    pub rho : f64,
     pub theta : Array1<f64>,
     pub phi : Array1<f64>,
 }
 
 
 impl SAxes {
-    pub fn new(m_theta : usize, rho: f64, amount: usize) -> Self {
+    pub fn new(m_theta : usize, amount: usize) -> Self {
         Self {
-            rho, 
             theta : Array1::<f64>::zeros(m_theta),
             phi : Array1::<f64>::zeros(amount),
         }
@@ -124,32 +166,20 @@ impl SAxes {
 /// Cremer-Pople sphere, where every point represents a specific conformation
 ///
 ///
-fn equidistance_sphere(amount : u16 ) -> (SAxes, usize) {
-    // Set a value as surface area / points
-    let corrected_amount: f64 = corrected_amount_by_sphere_radius(amount as f64);
-    let a: f64 = ( 4. * PI * RHO.powi(2)) / corrected_amount;
+fn equidistance_sphere(amount : usize ) -> (SAxes, usize) {
+    let rings = equidistance_partition(amount);
+    let m_theta = rings.len() as f64;
 
     let mut idx : u32 = 0; // indexing the arrays
 
-    // Set d as the square root of a
-    let d: f64 = a.sqrt();
-
-    // Round of the ratio between PI and the value of d
-    let m_theta: f64 = (PI / d).round();
+    let amount_sizeof: usize = rings.iter().sum();
+    let mut globe = SAxes::new(rings.len(), amount_sizeof);
 
-    // Set d_theta and d_phi
-    let d_theta: f64 = PI / m_theta;
-    let d_phi: f64 = a / d_theta;
+    for (m, m_phi) in rings.iter().enumerate() {
+        globe.theta[m] = (PI * (m as f64 + 0.5)) / m_theta;
 
-    let amount_sizeof: usize = corrected_amount_to_size_up_arrays(m_theta, d_phi);
-    let mut globe = SAxes::new(m_theta as usize, RHO, amount_sizeof as usize);
-
-    for m in 0..m_theta as u32 {
-        globe.theta[m as usize] = (PI * (m as f64 + 0.5)) / m_theta;
-        let m_phi: f64 = (TWOPI * globe.theta[m as usize].sin() / d_phi).round();
-
-        for n in 0..m_phi as u32 {
-            globe.phi[idx as usize] = (TWOPI * n as f64) / m_phi;
+        for n in 0..*m_phi as u32 {
+            globe.phi[idx as usize] = (TWOPI * n as f64) / *m_phi as f64;
             idx += 1;
             
         }
@@ -158,19 +188,27 @@ fn equidistance_sphere(amount : u16 ) -> (SAxes, usize) {
     (globe, amount_sizeof) // return the struct containing all the points on the surface of our globe
 }
 
+/// The equal-area partition of the sphere that underlies the equidistant grid, as the amount of points on every ring
+/// Ring m spans theta [m, m + 1] * pi / rings, and its points lie on its center, at phi = 2pi * n / points
+pub fn equidistance_partition(amount : usize) -> Vec<usize> {
+    // Set a value as surface area / points
+    let corrected_amount: f64 = corrected_amount_by_sphere_radius(amount as f64);
+    let a: f64 = ( 4. * PI * RHO.powi(2)) / corrected_amount;
 
-fn corrected_amount_to_size_up_arrays(m_theta : f64, d_phi : f64) -> usize {
-    // Counting the amount of points that are actually generated
-    let mut size_array: u32 = 0;
+    // Set d as the square root of a
+    let d: f64 = a.sqrt();
 
-    for m in 0..m_theta as u32 {
-        let theta: f64 = (PI * (m as f64 + 0.5)) / m_theta;
-        let m_phi: f64 = (TWOPI * theta.sin() / d_phi).round();
-        size_array += m_phi as u32;
+    // Round of the ratio between PI and the value of d
+    let m_theta: f64 = (PI / d).round();
 
-    };
+    // Set d_theta and d_phi
+    let d_theta: f64 = PI / m_theta;
+    let d_phi: f64 = a / d_theta;
 
-    size_array as usize // return exact amount of points that will be sampled over
+    (0..m_theta as u32).map(|m| {
+        let theta: f64 = (PI * (m as f64 + 0.5)) / m_theta;
+        (TWOPI * theta.sin() / d_phi).round() as usize
+    }).collect()
 }
 
 /// Markus Deserno's mathematics only works out if we commit to a radius = 1 unit
@@ -189,32 +227,23 @@ fn corrected_amount_by_sphere_radius(num : f64) -> f64 {
 /// Calculate the local elevation z_j for the Cremer-Pople coordinate prompted 
 ///
 ///
-fn cremerpople_evelation(sphere : &SAxes, amount: usize) -> Array2<f64> {
+fn cremerpople_evelation(theta : &[f64], phi: &[f64]) -> Array2<f64> {
     // spherical coordinates are by default in radians
 
     // 6 atomic elevations (Z_j) for any set of (r, theta, phi)
-    let mut z: Array2<f64> = Array2::zeros((amount, Z_SIZE));
+    let mut z: Array2<f64> = Array2::zeros((phi.len(), Z_SIZE));
 
     // Set two constant values
-    let constant1 = [0.,1.,2.,3.,4.,5.].map(|j| ((TWOPI * j) / 3.));
+    let constant1 = [0.,1.,2.,3.,4.,5.].map(|j| (TWOPI * j) / 3.);
     let constant2 = [0, 1, 2, 3, 4, 5].map(|j| (-1_f64).powi(j));
 
     // Set some more constant values
     let one_over_sqrt_three: f64 = 3_f64.sqrt() ;
     let one_over_sqrt_six: f64 = 6_f64.sqrt() ;
 
-    let mut idx_theta: usize = 0;
-    for i in 0..amount { 
-        // the way we generate the sphere is in layered circles.
-        // every new circle, we start off again at phi == 0.0
-        // if we move to a new layer; we have to the next theta value
-        // NOTE :the theta and phi arrays are not of the same length
-        if (sphere.phi[i] == 0.0) && i != 0 {
-            idx_theta += 1 
-        };
-
+    for i in 0..phi.len() { 
         for j in 0..Z_SIZE {
-            z[[i, j]] = calculate_local_elevation(sphere.rho, sphere.theta[idx_theta], sphere.phi[i], constant1[j], constant2[j],
+            z[[i, j]] = calculate_local_elevation(RHO, theta[i], phi[i], constant1[j], constant2[j],
                                                   one_over_sqrt_three, one_over_sqrt_six);
         }
     }
@@ -264,14 +293,14 @@ where
     /// The `self` parameter is actually the local_elevation matrix (z_j)
     fn projection_and_partition(&self, sphere_size : usize) -> ProjectionPartition {
 
-        let mut rpij_arr = Array2::<f64>::zeros((sphere_size as usize, Z_SIZE));
-        let mut cospb_arr = Array2::<f64>::zeros((sphere_size as usize, Z_SIZE));
-        let mut sinpb_arr = Array2::<f64>::zeros((sphere_size as usize, Z_SIZE));
-        let mut op_arr = Array1::<f64>::zeros(sphere_size as usize);
-        let mut qp_arr = Array1::<f64>::zeros(sphere_size as usize);
-        let mut oq_arr = Array1::<f64>::zeros(sphere_size as usize);
+        let mut rpij_arr = Array2::<f64>::zeros((sphere_size, Z_SIZE));
+        let mut cospb_arr = Array2::<f64>::zeros((sphere_size, Z_SIZE));
+        let mut sinpb_arr = Array2::<f64>::zeros((sphere_size, Z_SIZE));
+        let mut op_arr = Array1::<f64>::zeros(sphere_size);
+        let mut qp_arr = Array1::<f64>::zeros(sphere_size);
+        let mut oq_arr = Array1::<f64>::zeros(sphere_size);
 
-        for i in 0..sphere_size as usize {
+        for i in 0..sphere_size {
             
             for j in 0..Z_SIZE {
                 rpij_arr[[i,j]] = ( RIJSQ - 
@@ -346,12 +375,8 @@ pub struct SixRingAtoms {
 impl SixRingAtoms {
     fn calculate_geometric_center(&self) -> Coordinate {
 
-    let mut p_g = [0.;3];
-
-    for i in 0..3 { // 0 -> 1 -> 2
-        p_g[i] = (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i] + self.p6[i]) / 6.
-    };
-    p_g
+    // 0 -> 1 -> 2
+    [0, 1, 2].map(|i| (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i] + self.p6[i]) / 6.)
     }
     
 }
@@ -424,7 +449,7 @@ pub fn reconstruct_coordinates(proj : ProjectionPartition, sphere_size : usize,
                                 0.,
                                 0.]; //pO
         let p_p : Coordinate = [(proj.op[i].powi(2) + proj.oq[i].powi(2) - proj.qp[i].powi(2))/(2. * proj.oq[i]),
-                                (proj.op[i].powi(2) - ( ( (proj.op[i].powi(2) + proj.oq[i].powi(2) - proj.qp[i].powi(2)).powi(2) ) / (4. * proj.op[i].powi(2)) ) ).sqrt(),
+                                (proj.op[i].powi(2) - ( ( (proj.op[i].powi(2) + proj.oq[i].powi(2) - proj.qp[i].powi(2)).powi(2) ) / (4. * proj.oq[i].powi(2)) ) ).sqrt(),
                                 0.]; //pP
         let p_q : Coordinate = [proj.oq[i],
                                 0.,
@@ -0,0 +1,18 @@
+//! Python exceptions raised by the puckepy API
+//!
+//! Every exception inherits from `PuckepyError`, so a batch pipeline can catch all of them at once
+//!
+//! PuckepyError
+//!  ├── FileFormatError   : a file cannot be read or parsed in its expected format
+//!  ├── AtomNotFoundError : a queried atom name does not exist in the parsed molecule
+//!  ├── RingSizeError     : the amount of atoms does not match the ring system
+//!  └── OutOfRangeError   : a value or an index lies outside its permitted range
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+
+create_exception!(puckepy, PuckepyError, PyException, "Base class of all exceptions raised by puckepy.");
+create_exception!(puckepy, FileFormatError, PuckepyError, "A file cannot be read or parsed in its expected format.");
+create_exception!(puckepy, AtomNotFoundError, PuckepyError, "A queried atom name does not exist in the parsed molecule.");
+create_exception!(puckepy, RingSizeError, PuckepyError, "The amount of atoms does not match the ring system.");
+create_exception!(puckepy, OutOfRangeError, PuckepyError, "A value or an index lies outside its permitted range.");
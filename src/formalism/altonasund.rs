@@ -1,9 +1,16 @@
-use pyo3::{pymethods, pyclass};
+use numpy::{IntoPyArray, PyReadonlyArray3};
+use pyo3::{pymethods, pyclass, PyResult, Python};
 
+use crate::errors::{AtomNotFoundError, OutOfRangeError, PuckepyError, RingSizeError};
 use crate::geometry::molecule_ops::dihedral;
 use crate::formalism::{
     moleculefile::Pdb,
-    search_atomname::FindString,
+    search_atomname::{FindString, SelectCoordinates},
+    iupac::{fivering_conformer, Conformer},
+    ringperception::{rings_of_size, NamedRings},
+    trajectory::{map_frames, FramesArray},
+    inversion::RingGeometry,
+    interconversion::as_to_cp5,
 };
 
 const PIOVERFIVE: f64 = 0.628318530718;
@@ -16,30 +23,30 @@ pub struct AS {
 }
 
 #[pymethods]
+#[allow(clippy::wrong_self_convention)]
 impl AS {
 
     #[new]
-    fn new(amplitude: f64, phase_angle: f64) -> AS {
+    fn new(amplitude: f64, phase_angle: f64) -> PyResult<AS> {
         if amplitude > 1. {
-            panic!("amplitude value is larger than 1.")
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) is larger than 1.", amplitude)))
         }
 
         if !(0.0..=360.0).contains(&phase_angle) {
-            panic!("phase_angle value should be within the range of 0 -> 360")
+            return Err(OutOfRangeError::new_err(format!("phase_angle value ({}) should be within the range of 0 -> 360", phase_angle)))
         }
-        AS { amplitude, phase_angle }
+        Ok(AS { amplitude, phase_angle })
     }
     
     
     // Find the indices of the atomnames and pass them to self.as_from_indices()
-    fn from_atomnames(&self, pdb: &Pdb, query_names: Vec<String>) -> (f64, f64) {
+    fn from_atomnames(&self, pdb: &Pdb, query_names: Vec<String>) -> PyResult<(f64, f64)> {
 
         // Make empty vec :
         let mut indices: Vec<usize> = Vec::with_capacity(6);
 
-        let _ = match query_names.len() {
-            5 => 5,
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        if query_names.len() != 5 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 5, has been queried. Expected 5 elements.", query_names.len())))
         };
 
 
@@ -47,7 +54,7 @@ impl AS {
         for name in query_names.iter() {
             match pdb.atomnames.at_position(name) {
                 Ok(a) => indices.push(a),
-                Err(()) => panic!("Could not find \"{}\" atomname in the queried pdb.", name)
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
             }
         }
 
@@ -56,20 +63,18 @@ impl AS {
     }
 
     // Calculate Altona Sundaralingam formalism by the indices
-    fn from_indices(&self, coordinates: Vec<[f64;3]>, indices: Vec<usize>) -> (f64, f64) {
+    fn from_indices(&self, coordinates: Vec<[f64;3]>, indices: Vec<usize>) -> PyResult<(f64, f64)> {
         
-        let mut molarray: Vec<[f64; 3]> = vec![];
-
-        let _ = match indices.len() {
-            5 => 5,
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        if indices.len() != 5 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 5, has been queried. Expected 5 elements.", indices.len())))
         };
 
-        for idx in indices {
-            molarray.push(coordinates[idx])
-        }
+        let molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
 
-        altona_sundaralingam(&mut molarray)
+        Ok(altona_sundaralingam(&molarray))
 
 //       match cremerpople::cremer_pople(&mut molarray) {
 //           MemberedRing::Five(a) => a.to_as(),
@@ -77,13 +82,50 @@ impl AS {
 //       }
     }
 
+    // Calculate Altona Sundaralingam formalism on every frame of an (n_frames, n_atoms, 3) array
+    fn from_trajectory<'py>(&self, py: Python<'py>, coordinates: PyReadonlyArray3<'py, f64>, indices: Vec<usize>) 
+        -> PyResult<(FramesArray<'py>, FramesArray<'py>)> {
+
+        let frames = map_frames(py, &coordinates, &indices, 5, |molarray| altona_sundaralingam(&molarray))?;
+
+        let (amplitude, phase_angle): (Vec<f64>, Vec<f64>) = frames.into_iter().unzip();
+
+        Ok((amplitude.into_pyarray_bound(py), phase_angle.into_pyarray_bound(py)))
+    }
+
+    // Find every five-membered ring in the pdb and calculate its Altona Sundaralingam formalism
+    fn from_rings(&self, pdb: &Pdb) -> PyResult<NamedRings<(f64, f64)>> {
+
+        Ok(rings_of_size(pdb, 5)?
+            .into_iter()
+            .map(|(names, molarray)| (names, altona_sundaralingam(&molarray)))
+            .collect())
+    }
+
+    // Assign the nearest IUPAC canonical conformer on the pseudorotation wheel
+    #[pyo3(signature = (atomnames=None))]
+    fn conformer(&self, atomnames: Option<Vec<String>>) -> PyResult<Conformer> {
+        fivering_conformer(self.phase_angle, atomnames)
+    }
+
+    // Convert to the Cremer-Pople (amplitude, phase_angle), exactly by solving for the CP5 coordinates whose ring,
+    // inverted with the geometry, measures these AS coordinates, or approximately by phi2 = P - 90 and q2 = nu_max (mean bondlength) / 2 sqrt(2)
+    #[pyo3(signature = (bondlengths=None, angles=None, elements=None, exact=true))]
+    fn to_cp5(&self, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>, exact: bool) -> PyResult<(f64, f64)> {
+        let geometry = RingGeometry::new(5, bondlengths, angles, elements)?;
+        match as_to_cp5(self.amplitude, self.phase_angle, &geometry, exact) {
+            Some(cp5) => Ok(cp5),
+            None => Err(PuckepyError::new_err(format!("The AS coordinates ({}, {}) could not be converted exactly. The Newton steps did not converge.", self.amplitude, self.phase_angle)))
+        }
+    }
+
 }
 
 
 
 
 
-/// (b) For abbreviated nomenclature see M. Sundaralingam, J. A". Chem. SOC.,93, 6644 (1971). and references therein.
+// (b) For abbreviated nomenclature see M. Sundaralingam, J. A". Chem. SOC.,93, 6644 (1971). and references therein.
 
 // tan(P) = (theta2 + theta4 - theta1 - theta3 ) / (2 * theta0 * (sin36° + sin72°))
 // tan(P) = (nu4 + nu1 - nu3 - nu0 ) / (2 * nu2 * (sin36° + sin72°))
@@ -104,7 +146,7 @@ impl AS {
 //     Instead of AS's assumption of C2' -> C3' -> C4' -> O4' -> C1'  
 // 
 // Function courtesy of Cpptraj Github : https://github.com/Amber-MD/cpptraj/blob/master/src/TorsionRoutines.cpp
-fn altona_sundaralingam(coordinates: &Vec<[f64;3]>) -> (f64, f64) {
+pub fn altona_sundaralingam(coordinates: &[[f64;3]]) -> (f64, f64) {
     
     //  we follow the order of O4' - C1' - C2' - C3' - C4' when the atoms are being passed to the function
     //  NB: cpptraj follows  C1' - C2' - C3' - C4' - O4' when the atoms are being passed to the function
@@ -115,13 +157,13 @@ fn altona_sundaralingam(coordinates: &Vec<[f64;3]>) -> (f64, f64) {
     let nu4 = dihedral(coordinates[3], coordinates[4], coordinates[0], coordinates[1]); // nu4 -> theta2 -> v3
 
     // this part is courtesy of the AS_Pucker() function in https://github.com/Amber-MD/cpptraj/blob/master/src/TorsionRoutines.cpp 
-    let a = ((nu2 + 1. ) +// nu0 + cos(1.) == nu0 + 1
+    let a = (nu2 + // theta0 * cos(0.) == theta0
              (nu3 * (4. * PIOVERFIVE).cos()) +
              (nu4 * (8. * PIOVERFIVE).cos()) +
              (nu0 * (12. * PIOVERFIVE).cos()) +
              (nu1 * (16. * PIOVERFIVE).cos())) * 0.4;
 
-    let b =  (nu2 + // nu0 + sin(0.) == nu0 + 0
+    let b =  (// theta0 * sin(0.) == 0
              (nu3 * (4. * PIOVERFIVE).sin()) +
              (nu4 * (8. * PIOVERFIVE).sin()) +
              (nu0 * (12. * PIOVERFIVE).sin()) +
@@ -1,15 +1,21 @@
 use std::f64::consts::PI;
-use pyo3::{pyclass, pymethods};
+use numpy::{IntoPyArray, PyReadonlyArray3};
+use pyo3::{pyclass, pymethods, PyResult, Python};
 
 use crate::conf_sampling::sixring::TWOPI;
+use crate::errors::{AtomNotFoundError, OutOfRangeError, RingSizeError};
 use crate::formalism::{
     moleculefile::Pdb,
     PIS_IN_180,
-    search_atomname::FindString
+    search_atomname::{FindString, SelectCoordinates},
+    iupac::{fivering_conformer, sixring_conformer, Conformer, CP5_TO_AS_OFFSET},
+    ringperception::{rings_of_size, NamedRings},
+    trajectory::{map_frames, FramesArray},
 };
 
 use crate::geometry::fundamental_ops::{normalise_vector, cross_product, dot_product};
-use crate::formalism::inversion;
+use crate::formalism::inversion::{self, RingGeometry};
+use crate::formalism::interconversion;
 
 
 // Enum to control the which type of n-membered ring system is being produced and 
@@ -22,6 +28,74 @@ enum MemberedRing {
     Six(CP6)
 }
 
+/// The CP4 struct holds the single puckering coordinate of a four-membered ring
+/// `amplitude` is the signed q_2 (= q_{N/2}) : positive when atoms 1 and 3 lie above the mean plane
+#[pyclass(get_all)]
+pub struct CP4 {
+    amplitude: f64,
+}
+
+#[pymethods]
+#[allow(clippy::wrong_self_convention)]
+impl CP4 {
+
+    #[new]
+    fn new(amplitude: f64) -> PyResult<CP4> {
+        if amplitude.abs() > 1. {
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) should be within the range of -1 -> 1", amplitude)))
+        }
+
+        Ok(CP4 { amplitude })
+    }
+
+    // Find indices of atomnames and pass them to self.from_indices()
+    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> PyResult<f64> {
+
+        if query_names.len() != 4 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 4, has been queried. Expected 4 elements.", query_names.len())))
+        };
+
+        // Make empty vec :
+        let mut indices: Vec<usize> = Vec::with_capacity(4);
+
+        // Search for the indices of the atom names
+        for name in query_names.iter() {
+            match pdb.atomnames.at_position(name) {
+                Ok(a) => indices.push(a),
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
+            }
+        }
+
+        self.from_indices(pdb.coordinates.clone(), indices)
+    }
+
+    // Calculate Cremer-Pople formalism by prompted indices
+    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> PyResult<f64> {
+
+        if indices.len() != 4 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 4, has been queried. Expected 4 elements.", indices.len())))
+        };
+
+        let mut molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
+
+        // A four-membered ring only has the q_{N/2} term
+        Ok(cremer_pople_n(&mut molarray).q_half.unwrap_or_default())
+    }
+
+    // Reconstruct the ring, with per-bond lengths or the defaults of its elements. Otherwise C-C bonds of 1.54 Å
+    // The endocyclic angles follow from the amplitude
+    #[pyo3(signature = (bondlengths=None, elements=None))]
+    fn invert(&self, bondlengths: Option<Vec<f64>>, elements: Option<Vec<String>>) -> PyResult<[[f64;3]; 4]> {
+        let geometry = RingGeometry::new(4, bondlengths, None, elements)?;
+        Ok(inversion::fourring::invert_fourring(self.amplitude, &geometry))
+    }
+
+}
+
+
 /// The CP tuple-struct holds the (amplitude, phase_angle) parameters
 #[pyclass(get_all)]
 pub struct CP5 {
@@ -30,37 +104,37 @@ pub struct CP5 {
 }
 
 #[pymethods]
+#[allow(clippy::wrong_self_convention)] // from_*() is called on an instance in Python, as CP5().from_indices()
 impl CP5 {
 
     #[new]
-    fn new(amplitude: f64, phase_angle: f64) -> CP5 {
+    fn new(amplitude: f64, phase_angle: f64) -> PyResult<CP5> {
         if amplitude > 1. {
-            panic!("amplitude value is larger than 1.")
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) is larger than 1.", amplitude)))
         }
 
         if !(0.0..=360.0).contains(&phase_angle) {
-            panic!("phase_angle value should be within the range of 0 -> 360")
+            return Err(OutOfRangeError::new_err(format!("phase_angle value ({}) should be within the range of 0 -> 360", phase_angle)))
         }
 
-        CP5 { amplitude, phase_angle }
+        Ok(CP5 { amplitude, phase_angle })
     }
 
     // Find indices of atomnames and pass them to self.cp_from_indices()
-    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> (f64, f64) {
+    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> PyResult<(f64, f64)> {
 
         // Make empty vec :
         let mut indices: Vec<usize> = Vec::with_capacity(6);
 
-        let _ = match query_names.len() {
-            5 => 5,
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        if query_names.len() != 5 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 5, has been queried. Expected 5 elements.", query_names.len())))
         };
 
         // Search for the indices of the atom names
         for name in query_names.iter() {
             match pdb.atomnames.at_position(name) {
                 Ok(a) => indices.push(a),
-                Err(()) => panic!("Could not find \"{}\" atomname in the queried pdb.", name)
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
             }
         }
 
@@ -71,18 +145,71 @@ impl CP5 {
     }
 
     // Calculate Cremer-Pople formalism by prompted indices
-    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> (f64, f64) {
+    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> PyResult<(f64, f64)> {
         
-        let mut molarray: Vec<[f64; 3]>  = indices.iter().map(|i| coordinates[*i]).collect();
+        let mut molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
 
-        match cremer_pople(&mut molarray) {
-           MemberedRing::Five(cp) => (cp.amplitude, cp.phase_angle),
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        match cremer_pople(&mut molarray)? {
+           MemberedRing::Five(cp) => Ok((cp.amplitude, cp.phase_angle)),
+           _ => Err(RingSizeError::new_err(format!("An amount of {}, not equal to 5, has been queried. Expected 5 elements.", indices.len())))
         }
     }
+
+    // Calculate Cremer-Pople formalism on every frame of an (n_frames, n_atoms, 3) array
+    fn from_trajectory<'py>(&self, py: Python<'py>, coordinates: PyReadonlyArray3<'py, f64>, indices: Vec<usize>) 
+        -> PyResult<(FramesArray<'py>, FramesArray<'py>)> {
+
+        let frames = map_frames(py, &coordinates, &indices, 5, |mut molarray| {
+            match cremer_pople(&mut molarray) {
+                Ok(MemberedRing::Five(cp)) => (cp.amplitude, cp.phase_angle),
+                _ => unreachable!("map_frames() only passes five atoms per frame")
+            }
+        })?;
+
+        let (amplitude, phase_angle): (Vec<f64>, Vec<f64>) = frames.into_iter().unzip();
+
+        Ok((amplitude.into_pyarray_bound(py), phase_angle.into_pyarray_bound(py)))
+    }
+
+    // Find every five-membered ring in the pdb and calculate its Cremer-Pople formalism
+    fn from_rings(&self, pdb: &Pdb) -> PyResult<NamedRings<(f64, f64)>> {
+
+        let mut puckers = vec![];
+        for (names, mut molarray) in rings_of_size(pdb, 5)? {
+            if let MemberedRing::Five(cp) = cremer_pople(&mut molarray)? {
+                puckers.push((names, (cp.amplitude, cp.phase_angle)))
+            }
+        }
+
+        Ok(puckers)
+    }
     
-    fn invert(&self) -> [[f64;3]; 5] {
-        inversion::fivering::invert_fivering(self.amplitude, self.phase_angle)
+    // Reconstruct the ring, with per-bond lengths and per-atom endocyclic angles (degrees),
+    // or the defaults of its elements. Otherwise C-C bonds of 1.54 Å and tetrahedral angles
+    #[pyo3(signature = (bondlengths=None, angles=None, elements=None))]
+    fn invert(&self, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>) -> PyResult<[[f64;3]; 5]> {
+        let geometry = RingGeometry::new(5, bondlengths, angles, elements)?;
+        Ok(inversion::fivering::invert_fivering(self.amplitude, self.phase_angle, &geometry))
+    }
+
+    // Convert to the Altona-Sundaralingam (amplitude, phase_angle), exactly by measuring the ring inverted with
+    // the geometry of self.invert(), or approximately by P = phi2 + 90 and nu_max = 2 sqrt(2) q2 / (mean bondlength)
+    #[pyo3(signature = (bondlengths=None, angles=None, elements=None, exact=true))]
+    fn to_as(&self, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>, exact: bool) -> PyResult<(f64, f64)> {
+        let geometry = RingGeometry::new(5, bondlengths, angles, elements)?;
+        Ok(interconversion::cp5_to_as(self.amplitude, self.phase_angle, &geometry, exact))
+    }
+
+    // Assign the nearest IUPAC canonical conformer. The wheel is expressed in the Altona-Sundaralingam
+    // phase angle, so the canonical phase angle is converted back to the Cremer-Pople one
+    #[pyo3(signature = (atomnames=None))]
+    fn conformer(&self, atomnames: Option<Vec<String>>) -> PyResult<Conformer> {
+        let mut conformer = fivering_conformer(self.phase_angle + CP5_TO_AS_OFFSET, atomnames)?;
+        conformer.phase_angle = (conformer.phase_angle - CP5_TO_AS_OFFSET).rem_euclid(360.);
+        Ok(conformer)
     }
 
 }
@@ -96,49 +223,79 @@ pub struct CP6 {
 }
 
 #[pymethods]
+#[allow(clippy::wrong_self_convention)]
 impl CP6 {
 
     #[new]
-    fn new(amplitude: f64, phase_angle: f64, theta: f64) -> CP6 {
+    fn new(amplitude: f64, phase_angle: f64, theta: f64) -> PyResult<CP6> {
         if amplitude > 1. {
-            panic!("amplitude value is larger than 1.")
+            return Err(OutOfRangeError::new_err(format!("amplitude value ({}) is larger than 1.", amplitude)))
         }
 
         if !(0.0..=360.0).contains(&phase_angle) {
-            panic!("phase_angle value should be within the range of 0 -> 360")
+            return Err(OutOfRangeError::new_err(format!("phase_angle value ({}) should be within the range of 0 -> 360", phase_angle)))
         }
 
         if !(0.0..=180.0).contains(&theta) {
-            panic!("theta value should be within the range of 0 -> 180")
+            return Err(OutOfRangeError::new_err(format!("theta value ({}) should be within the range of 0 -> 180", theta)))
         }
 
-        CP6 { amplitude, phase_angle, theta }
+        Ok(CP6 { amplitude, phase_angle, theta })
     }
 
     // Calculate Cremer-Pople formalism by prompted indices
-    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> (f64, f64, f64) {
+    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> PyResult<(f64, f64, f64)> {
         
-        let mut molarray: Vec<[f64; 3]> = vec![];
+        let mut molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
+
+       match cremer_pople(&mut molarray)? {
+           MemberedRing::Six(cp) => Ok((cp.amplitude, cp.phase_angle, cp.theta)),
+           _ => Err(RingSizeError::new_err(format!("An amount of {}, not equal to 6, has been queried. Expected 6 elements.", indices.len())))
+       }
+    }
+
+    // Calculate Cremer-Pople formalism on every frame of an (n_frames, n_atoms, 3) array
+    fn from_trajectory<'py>(&self, py: Python<'py>, coordinates: PyReadonlyArray3<'py, f64>, indices: Vec<usize>) 
+        -> PyResult<(FramesArray<'py>, FramesArray<'py>, FramesArray<'py>)> {
+
+        let frames = map_frames(py, &coordinates, &indices, 6, |mut molarray| {
+            match cremer_pople(&mut molarray) {
+                Ok(MemberedRing::Six(cp)) => (cp.amplitude, cp.phase_angle, cp.theta),
+                _ => unreachable!("map_frames() only passes six atoms per frame")
+            }
+        })?;
+
+        let amplitude: Vec<f64> = frames.iter().map(|cp| cp.0).collect();
+        let phase_angle: Vec<f64> = frames.iter().map(|cp| cp.1).collect();
+        let theta: Vec<f64> = frames.iter().map(|cp| cp.2).collect();
+
+        Ok((amplitude.into_pyarray_bound(py), phase_angle.into_pyarray_bound(py), theta.into_pyarray_bound(py)))
+    }
+
+    // Find every six-membered ring in the pdb and calculate its Cremer-Pople formalism
+    fn from_rings(&self, pdb: &Pdb) -> PyResult<NamedRings<(f64, f64, f64)>> {
 
-        for idx in indices {
-            molarray.push(coordinates[idx])
+        let mut puckers = vec![];
+        for (names, mut molarray) in rings_of_size(pdb, 6)? {
+            if let MemberedRing::Six(cp) = cremer_pople(&mut molarray)? {
+                puckers.push((names, (cp.amplitude, cp.phase_angle, cp.theta)))
+            }
         }
 
-       match cremer_pople(&mut molarray) {
-           MemberedRing::Six(cp) => (cp.amplitude, cp.phase_angle, cp.theta),
-           _ => panic!("An amount, not equal to 6, has been queried. Expected 6 elements.")
-       }
+        Ok(puckers)
     }
     
     // Find indices of atomnames and pass them to self.cp_from_indices()
-    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> (f64, f64, f64) {
+    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> PyResult<(f64, f64, f64)> {
 
         // Make empty vec :
         let mut indices: Vec<usize> = Vec::with_capacity(6);
 
-        let _ = match query_names.len() {
-            6 => 6,
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        if query_names.len() != 6 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 6, has been queried. Expected 6 elements.", query_names.len())))
         };
 
 
@@ -146,15 +303,91 @@ impl CP6 {
         for name in query_names.iter() {
             match pdb.atomnames.at_position(name) {
                 Ok(a) => indices.push(a),
-                Err(()) => panic!("Could not find \"{}\" atomname in the queried pdb.", name)
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
             }
         }
 
         self.from_indices(pdb.coordinates.clone(), indices)
     }
 
-    fn invert(&self) -> [[f64;3]; 6] {
-        inversion::sixring::invert_sixring(self.amplitude, self.phase_angle, self.theta)
+    // Reconstruct the ring, with per-bond lengths and per-atom endocyclic angles (degrees),
+    // or the defaults of its elements. Otherwise C-C bonds of 1.54 Å and tetrahedral angles
+    #[pyo3(signature = (bondlengths=None, angles=None, elements=None))]
+    fn invert(&self, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>) -> PyResult<[[f64;3]; 6]> {
+        let geometry = RingGeometry::new(6, bondlengths, angles, elements)?;
+        Ok(inversion::sixring::invert_sixring(self.amplitude, self.phase_angle, self.theta, &geometry))
+    }
+
+    // Assign the nearest IUPAC canonical conformer on the Cremer-Pople sphere
+    #[pyo3(signature = (atomnames=None))]
+    fn conformer(&self, atomnames: Option<Vec<String>>) -> PyResult<Conformer> {
+        sixring_conformer(self.phase_angle, self.theta, atomnames)
+    }
+
+}
+
+
+/// The CPN struct holds the puckering parameters of any N-membered ring system (N >= 4)
+/// `amplitude` is the total puckering amplitude Q
+/// `amplitudes` and `phase_angles` hold the (q_m, phi_m) pairs, for m = 2 -> (N-1)/2
+/// `q_half` holds q_{N/2}, which only exists for even-membered rings
+#[pyclass(get_all)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct CPN {
+    ring_size: usize,
+    amplitude: f64,
+    amplitudes: Vec<f64>,
+    phase_angles: Vec<f64>,
+    q_half: Option<f64>,
+}
+
+#[pymethods]
+#[allow(clippy::wrong_self_convention)]
+impl CPN {
+
+    #[new]
+    fn new(ring_size: usize) -> PyResult<CPN> {
+        if ring_size < 4 {
+            return Err(RingSizeError::new_err(format!("ring_size should at least be 4. Got {}.", ring_size)))
+        }
+
+        Ok(CPN { ring_size, amplitude: 0., amplitudes: vec![], phase_angles: vec![], q_half: None })
+    }
+
+    // Find indices of atomnames and pass them to self.from_indices()
+    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> PyResult<CPN> {
+
+        if query_names.len() != self.ring_size {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to {}, has been queried. Expected {} elements.", query_names.len(), self.ring_size, self.ring_size)))
+        };
+
+        // Make empty vec :
+        let mut indices: Vec<usize> = Vec::with_capacity(self.ring_size);
+
+        // Search for the indices of the atom names
+        for name in query_names.iter() {
+            match pdb.atomnames.at_position(name) {
+                Ok(a) => indices.push(a),
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
+            }
+        }
+
+        self.from_indices(pdb.coordinates.clone(), indices)
+    }
+
+    // Calculate the generalised Cremer-Pople formalism by prompted indices
+    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> PyResult<CPN> {
+
+        if indices.len() != self.ring_size {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to {}, has been queried. Expected {} elements.", indices.len(), self.ring_size, self.ring_size)))
+        };
+
+        let mut molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
+
+        Ok(cremer_pople_n(&mut molarray))
     }
 
 }
@@ -162,21 +395,31 @@ impl CP6 {
 
 
 // The Cremer-Pople algorithm; the main function
-fn cremer_pople(molarray: &mut Vec<[f64; 3]>) -> MemberedRing {
+fn cremer_pople(molarray: &mut Vec<[f64; 3]>) -> PyResult<MemberedRing> {
     
     geometric_center_of_molecule(molarray);
-    let mol_axis = molecular_axis(&molarray);
-    let zj = local_elevation(&molarray, mol_axis);
+    let mol_axis = molecular_axis(molarray);
+    let zj = local_elevation(molarray, mol_axis);
     
     return_cp_coordinates(zj)
 
 }
 
+// The generalised Cremer-Pople algorithm, for any N-membered ring with N >= 4
+fn cremer_pople_n(molarray: &mut Vec<[f64; 3]>) -> CPN {
+
+    geometric_center_of_molecule(molarray);
+    let mol_axis = molecular_axis(molarray);
+    let zj = local_elevation(molarray, mol_axis);
+
+    return_cpn_coordinates(zj)
+}
+
 // Copied the coordinates over to make the array a mutable reference
 // works for all any-membered ring systems
 fn geometric_center_of_molecule(molarray : &mut Vec<[f64;3]>) { 
 
-    let (x, y, z) = calculate_average_per_dimension(&molarray);
+    let (x, y, z) = calculate_average_per_dimension(molarray);
 
     // move molecule array to geometric center
     for coord in molarray { // already a mutable reference.
@@ -189,7 +432,7 @@ fn geometric_center_of_molecule(molarray : &mut Vec<[f64;3]>) {
 }
 
 // works for all any-membered ring systems
-fn molecular_axis(molarray : &Vec<[f64;3]>) -> [f64;3] { 
+fn molecular_axis(molarray : &[[f64;3]]) -> [f64;3] { 
 
     let (cos_uv, sin_uv) = unit_vector(molarray.len());
 
@@ -206,11 +449,12 @@ fn molecular_axis(molarray : &Vec<[f64;3]>) -> [f64;3] {
     let (x0, y0, z0) = calculate_average_per_dimension(&rp);
     let (x1, y1, z1) = calculate_average_per_dimension(&rpp);
 
-    // return molecular axis
-    cross_product(
+    // return molecular axis; R' and R'' are only orthogonal for a regular polygon, so the
+    // cross product is normalised as well (Eq. 10)
+    normalise_vector(cross_product(
         normalise_vector([x0, y0, z0]),
         normalise_vector([x1, y1, z1])
-    )    
+    ))
 
 }
 
@@ -218,7 +462,7 @@ fn molecular_axis(molarray : &Vec<[f64;3]>) -> [f64;3] {
 // centered molecule's array and doing a dot(a, b) every 
 // coordinates and the molecular_axis
 // works for all any-membered ring systems
-fn local_elevation(molarray : &Vec<[f64;3]>, mol_axis: [f64;3]) -> Vec<f64> {
+fn local_elevation(molarray : &[[f64;3]], mol_axis: [f64;3]) -> Vec<f64> {
 
     // iterate over the array and get the local elevation for every coordinate
     molarray.iter()
@@ -227,7 +471,7 @@ fn local_elevation(molarray : &Vec<[f64;3]>, mol_axis: [f64;3]) -> Vec<f64> {
 }
 
 // Calculate the Cremer Pople Coordinates based on the local elevation
-fn return_cp_coordinates(zj : Vec<f64>) -> MemberedRing { 
+fn return_cp_coordinates(zj : Vec<f64>) -> PyResult<MemberedRing> { 
 
     // constant values for the calculations 
     let size = zj.len();
@@ -248,7 +492,7 @@ fn return_cp_coordinates(zj : Vec<f64>) -> MemberedRing {
     // Some mirroring and subtractions are needed to make everything come out right
 //    if sum1 <= 0.0 { phase_angle = PI - phase_angle }; 
 //    if sum1 < 0.0 { phase_angle = TWOPI - phase_angle }; 
-    if sum1 <= 0.0 { phase_angle = PI + phase_angle }; 
+    if sum1 <= 0.0 { phase_angle += PI }; 
     if sum1 > 0.0 { phase_angle -= PI }
 
     if phase_angle < 0.0 { phase_angle += TWOPI }; // radians range
@@ -256,7 +500,7 @@ fn return_cp_coordinates(zj : Vec<f64>) -> MemberedRing {
 
     match size {
         5 => {
-            MemberedRing::Five(CP5::new(amplitude, phase_angle))
+            Ok(MemberedRing::Five(CP5 { amplitude, phase_angle }))
         },
         6 => {
             let q3: f64 = zj.iter().zip([1., -1., 1., -1., 1., -1.])
@@ -266,15 +510,60 @@ fn return_cp_coordinates(zj : Vec<f64>) -> MemberedRing {
             // For some reason, it is necessary to mirror the value over PI
             let theta = (PI - (q3/amplitude).acos()) * PIS_IN_180; // acos -> to_degrees()
 
-            MemberedRing::Six(CP6::new(amplitude, phase_angle, theta))
+            Ok(MemberedRing::Six(CP6 { amplitude, phase_angle, theta }))
         },
-        _ => panic!("Ringsystem prompted is not FIVE-membered or SIX-membered.")
+        _ => Err(RingSizeError::new_err(format!("Ringsystem prompted is not FIVE-membered or SIX-membered. Got {} atoms.", size)))
+    }
+}
+
+
+// Calculate the generalised Cremer-Pople coordinates based on the local elevation
+// Cremer & Pople, J. Am. Chem. Soc. 1975, 97, 1354 (Eq. 12 - 14)
+fn return_cpn_coordinates(zj : Vec<f64>) -> CPN {
+
+    let size = zj.len();
+    let sqrt_cst = (2. / size as f64).sqrt();
+
+    // The mean plane normal from molecular_axis() points the opposite way of the one in the
+    // original paper. CP5 and CP6 correct for this by adding PI to the phase angle and mirroring
+    // theta; here we mirror the local elevations once, so every q_m and phi_m follows the paper
+    let zj: Vec<f64> = zj.iter().map(|z| -z).collect();
+
+    // By summing all zj^2 values and sqrting the result
+    let amplitude = zj.iter().map(|i| i * i).sum::<f64>().sqrt();
+
+    let mut amplitudes: Vec<f64> = Vec::with_capacity((size - 1) / 2);
+    let mut phase_angles: Vec<f64> = Vec::with_capacity((size - 1) / 2);
+
+    for m in 2..=((size - 1) / 2) {
+        // q_m * cos(phi_m) = sqrt_cst * sum1 (Eq. 12)
+        // q_m * sin(phi_m) = sqrt_cst * sum2 (Eq. 13)
+        let (sum1, sum2) = zj.iter().enumerate().fold((0., 0.), |(acc1, acc2), (j, z)| {
+            let angle = (TWOPI * (m * j) as f64) / size as f64;
+            (acc1 + (z * angle.cos()), acc2 - (z * angle.sin()))
+        });
+
+        amplitudes.push(sqrt_cst * (sum1 * sum1 + sum2 * sum2).sqrt());
+
+        let mut phase_angle = sum2.atan2(sum1);
+        if phase_angle < 0.0 { phase_angle += TWOPI }; // radians range
+        phase_angles.push(phase_angle * PIS_IN_180);
     }
+
+    // q_{N/2} = sqrt(1/N) * sum( z_j * (-1)^j ) (Eq. 14)
+    let q_half = match size % 2 {
+        0 => Some(zj.iter().enumerate()
+                      .map(|(j, z)| if j % 2 == 0 { *z } else { -z })
+                      .sum::<f64>() / (size as f64).sqrt()),
+        _ => None
+    };
+
+    CPN { ring_size: size, amplitude, amplitudes, phase_angles, q_half }
 }
 
 
 // Returns (cosined array, sined array)
-// works for up to six-membered ring systems
+// works for all any-membered ring systems
 fn unit_vector(size: usize) -> (Vec<f64>, Vec<f64>) {
 
         let cos_uv = (0..size).map(|x| ((2. * PI * x as f64) / size as f64 ).cos() ).collect();
@@ -283,8 +572,8 @@ fn unit_vector(size: usize) -> (Vec<f64>, Vec<f64>) {
         (cos_uv, sin_uv)
 }
 
-// works for up to six-membered ring systems
-fn calculate_average_per_dimension(molarray: &Vec<[f64;3]>) -> (f64, f64, f64) {
+// works for all any-membered ring systems
+fn calculate_average_per_dimension(molarray: &[[f64;3]]) -> (f64, f64, f64) {
 
     let size = molarray.len() as f64;
 
@@ -306,3 +595,169 @@ fn calculate_average_per_dimension(molarray: &Vec<[f64;3]>) -> (f64, f64, f64) {
 
 
 }
+
+
+
+#[cfg(test)]
+mod test_cremerpople {
+
+    use assert_float_eq::*;
+    use super::*;
+    use crate::geometry::molecule_ops::{bondangle, bondlength};
+    use crate::formalism::inversion::{fourring::invert_fourring, fivering::invert_fivering, sixring::invert_sixring, sevenring::invert_sevenring, RingGeometry};
+
+    #[test]
+    pub fn cpn_matches_cp5() {
+
+        let mut molarray = invert_fivering(0.35, 288., &RingGeometry::tetrahedral(5)).to_vec();
+        let cpn = cremer_pople_n(&mut molarray.clone());
+
+        let (amplitude, phase_angle) = match cremer_pople(&mut molarray).unwrap() {
+            MemberedRing::Five(cp) => (cp.amplitude, cp.phase_angle),
+            _ => unreachable!()
+        };
+
+        assert!(cpn.q_half.is_none());
+        assert_float_absolute_eq!(cpn.amplitudes[0], amplitude, 0.0001);
+        assert_float_absolute_eq!(cpn.phase_angles[0], phase_angle, 0.0001);
+    }
+
+    #[test]
+    pub fn cpn_matches_cp6() {
+
+        let mut molarray = invert_sixring(0.67, 120., 45., &RingGeometry::tetrahedral(6)).to_vec();
+        let cpn = cremer_pople_n(&mut molarray.clone());
+
+        let (amplitude, phase_angle, theta) = match cremer_pople(&mut molarray).unwrap() {
+            MemberedRing::Six(cp) => (cp.amplitude, cp.phase_angle, cp.theta),
+            _ => unreachable!()
+        };
+
+        // theta = atan2(q_2, q_3)
+        let q_half = cpn.q_half.unwrap();
+        assert_float_absolute_eq!(cpn.amplitude, amplitude, 0.0001);
+        assert_float_absolute_eq!(cpn.phase_angles[0], phase_angle, 0.0001);
+        assert_float_absolute_eq!(cpn.amplitudes[0].atan2(q_half) * PIS_IN_180, theta, 0.0001);
+    }
+
+    #[test]
+    pub fn inverted_ring_geometry() {
+
+        use crate::geometry::molecule_ops::{bondangle, bondlength};
+
+        // Every bond is 1.54 Å and the ring is centered on the origin. Of the endocyclic angles,
+        // the reconstruction places atoms 1 and 4 of a five-ring, and 1, 3 and 5 of a six-ring, by the tetrahedral angle
+        let rings = [
+            (invert_fivering(0.35, 288., &RingGeometry::tetrahedral(5)).to_vec(), vec![1, 4]),
+            (invert_sixring(0.67, 120., 45., &RingGeometry::tetrahedral(6)).to_vec(), vec![1, 3, 5]),
+        ];
+        for (ring, placed) in rings {
+            let size = ring.len();
+            for j in 0..size {
+                assert_float_absolute_eq!(bondlength(ring[j], ring[(j + 1) % size]), 1.54, 1e-9);
+            }
+            for j in placed {
+                assert_float_absolute_eq!(bondangle(ring[j - 1], ring[j], ring[(j + 1) % size]), 109.4712, 0.0001);
+            }
+            let (x, y, z) = calculate_average_per_dimension(&ring);
+            assert_float_absolute_eq!(x.abs() + y.abs() + z.abs(), 0., 1e-9);
+        }
+    }
+
+    #[test]
+    pub fn inverted_ring_of_elements() {
+
+        // A ring of carbons by its elements is the tetrahedral ring
+        let carbons = RingGeometry::new(6, None, None, Some(vec!["C".to_string(); 6])).unwrap();
+        assert_eq!(invert_sixring(0.67, 120., 45., &carbons), invert_sixring(0.67, 120., 45., &RingGeometry::tetrahedral(6)));
+
+        // Queried angles are enforced on whichever atoms they are, by rotating the ring onto the enforced positions
+        let angles = vec![Some(104.), None, Some(100.), None, None];
+        let mut fivering = invert_fivering(0.35, 288., &RingGeometry::new(5, None, Some(angles), None).unwrap()).to_vec();
+        assert_float_absolute_eq!(bondangle(fivering[4], fivering[0], fivering[1]), 104., 1e-9);
+        assert_float_absolute_eq!(bondangle(fivering[1], fivering[2], fivering[3]), 100., 1e-9);
+        match cremer_pople(&mut fivering).unwrap() {
+            MemberedRing::Five(cp) => {
+                assert_float_absolute_eq!(cp.amplitude, 0.35, 1e-9);
+                assert_float_absolute_eq!(cp.phase_angle, 288., 1e-6);
+            },
+            _ => unreachable!()
+        };
+
+        let angles = vec![Some(112.), None, Some(108.), None, Some(106.), None];
+        let mut sixring = invert_sixring(0.67, 120., 45., &RingGeometry::new(6, None, Some(angles), None).unwrap()).to_vec();
+        for (j, angle) in [(0, 112.), (2, 108.), (4, 106.)] {
+            assert_float_absolute_eq!(bondangle(sixring[(j + 5) % 6], sixring[j], sixring[j + 1]), angle, 1e-9);
+        }
+        match cremer_pople(&mut sixring).unwrap() {
+            MemberedRing::Six(cp) => {
+                assert_float_absolute_eq!(cp.amplitude, 0.67, 1e-9);
+                assert_float_absolute_eq!(cp.phase_angle, 120., 1e-6);
+                assert_float_absolute_eq!(cp.theta, 45., 1e-6);
+            },
+            _ => unreachable!()
+        };
+
+        // The angle of the ring oxygen, first by convention, is enforced when looked up by elements
+        let furanose = RingGeometry::new(5, None, None, Some(["O", "C", "C", "C", "C"].map(String::from).to_vec())).unwrap();
+        let ring = invert_fivering(0.35, 288., &furanose);
+        assert_float_absolute_eq!(bondangle(ring[4], ring[0], ring[1]), 111.5, 1e-9);
+
+        let pyranose = RingGeometry::new(6, None, None, Some(["O", "C", "C", "C", "C", "C"].map(String::from).to_vec())).unwrap();
+        let ring = invert_sixring(0.67, 120., 45., &pyranose);
+        assert_float_absolute_eq!(bondangle(ring[5], ring[0], ring[1]), 111.5, 1e-9);
+
+        // Adjacent angles of a five-ring, or more than three angles of a six-ring, cannot all be enforced
+        assert!(RingGeometry::new(5, None, Some(vec![Some(100.), Some(100.), None, None, None]), None).is_err());
+        assert!(RingGeometry::new(5, None, Some(vec![Some(100.); 5]), None).is_err());
+        assert!(RingGeometry::new(6, None, Some(vec![Some(100.), None, None, Some(100.), None, None]), None).is_err());
+    }
+
+    #[test]
+    pub fn cpn_planar_ring() {
+
+        // A planar regular heptagon has no puckering
+        let mut molarray: Vec<[f64;3]> = (0..7).map(|j| {
+            let angle = (TWOPI * j as f64) / 7.;
+            [1.8 * angle.cos(), 1.8 * angle.sin(), 0.]
+        }).collect();
+
+        let cpn = cremer_pople_n(&mut molarray);
+
+        assert_eq!(cpn.amplitudes.len(), 2);
+        assert!(cpn.q_half.is_none());
+        assert_float_absolute_eq!(cpn.amplitude, 0., 0.0001);
+    }
+
+    #[test]
+    pub fn cpn_inverts_fourring() {
+
+        // Puckered cyclobutane : the endocyclic angles follow from the amplitude
+        let fourring = invert_fourring(0.25, &RingGeometry::tetrahedral(4));
+        let cpn = cremer_pople_n(&mut fourring.to_vec());
+
+        assert!(cpn.amplitudes.is_empty());
+        assert_float_absolute_eq!(cpn.q_half.unwrap(), 0.25, 0.0001);
+        assert_float_absolute_eq!(bondlength(fourring[0], fourring[1]), 1.54, 0.0001);
+        assert_float_absolute_eq!(bondangle(fourring[3], fourring[0], fourring[1]), (0.25_f64.powi(2) / 1.54_f64.powi(2)).acos().to_degrees(), 0.0001);
+
+        // Oxetane, with shorter C-O bonds
+        let geometry = RingGeometry::new(4, None, None, Some(["O", "C", "C", "C"].map(String::from).to_vec())).unwrap();
+        let fourring = invert_fourring(-0.15, &geometry);
+        assert_float_absolute_eq!(cremer_pople_n(&mut fourring.to_vec()).q_half.unwrap(), -0.15, 0.0001);
+        assert_float_absolute_eq!(bondlength(fourring[3], fourring[0]), geometry.rij[3], 0.0001);
+    }
+
+    #[test]
+    pub fn cpn_inverts_sevenring() {
+
+        let mut molarray = invert_sevenring(0.35, 75., 0.5, 310., &RingGeometry::tetrahedral(7)).to_vec();
+        let cpn = cremer_pople_n(&mut molarray);
+
+        assert_float_absolute_eq!(cpn.amplitudes[0], 0.35, 0.0001);
+        assert_float_absolute_eq!(cpn.phase_angles[0], 75., 0.0001);
+        assert_float_absolute_eq!(cpn.amplitudes[1], 0.5, 0.0001);
+        assert_float_absolute_eq!(cpn.phase_angles[1], 310., 0.0001);
+    }
+
+}
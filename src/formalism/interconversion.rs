@@ -0,0 +1,133 @@
+//! CONVERSION BETWEEN THE ALTONA-SUNDARALINGAM AND THE CREMER-POPLE COORDINATES OF A FIVE-MEMBERED RING
+//!
+//! AS describes the ring by its endocyclic torsions (nu_max, P), CP by the elevations of its atoms (q2, phi2).
+//! Both go around the same pseudorotation wheel, but their relation depends on the geometry of the ring.
+//!
+//! Exactly : the ring is reconstructed with invert_fivering() and its AS coordinates are measured.
+//!     The other way around, the (q2, phi2) that reproduce the (nu_max, P) are solved for by Newton steps.
+//!
+//! Approximately : for small puckers of a ring with equal bonds r and equal angles,
+//!     the torsions are linear in the elevations, which gives
+//!         P = phi2 + 90
+//!         nu_max = 2 sqrt(2) q2 / r (radians)
+//!     With r the mean bondlength, i.e. 1.837 rad/Angstrom at r = 1.54. Against the exact conversion, over the whole wheel :
+//!         tetrahedral ring      q2 <= 0.2  : P within 0.7 degrees, nu_max within 1 %
+//!                               q2 <= 0.45 : P within 2 degrees, nu_max within 3.5 %
+//!         furanose (O C C C C)  q2 <= 0.2  : P within 4.5 degrees, nu_max within 6.5 %
+//!                               q2 <= 0.45 : P within 4.5 degrees, nu_max within 7.5 %
+//!     The error of nu_max grows with the amplitude. Both grow with how far the angles of the ring are from those of a regular pentagon
+
+use std::f64::consts::SQRT_2;
+
+use crate::formalism::altonasund::altona_sundaralingam;
+use crate::formalism::inversion::{fivering::invert_fivering, RingGeometry};
+use crate::formalism::iupac::CP5_TO_AS_OFFSET;
+
+const NEWTON_STEPS: usize = 50;
+
+
+/// The AS (amplitude, phase_angle) of the CP5 (amplitude, phase_angle) of a ring of `geometry`
+pub fn cp5_to_as(amplitude: f64, phase_angle: f64, geometry: &RingGeometry, exact: bool) -> (f64, f64) {
+    match exact {
+        true => altona_sundaralingam(&invert_fivering(amplitude, phase_angle, geometry)),
+        false => (amplitude * torsion_per_elevation(geometry), (phase_angle + CP5_TO_AS_OFFSET).rem_euclid(360.)),
+    }
+}
+
+/// The CP5 (amplitude, phase_angle) of the AS (amplitude, phase_angle) of a ring of `geometry`
+/// None if the exact conversion does not converge
+pub fn as_to_cp5(amplitude: f64, phase_angle: f64, geometry: &RingGeometry, exact: bool) -> Option<(f64, f64)> {
+
+    let approximate = (amplitude / torsion_per_elevation(geometry), (phase_angle - CP5_TO_AS_OFFSET).rem_euclid(360.));
+    // A planar ring has no phase angle to solve for
+    if !exact || amplitude == 0. {
+        return Some(approximate)
+    };
+
+    // Solve on the wheel in cartesian coordinates, which have no singularity at the planar ring
+    let target = on_wheel(amplitude, phase_angle - CP5_TO_AS_OFFSET);
+    let residual = |p: [f64; 2]| {
+        let (nu_max, p_as) = cp5_to_as(p[0].hypot(p[1]), p[1].atan2(p[0]).to_degrees(), geometry, true);
+        let [x, y] = on_wheel(nu_max, p_as - CP5_TO_AS_OFFSET);
+        [x - target[0], y - target[1]]
+    };
+
+    let mut p = on_wheel(approximate.0, approximate.1);
+    for _ in 0..NEWTON_STEPS {
+        let r = residual(p);
+        if r[0].hypot(r[1]) < 1e-12 {
+            return Some((p[0].hypot(p[1]), p[1].atan2(p[0]).to_degrees().rem_euclid(360.)))
+        };
+
+        // Jacobian by central differences
+        let h = 1e-7;
+        let [dx, dy] = [[h, 0.], [0., h]].map(|step| {
+            let (forward, backward) = (residual([p[0] + step[0], p[1] + step[1]]), residual([p[0] - step[0], p[1] - step[1]]));
+            [(forward[0] - backward[0]) / (2. * h), (forward[1] - backward[1]) / (2. * h)]
+        });
+        let determinant = (dx[0] * dy[1]) - (dy[0] * dx[1]);
+        if !determinant.is_normal() {
+            return None
+        };
+
+        p = [
+            p[0] - (((dy[1] * r[0]) - (dy[0] * r[1])) / determinant),
+            p[1] - (((dx[0] * r[1]) - (dx[1] * r[0])) / determinant),
+        ];
+    }
+
+    None
+}
+
+
+// nu_max (radians) per q2 (Angstrom), from the mean bondlength of the ring
+fn torsion_per_elevation(geometry: &RingGeometry) -> f64 {
+    let mean_bondlength = geometry.rij.iter().sum::<f64>() / geometry.rij.len() as f64;
+    2. * SQRT_2 / mean_bondlength
+}
+
+fn on_wheel(amplitude: f64, phase_angle: f64) -> [f64; 2] {
+    let (sin, cos) = phase_angle.to_radians().sin_cos();
+    [amplitude * cos, amplitude * sin]
+}
+
+
+#[cfg(test)]
+mod test_interconversion {
+
+    use super::*;
+
+    #[test]
+    fn exact_round_trip() {
+        let furanose = RingGeometry::new(5, None, None, Some(["O", "C", "C", "C", "C"].map(String::from).to_vec())).unwrap();
+        for phase_angle in [0., 18., 95., 162., 250., 341.] {
+            let (nu_max, p) = cp5_to_as(0.38, phase_angle, &furanose, true);
+            let (q2, phi2) = as_to_cp5(nu_max, p, &furanose, true).unwrap();
+
+            assert!((q2 - 0.38).abs() < 1e-9);
+            assert!(((phi2 - phase_angle + 180.).rem_euclid(360.) - 180.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn approximate_near_exact() {
+        // Against the bounds in the documentation, over the whole wheel and up to the amplitude of the bound
+        let furanose = || RingGeometry::new(5, None, None, Some(["O", "C", "C", "C", "C"].map(String::from).to_vec())).unwrap();
+        let bounds = [
+            (RingGeometry::tetrahedral(5), 0.2, 0.7, 0.01),
+            (RingGeometry::tetrahedral(5), 0.45, 2., 0.035),
+            (furanose(), 0.2, 4.5, 0.065),
+            (furanose(), 0.45, 4.5, 0.075),
+        ];
+
+        for (geometry, max_amplitude, p_bound, nu_bound) in bounds {
+            for (amplitude, phase_angle) in (1..=9).flat_map(|i| (0..180).map(move |j| (max_amplitude * i as f64 / 9., j as f64 * 2.))) {
+                let (exact_nu, exact_p) = cp5_to_as(amplitude, phase_angle, &geometry, true);
+                let (approximate_nu, approximate_p) = cp5_to_as(amplitude, phase_angle, &geometry, false);
+
+                assert!(((approximate_nu / exact_nu) - 1.).abs() < nu_bound);
+                assert!(((approximate_p - exact_p + 180.).rem_euclid(360.) - 180.).abs() < p_bound);
+            }
+        }
+    }
+}
@@ -2,22 +2,24 @@ use crate::geometry::fundamental_ops::{Coordinate, RotMatrix, RotationMatrix, su
 use std::f64::consts::PI;
 
 use crate::conf_sampling::sixring::TWOPI;
-use crate::formalism::inversion::{RIJ, RIJSQ, COSBIJK};
+use crate::formalism::inversion::RingGeometry;
 
 // Return the array of coordinates
-pub fn invert_fivering(rho: f64, phi2: f64) -> [[f64; 3]; 5] {
+// The bondlengths and endocyclic angles of the ring are those of the `geometry`
+// The ring is reconstructed from atom `geometry.rotation` onwards, which shifts phi2 by 144 degrees per atom
+pub fn invert_fivering(rho: f64, phi2: f64, geometry: &RingGeometry) -> [[f64; 3]; 5] {
 
-    let zj = local_elevation(rho, phi2);
-    let projection = projection_and_partition(&zj);
+    let zj = local_elevation(rho, phi2 - (144. * geometry.rotation as f64));
+    let projection = projection_and_partition(&zj, &geometry.rotated());
     let fivering = reconstruct_coordinates(projection, zj);
 
-    [
+    geometry.unrotate([
         fivering.p1,
         fivering.p2,
         fivering.p3,
         fivering.p4,
         fivering.p5,
-    ]
+    ])
 }
 
 
@@ -26,7 +28,7 @@ fn local_elevation(rho : f64, phi2: f64) -> [f64;5] {
     
     let phi2 = phi2.to_radians();
     // Calculate local elevation
-    let constant1 = [0.,1.,2.,3.,4.].map(|j| ((2. * TWOPI * j) / 5.));
+    let constant1 = [0.,1.,2.,3.,4.].map(|j| (2. * TWOPI * j) / 5.);
 
     let two_fifth_sqrt: f64 = (2_f64/5_f64).sqrt() ;
 //    let one_over_sqrt_six: f64 = 6_f64.sqrt() ;
@@ -53,14 +55,16 @@ struct ProjectionPartition {
 
 }
 
-fn projection_and_partition(zj: &[f64;5]) -> ProjectionPartition {
+fn projection_and_partition(zj: &[f64;5], geometry: &RingGeometry) -> ProjectionPartition {
+
+    let rij = &geometry.rij;
 
     let mut rpij_arr: [f64;5] = [0.;5];
     let mut cospb_arr: [f64;5] =  [0.;5];
     let mut sinpb_arr: [f64;5] = [0.;5];
 
     for j in 0..5 {
-        rpij_arr[j] = ( RIJSQ - 
+        rpij_arr[j] = ( rij[j].powi(2) - 
                             ( zj[j] - zj[(j+1) % 5] ).powi(2)
                           ).sqrt();
     }
@@ -74,7 +78,7 @@ fn projection_and_partition(zj: &[f64;5]) -> ProjectionPartition {
         cospb_arr[j] = ( (zj[(j+2) % 5] - zj[j]).powi(2) // zk - zi 
                            - (zj[(j+1) % 5] - zj[j]).powi(2) // zj - zi
                            - (zj[(j+2) % 5] - zj[(j+1) % 5]).powi(2) // zk - zj
-                           + (2. * RIJ * RIJ * COSBIJK) // 2 * rij * rjk * cos Bijk
+                           + (2. * rij[j] * rij[(j+1) % 5] * geometry.cosbijk[(j+1) % 5]) // 2 * rij * rjk * cos Bijk
                            ) / (2. * rpij_arr[j] * rpij_arr[(j+1) % 5] ); // 2 * rpij * rpjk 
 
         sinpb_arr[j] = (1. - cospb_arr[j].powi(2) ).sqrt();
@@ -111,12 +115,8 @@ struct FiveRingAtoms {
 impl FiveRingAtoms {
     fn calculate_geometric_center(&self) -> Coordinate {
 
-    let mut p_g = [0.;3];
-
-    for i in 0..3 { // 0 -> 1 -> 2
-        p_g[i] = (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i]) / 6.
-    }
-    p_g
+    // 0 -> 1 -> 2
+    [0, 1, 2].map(|i| (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i]) / 5.)
     }
     
 }
@@ -157,9 +157,9 @@ fn reconstruct_coordinates(proj : ProjectionPartition, z_j : [f64;5]) -> FiveRin
                 [proj.oq ,
                  0.,
                  0.],
-            s34 : 
-                [proj.rpij[4] - (proj.rpij[3] * proj.cosbpijk[4]),
-                 proj.rpij[3] * proj.sinbpijk[4],
+            s34 : // the angle on atom 4 (B'345), which also spans oq
+                [proj.rpij[4] - (proj.rpij[3] * proj.cosbpijk[3]),
+                 proj.rpij[3] * proj.sinbpijk[3],
                  0.],
             s35 :
                 [proj.rpij[4],
@@ -179,7 +179,7 @@ fn reconstruct_coordinates(proj : ProjectionPartition, z_j : [f64;5]) -> FiveRin
                             0.,
                             0.]; //point O in triangle, origin 
     let p_p : Coordinate = [(proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2))/(2. * proj.oq),
-                            (proj.op.powi(2) - ( ( (proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2)).powi(2) ) / (4. * proj.op.powi(2)) ) ).sqrt(),
+                            (proj.op.powi(2) - ( ( (proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2)).powi(2) ) / (4. * proj.oq.powi(2)) ) ).sqrt(),
                             0.]; //point P in triangle, top
     let p_q : Coordinate = [proj.oq,
                             0.,
@@ -0,0 +1,46 @@
+use crate::formalism::inversion::RingGeometry;
+
+// Returns array of coordinates
+// The bondlengths of the ring are those of the `geometry`. A four-membered ring has a single
+// degree of freedom, so its endocyclic angles follow from the amplitude (cos(b) = q^2 / r^2 for equal bonds)
+// Amplitudes that are larger than a bondlength return NaN coordinates
+pub fn invert_fourring(amplitude: f64, geometry: &RingGeometry) -> [[f64; 3]; 4] {
+
+    let zj = local_elevation(amplitude);
+    let [a, b, c, d] = projection(&zj, geometry);
+
+    // The projected ring is the cyclic quadrilateral of its projected bondlengths (a square for equal bonds).
+    // Its diagonal 1-3 (1-based) follows from Ptolemy's theorem and the law of cosines
+    let diagonal = (((a * c) + (b * d)) * ((a * d) + (b * c)) / ((a * b) + (c * d))).sqrt();
+
+    // Atoms 2 and 4 lie on either side of the diagonal; atom 2 above it, so that
+    // the mean plane normal R' x R'' (Eq. 10) runs along +z
+    let x2 = (diagonal.powi(2) + a.powi(2) - b.powi(2)) / (2. * diagonal);
+    let x4 = (diagonal.powi(2) + d.powi(2) - c.powi(2)) / (2. * diagonal);
+    let ring = [
+        [0., 0.],
+        [x2, (a.powi(2) - x2.powi(2)).sqrt()],
+        [diagonal, 0.],
+        [x4, -(d.powi(2) - x4.powi(2)).sqrt()],
+    ];
+
+    // Move the geometric center to the origin
+    let center = [0, 1].map(|i| ring.iter().map(|p| p[i]).sum::<f64>() / 4.);
+
+    let mut fourring = [[0.; 3]; 4];
+    for (j, p) in ring.iter().enumerate() {
+        fourring[j] = [p[0] - center[0], p[1] - center[1], zj[j]];
+    }
+
+    fourring
+}
+
+// Z_j = sqrt(1/4) * q2 * cos(pi * j) (Eq. 14)
+fn local_elevation(amplitude: f64) -> [f64; 4] {
+    [1., -1., 1., -1.].map(|sign| sign * amplitude / 2.)
+}
+
+// The bondlengths of the ring, projected onto the mean plane
+fn projection(zj: &[f64; 4], geometry: &RingGeometry) -> [f64; 4] {
+    [0, 1, 2, 3].map(|j| (geometry.rij[j].powi(2) - (zj[(j + 1) % 4] - zj[j]).powi(2)).sqrt())
+}
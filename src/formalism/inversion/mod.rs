@@ -1,8 +1,158 @@
 pub mod sixring;
 pub mod fivering;
+pub mod fourring;
+pub mod sevenring;
+
+use pyo3::PyResult;
+
+use crate::errors::{OutOfRangeError, RingSizeError};
+use crate::formalism::ringperception::covalent_radius;
 
 
 // Constants required to calculate for the inversions
 pub const RIJ : f64 = 1.54;
 pub const RIJSQ : f64 = 1.54*1.54;
 pub const COSBIJK : f64 = -1./3. ;// cos(109.5)
+
+
+/// The endocyclic geometry a ring is reconstructed with.
+/// `rij[j]` is the bondlength between atom j and atom j+1 (the last one closes the ring)
+/// `cosbijk[j]` is the cosine of the endocyclic angle centered on atom j
+/// The ring closure leaves N-3 angles free, so that only the angles on positions 1 and 4 of a five-membered ring,
+/// and 1, 3 and 5 of a six-membered ring are enforced. The others follow from the puckering.
+/// `rotation` : atom j is reconstructed at position (j + rotation) % N, so that the angles that matter land on enforced positions
+pub struct RingGeometry {
+    pub rij: Vec<f64>,
+    pub cosbijk: Vec<f64>,
+    pub rotation: usize,
+}
+
+impl RingGeometry {
+
+    /// C-C bonds of 1.54 Å and tetrahedral angles on every atom
+    pub fn tetrahedral(ring_size: usize) -> Self {
+        RingGeometry { rij: vec![RIJ; ring_size], cosbijk: vec![COSBIJK; ring_size], rotation: 0 }
+    }
+
+    /// Build the geometry of a ring, from either the bondlengths and angles (degrees) or the elements.
+    /// Explicit `bondlengths` and `angles` take precedence over the defaults looked up by `elements`.
+    /// A ring of carbons looked up by `elements` equals RingGeometry::tetrahedral()
+    ///
+    /// Every queried angle is enforced, by rotating the ring onto the enforced positions. None leaves an angle free.
+    /// Of the angles looked up by `elements`, the rotation enforces as many of the non-tetrahedral (O, S, Se, P) angles as possible
+    pub fn new(ring_size: usize, bondlengths: Option<Vec<f64>>, angles: Option<Vec<Option<f64>>>, elements: Option<Vec<String>>) -> PyResult<Self> {
+
+        if let Some(v) = &bondlengths {
+            if v.len() != ring_size {
+                return Err(RingSizeError::new_err(format!("An amount of {} bondlengths, not equal to {}, has been queried. Expected {} elements.", v.len(), ring_size, ring_size)))
+            }
+        };
+        if let Some(v) = &angles {
+            if v.len() != ring_size {
+                return Err(RingSizeError::new_err(format!("An amount of {} angles, not equal to {}, has been queried. Expected {} elements.", v.len(), ring_size, ring_size)))
+            }
+        };
+
+        let mut geometry = match &elements {
+            Some(e) if e.len() != ring_size => {
+                return Err(RingSizeError::new_err(format!("An amount of {} elements, not equal to {}, has been queried. Expected {} elements.", e.len(), ring_size, ring_size)))
+            },
+            Some(e) => RingGeometry {
+                rij: (0..ring_size).map(|j| endocyclic_bondlength(&e[j], &e[(j + 1) % ring_size])).collect(),
+                cosbijk: e.iter().map(|atom| endocyclic_cosine(atom)).collect(),
+                rotation: 0,
+            },
+            None => RingGeometry::tetrahedral(ring_size),
+        };
+
+        if let Some(rij) = bondlengths { geometry.rij = rij };
+
+        // The angles that have to be enforced, and those that preferably are
+        let queried: Vec<bool> = match &angles {
+            Some(a) => a.iter().map(|a| a.is_some()).collect(),
+            None => vec![false; ring_size],
+        };
+        let preferred: Vec<bool> = geometry.cosbijk.iter().map(|cos| *cos != COSBIJK).collect();
+
+        if let Some(angles) = angles {
+            for (j, angle) in angles.iter().enumerate() {
+                if let Some(a) = angle { geometry.cosbijk[j] = a.to_radians().cos() }
+            }
+        };
+
+        geometry.rotation = rotation_onto(enforced_positions(ring_size), &queried, &preferred)?;
+
+        Ok(geometry)
+    }
+
+    /// The geometry in the order of the positions of the reconstruction
+    pub fn rotated(&self) -> RingGeometry {
+        let n = self.rij.len();
+        let mut rotated = RingGeometry { rij: vec![0.; n], cosbijk: vec![0.; n], rotation: 0 };
+        for j in 0..n {
+            rotated.rij[(j + self.rotation) % n] = self.rij[j];
+            rotated.cosbijk[(j + self.rotation) % n] = self.cosbijk[j];
+        }
+        rotated
+    }
+
+    /// The reconstructed coordinates, back in the order of the atoms of the ring
+    pub fn unrotate<const N: usize>(&self, ring: [[f64; 3]; N]) -> [[f64; 3]; N] {
+        std::array::from_fn(|j| ring[(j + self.rotation) % N])
+    }
+}
+
+// The positions on which the reconstruction enforces the endocyclic angle
+// The inversions of the other ring sizes take no angles (four-membered) or the tetrahedral ones only (seven-membered)
+fn enforced_positions(ring_size: usize) -> &'static [usize] {
+    match ring_size {
+        5 => &[1, 4],
+        6 => &[1, 3, 5],
+        _ => &[],
+    }
+}
+
+// The first rotation that places every queried angle on an enforced position, and most of the preferred ones
+fn rotation_onto(enforced: &[usize], queried: &[bool], preferred: &[bool]) -> PyResult<usize> {
+
+    let n = queried.len();
+    if enforced.is_empty() {
+        return Ok(0)
+    };
+
+    let placed = |rotation: usize, atoms: &[bool]| -> Vec<bool> {
+        (0..n).map(|j| !atoms[j] || enforced.contains(&((j + rotation) % n))).collect()
+    };
+
+    match (0..n).filter(|r| placed(*r, queried).iter().all(|p| *p))
+                .max_by_key(|r| (placed(*r, preferred).iter().filter(|p| **p).count(), n - r)) {
+        Some(rotation) => Ok(rotation),
+        None => {
+            let atoms: Vec<usize> = (0..n).filter(|j| queried[*j]).collect();
+            Err(OutOfRangeError::new_err(format!(
+                "The angles on atoms {:?} cannot all be enforced. A {}-membered ring only enforces {} angles, on atoms {:?} or a rotation of them. Pass None for the others.",
+                atoms, n, enforced.len(), enforced)))
+        }
+    }
+}
+
+// Typical bondlength between two atoms of a saturated ring
+// C-C is the 1.54 of RingGeometry::tetrahedral(), the others the sum of the covalent radii, e.g. C-O = 1.42, C-N = 1.47
+fn endocyclic_bondlength(atom: &str, next: &str) -> f64 {
+    match atom.eq_ignore_ascii_case("C") && next.eq_ignore_ascii_case("C") {
+        true => RIJ,
+        false => covalent_radius(atom) + covalent_radius(next),
+    }
+}
+
+// Cosine of the typical endocyclic angle on an atom of a saturated ring
+fn endocyclic_cosine(element: &str) -> f64 {
+    let angle: f64 = match element.to_ascii_uppercase().as_str() {
+        "O" => 111.5, // C-O-C of furanoses and pyranoses
+        "S" => 97.,
+        "SE" => 94.,
+        "P" => 100.,
+        _ => return COSBIJK, // sp3 C, N, Si, ...
+    };
+    angle.to_radians().cos()
+}
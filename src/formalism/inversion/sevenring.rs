@@ -0,0 +1,128 @@
+use std::f64::consts::PI;
+
+use crate::conf_sampling::sixring::TWOPI;
+use crate::formalism::inversion::RingGeometry;
+
+type Point = [f64; 2];
+
+// Returns array of coordinates
+// The bondlengths of the ring, and the endocyclic angles on atoms 2, 3, 5 and 6 (1-based), are those of the `geometry`
+// The angles on atoms 1, 4 and 7 follow from the closure of the ring
+// Conformers that cannot be closed with this geometry return NaN coordinates
+pub fn invert_sevenring(q2: f64, phi2: f64, q3: f64, phi3: f64, geometry: &RingGeometry) -> [[f64; 3]; 7] {
+
+    let zj = local_elevation(q2, phi2, q3, phi3);
+    let (rpij, bpijk) = projection(&zj, geometry);
+
+    // Both rigid chains 1-2-3-4 and 4-5-6-7 hang from the triangle 1-4-7
+    let chain_a = rigid_chain([rpij[0], rpij[1], rpij[2]], [bpijk[1], bpijk[2]]);
+    let chain_b = rigid_chain([rpij[3], rpij[4], rpij[5]], [bpijk[4], bpijk[5]]);
+
+    let d14 = distance(chain_a[0], chain_a[3]);
+    let d47 = distance(chain_b[0], chain_b[3]);
+    let d71 = rpij[6];
+
+    let p1: Point = [0., 0.];
+    let p4: Point = [d14, 0.];
+    let x7 = (d14.powi(2) + d71.powi(2) - d47.powi(2)) / (2. * d14);
+    let p7: Point = [x7, (d71.powi(2) - x7.powi(2)).sqrt()];
+
+    let a = place_chain(chain_a, p1, p4, p7);
+    let b = place_chain(chain_b, p4, p7, p1);
+    let ring: [Point; 7] = [a[0], a[1], a[2], a[3], b[1], b[2], b[3]];
+
+    // Move the geometric center to the origin
+    // The ring is laid out counterclockwise; mirror it, so the mean plane normal R' x R'' (Eq. 10) runs along +z
+    let center = [0, 1].map(|i| ring.iter().map(|p| p[i]).sum::<f64>() / 7.);
+
+    let mut sevenring = [[0.; 3]; 7];
+    for (j, p) in ring.iter().enumerate() {
+        sevenring[j] = [p[0] - center[0], center[1] - p[1], zj[j]];
+    }
+
+    sevenring
+}
+
+// Z_j = sqrt(2/7) * ( q2 cos(phi2 + 2*2pi*j/7) + q3 cos(phi3 + 3*2pi*j/7) )
+fn local_elevation(q2: f64, phi2: f64, q3: f64, phi3: f64) -> [f64; 7] {
+
+    let (phi2, phi3) = (phi2.to_radians(), phi3.to_radians());
+    let two_sevenths_sqrt: f64 = (2_f64 / 7_f64).sqrt();
+
+    [0., 1., 2., 3., 4., 5., 6.].map(|j| {
+        let term2 = q2 * (phi2 + (2. * TWOPI * j) / 7.).cos();
+        let term3 = q3 * (phi3 + (3. * TWOPI * j) / 7.).cos();
+        (term2 + term3) * two_sevenths_sqrt
+    })
+}
+
+// The bondlengths (rpij) and endocyclic angles (bpijk, radians) of the ring, projected onto the mean plane
+// bpijk[j] is the projected angle centered on atom j
+fn projection(zj: &[f64; 7], geometry: &RingGeometry) -> ([f64; 7], [f64; 7]) {
+
+    let rij = &geometry.rij;
+    let mut rpij = [0.; 7];
+    let mut bpijk = [0.; 7];
+
+    for j in 0..7 {
+        rpij[j] = (rij[j].powi(2) - (zj[(j + 1) % 7] - zj[j]).powi(2)).sqrt();
+    }
+
+    for j in 0..7 {
+        let (i, k) = ((j + 6) % 7, (j + 1) % 7);
+        let cosbp = ( (zj[k] - zj[i]).powi(2)
+                    - (zj[j] - zj[i]).powi(2)
+                    - (zj[k] - zj[j]).powi(2)
+                    + (2. * rij[i] * rij[j] * geometry.cosbijk[j])
+                    ) / (2. * rpij[i] * rpij[j]);
+        bpijk[j] = cosbp.acos();
+    }
+
+    (rpij, bpijk)
+}
+
+// Lay out a chain of three bonds, turning left by (pi - angle) on its two inner atoms
+fn rigid_chain(bonds: [f64; 3], angles: [f64; 2]) -> [Point; 4] {
+
+    let mut chain = [[0.; 2]; 4];
+    let mut heading: f64 = 0.;
+
+    for (n, bond) in bonds.iter().enumerate() {
+        if n > 0 {
+            heading += PI - angles[n - 1];
+        };
+        chain[n + 1] = [chain[n][0] + bond * heading.cos(), chain[n][1] + bond * heading.sin()];
+    }
+
+    chain
+}
+
+// Rotate the chain onto its endpoints, with its inner atoms on the side facing away from `opposite`
+fn place_chain(chain: [Point; 4], start: Point, end: Point, opposite: Point) -> [Point; 4] {
+
+    let placed = align(chain, start, end);
+    if side(start, end, placed[1]) * side(start, end, opposite) < 0. {
+        return placed
+    };
+
+    // The mirrored chain shares its endpoints
+    align(chain.map(|p| [p[0], -p[1]]), start, end)
+}
+
+// Rotate the chain, that starts in the origin, so that it runs from `start` to `end`
+fn align(chain: [Point; 4], start: Point, end: Point) -> [Point; 4] {
+
+    let rotation = (end[1] - start[1]).atan2(end[0] - start[0]) - chain[3][1].atan2(chain[3][0]);
+    let (sin, cos) = rotation.sin_cos();
+
+    chain.map(|p| [start[0] + p[0] * cos - p[1] * sin, start[1] + p[0] * sin + p[1] * cos])
+}
+
+// Sign of the side of the line a -> b the point p lies on
+fn side(a: Point, b: Point, p: Point) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
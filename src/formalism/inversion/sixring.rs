@@ -2,23 +2,27 @@ use crate::geometry::fundamental_ops::{LinAlg, Coordinate, RotMatrix, RotationMa
 use std::f64::consts::PI;
 
 use crate::conf_sampling::sixring::TWOPI;
-use crate::formalism::inversion::{RIJ, RIJSQ, COSBIJK};
+use crate::formalism::inversion::RingGeometry;
 
 // Returns array of coordinates
-pub fn invert_sixring(rho: f64, phi2: f64, theta: f64) -> [[f64; 3]; 6] {
-
-    let zj = local_elevation(rho, phi2, theta);
-    let projection = projection_and_partition(&zj);
+// The bondlengths and endocyclic angles of the ring are those of the `geometry`
+// The ring is reconstructed from atom `geometry.rotation` onwards, which shifts phi2 by 120 degrees per atom
+// and, by an odd amount of atoms, flips the sign of the q3 term (theta -> 180 - theta)
+pub fn invert_sixring(rho: f64, phi2: f64, theta: f64, geometry: &RingGeometry) -> [[f64; 3]; 6] {
+
+    let theta = if geometry.rotation % 2 == 1 { 180. - theta } else { theta };
+    let zj = local_elevation(rho, phi2 - (120. * geometry.rotation as f64), theta);
+    let projection = projection_and_partition(&zj, &geometry.rotated());
     let sixring = reconstruct_coordinates(projection, zj);
 
-    [
+    geometry.unrotate([
         sixring.p1,
         sixring.p2,
         sixring.p3,
         sixring.p4,
         sixring.p5,
         sixring.p6,
-    ]
+    ])
 }
 
 
@@ -28,7 +32,7 @@ fn local_elevation(rho : f64, phi2: f64, theta: f64) -> [f64;6] {
     let phi2 = phi2.to_radians();
     let theta = theta.to_radians();
     // Calculate local elevation
-    let constant1 = [0.,1.,2.,3.,4.,5.].map(|j| ((TWOPI * j) / 3.));
+    let constant1 = [0.,1.,2.,3.,4.,5.].map(|j| (TWOPI * j) / 3.);
     let constant2 = [0,1,2,3,4,5].map(|j| (-1_f64).powi(j));
 
     let one_over_sqrt_three: f64 = 3_f64.sqrt() ;
@@ -57,14 +61,16 @@ struct ProjectionPartition {
 
 }
 
-fn projection_and_partition(zj: &[f64;6]) -> ProjectionPartition {
+fn projection_and_partition(zj: &[f64;6], geometry: &RingGeometry) -> ProjectionPartition {
+
+    let rij = &geometry.rij;
 
     let mut rpij_arr: [f64;6] = [0.;6];
     let mut cospb_arr: [f64;6] =  [0.;6];
     let mut sinpb_arr: [f64;6] = [0.;6];
 
     for j in 0..6 {
-        rpij_arr[j] = ( RIJSQ - 
+        rpij_arr[j] = ( rij[j].powi(2) - 
                             ( zj[j] - zj[(j+1) % 6] ).powi(2)
                           ).sqrt();
     }
@@ -78,7 +84,7 @@ fn projection_and_partition(zj: &[f64;6]) -> ProjectionPartition {
         cospb_arr[j] = ( (zj[(j+2) % 6] - zj[j]).powi(2) // zk - zi 
                            - (zj[(j+1) % 6] - zj[j]).powi(2) // zj - zi
                            - (zj[(j+2) % 6] - zj[(j+1) % 6]).powi(2) // zk - zj
-                           + (2. * RIJ * RIJ * COSBIJK) // 2 * rij * rjk * cos Bijk
+                           + (2. * rij[j] * rij[(j+1) % 6] * geometry.cosbijk[(j+1) % 6]) // 2 * rij * rjk * cos Bijk
                            ) / (2. * rpij_arr[j] * rpij_arr[(j+1) % 6] ); // 2 * rpij * rpjk 
 
         sinpb_arr[j] = (1. - &cospb_arr[j].powi(2) ).sqrt();
@@ -113,12 +119,8 @@ struct SixRingAtoms {
 impl SixRingAtoms {
     fn calculate_geometric_center(&self) -> Coordinate {
 
-    let mut p_g = [0.;3];
-
-    for i in 0..3 { // 0 -> 1 -> 2
-        p_g[i] = (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i] + self.p6[i]) / 6.
-    }
-    p_g
+    // 0 -> 1 -> 2
+    [0, 1, 2].map(|i| (self.p1[i] + self.p2[i] + self.p3[i] + self.p4[i] + self.p5[i] + self.p6[i]) / 6.)
     }
     
 }
@@ -196,7 +198,7 @@ fn reconstruct_coordinates(proj : ProjectionPartition, z_j : [f64;6]) -> SixRing
                             0.,
                             0.]; //pO
     let p_p : Coordinate = [(proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2))/(2. * proj.oq),
-                            (proj.op.powi(2) - ( ( (proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2)).powi(2) ) / (4. * proj.op.powi(2)) ) ).sqrt(),
+                            (proj.op.powi(2) - ( ( (proj.op.powi(2) + proj.oq.powi(2) - proj.qp.powi(2)).powi(2) ) / (4. * proj.oq.powi(2)) ) ).sqrt(),
                             0.]; //pP
     let p_q : Coordinate = [proj.oq,
                             0.,
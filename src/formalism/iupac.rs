@@ -0,0 +1,254 @@
+use pyo3::{pyclass, PyResult};
+
+use crate::errors::RingSizeError;
+
+// The pseudorotation wheel (five-membered rings) is expressed in the Altona-Sundaralingam phase angle.
+// Cremer-Pople phase angles of five-membered rings lie 90 degrees behind : P = phi + 90
+pub const CP5_TO_AS_OFFSET: f64 = 90.;
+
+// Elevation of the half-chairs and envelopes on the Cremer-Pople sphere
+const THETA_HALFCHAIR: f64 = 50.8;
+const THETA_ENVELOPE: f64 = 54.7;
+
+/// The Conformer struct holds the IUPAC canonical conformer that lies nearest to a puckering coordinate
+/// `label` is written as <endo atoms><shape><exo atoms>, e.g. 3T2, 2E, E3, 4C1, 1S3, B2,5, 2,5B, OH5
+/// `description` spells out the endo and exo atoms, e.g. "C3'-endo, C2'-exo"
+/// `distance` is the angle (degrees) between the coordinate and its canonical conformer
+///     along the pseudorotation wheel (five-membered rings) or over the Cremer-Pople sphere (six-membered rings)
+/// `sector` is the North/East/South/West quadrant of the pseudorotation wheel. Only for five-membered rings
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct Conformer {
+    pub label: String,
+    pub description: String,
+    pub phase_angle: f64,
+    pub theta: Option<f64>,
+    pub distance: f64,
+    pub sector: Option<String>,
+}
+
+// A canonical conformer : its shape, the ring positions above (endo) and below (exo) the mean plane, and its location
+struct Canonical {
+    shape: char,
+    endo: &'static [usize],
+    exo: &'static [usize],
+    phase_angle: f64,
+    theta: f64,
+}
+
+const fn canonical(shape: char, endo: &'static [usize], exo: &'static [usize], phase_angle: f64, theta: f64) -> Canonical {
+    Canonical { shape, endo, exo, phase_angle, theta }
+}
+
+// Positions in the ring : 0 = O4', 1 = C1', 2 = C2', 3 = C3', 4 = C4'
+// Altona-Sundaralingam phase angle, in steps of 18 degrees. Theta is unused
+const FIVERING_CANONICALS: [Canonical; 20] = [
+    canonical('T', &[3], &[2],   0., 0.),
+    canonical('E', &[3], &[],   18., 0.),
+    canonical('T', &[3], &[4],  36., 0.),
+    canonical('E', &[],  &[4],  54., 0.),
+    canonical('T', &[0], &[4],  72., 0.),
+    canonical('E', &[0], &[],   90., 0.),
+    canonical('T', &[0], &[1], 108., 0.),
+    canonical('E', &[],  &[1], 126., 0.),
+    canonical('T', &[2], &[1], 144., 0.),
+    canonical('E', &[2], &[],  162., 0.),
+    canonical('T', &[2], &[3], 180., 0.),
+    canonical('E', &[],  &[3], 198., 0.),
+    canonical('T', &[4], &[3], 216., 0.),
+    canonical('E', &[4], &[],  234., 0.),
+    canonical('T', &[4], &[0], 252., 0.),
+    canonical('E', &[],  &[0], 270., 0.),
+    canonical('T', &[1], &[0], 288., 0.),
+    canonical('E', &[1], &[],  306., 0.),
+    canonical('T', &[1], &[2], 324., 0.),
+    canonical('E', &[],  &[2], 342., 0.),
+];
+
+// Positions in the ring : 0 = O5, 1 = C1, 2 = C2, 3 = C3, 4 = C4, 5 = C5
+// Cremer-Pople (phase_angle, theta), with the 4C1 chair on the northern pole
+const SIXRING_CANONICALS: [Canonical; 38] = [
+    // Chairs
+    canonical('C', &[4], &[1], 0.,   0.),
+    canonical('C', &[1], &[4], 0., 180.),
+    // Northern envelopes and half-chairs
+    canonical('E', &[0], &[],    0., THETA_ENVELOPE),
+    canonical('H', &[0], &[1],  30., THETA_HALFCHAIR),
+    canonical('E', &[],  &[1],  60., THETA_ENVELOPE),
+    canonical('H', &[2], &[1],  90., THETA_HALFCHAIR),
+    canonical('E', &[2], &[],  120., THETA_ENVELOPE),
+    canonical('H', &[2], &[3], 150., THETA_HALFCHAIR),
+    canonical('E', &[],  &[3], 180., THETA_ENVELOPE),
+    canonical('H', &[4], &[3], 210., THETA_HALFCHAIR),
+    canonical('E', &[4], &[],  240., THETA_ENVELOPE),
+    canonical('H', &[4], &[5], 270., THETA_HALFCHAIR),
+    canonical('E', &[],  &[5], 300., THETA_ENVELOPE),
+    canonical('H', &[0], &[5], 330., THETA_HALFCHAIR),
+    // Boats and skew-boats on the equator
+    canonical('B', &[3, 0], &[],      0., 90.),
+    canonical('S', &[3],    &[1],    30., 90.),
+    canonical('B', &[],     &[1, 4], 60., 90.),
+    canonical('S', &[5],    &[1],    90., 90.),
+    canonical('B', &[2, 5], &[],    120., 90.),
+    canonical('S', &[2],    &[0],   150., 90.),
+    canonical('B', &[],     &[3, 0], 180., 90.),
+    canonical('S', &[1],    &[3],   210., 90.),
+    canonical('B', &[1, 4], &[],    240., 90.),
+    canonical('S', &[1],    &[5],   270., 90.),
+    canonical('B', &[],     &[2, 5], 300., 90.),
+    canonical('S', &[0],    &[2],   330., 90.),
+    // Southern envelopes and half-chairs
+    canonical('E', &[],  &[0],    0., 180. - THETA_ENVELOPE),
+    canonical('H', &[1], &[0],   30., 180. - THETA_HALFCHAIR),
+    canonical('E', &[1], &[],    60., 180. - THETA_ENVELOPE),
+    canonical('H', &[1], &[2],   90., 180. - THETA_HALFCHAIR),
+    canonical('E', &[],  &[2],  120., 180. - THETA_ENVELOPE),
+    canonical('H', &[3], &[2],  150., 180. - THETA_HALFCHAIR),
+    canonical('E', &[3], &[],   180., 180. - THETA_ENVELOPE),
+    canonical('H', &[3], &[4],  210., 180. - THETA_HALFCHAIR),
+    canonical('E', &[],  &[4],  240., 180. - THETA_ENVELOPE),
+    canonical('H', &[5], &[4],  270., 180. - THETA_HALFCHAIR),
+    canonical('E', &[5], &[],   300., 180. - THETA_ENVELOPE),
+    canonical('H', &[5], &[0],  330., 180. - THETA_HALFCHAIR),
+];
+
+const FURANOSE_ATOMNAMES: [&str; 5] = ["O4'", "C1'", "C2'", "C3'", "C4'"];
+const PYRANOSE_ATOMNAMES: [&str; 6] = ["O5", "C1", "C2", "C3", "C4", "C5"];
+
+
+
+/// Assign the nearest canonical conformer of a five-membered ring, from its Altona-Sundaralingam phase angle
+/// The `atomnames` follow the order in which the ring was queried. Defaults to O4', C1', C2', C3', C4'
+pub fn fivering_conformer(phase_angle: f64, atomnames: Option<Vec<String>>) -> PyResult<Conformer> {
+
+    let atomnames = ring_atomnames(atomnames, &FURANOSE_ATOMNAMES)?;
+    let phase_angle = phase_angle.rem_euclid(360.);
+
+    let (canonical, distance) = FIVERING_CANONICALS.iter()
+        .map(|c| (c, angular_difference(phase_angle, c.phase_angle)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    Ok(Conformer {
+        label: label(canonical, &atomnames),
+        description: description(canonical, &atomnames),
+        phase_angle: canonical.phase_angle,
+        theta: None,
+        distance,
+        sector: Some(pseudorotation_sector(phase_angle)),
+    })
+}
+
+/// Assign the nearest canonical conformer of a six-membered ring, from its Cremer-Pople (phase_angle, theta)
+/// The `atomnames` follow the order in which the ring was queried. Defaults to O5, C1, C2, C3, C4, C5
+pub fn sixring_conformer(phase_angle: f64, theta: f64, atomnames: Option<Vec<String>>) -> PyResult<Conformer> {
+
+    let atomnames = ring_atomnames(atomnames, &PYRANOSE_ATOMNAMES)?;
+
+    let (canonical, distance) = SIXRING_CANONICALS.iter()
+        .map(|c| (c, great_circle_distance(phase_angle, theta, c.phase_angle, c.theta)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    Ok(Conformer {
+        label: label(canonical, &atomnames),
+        description: description(canonical, &atomnames),
+        phase_angle: canonical.phase_angle,
+        theta: Some(canonical.theta),
+        distance,
+        sector: None,
+    })
+}
+
+// North is centered around the C3'-endo region (P = 0), South around the C2'-endo region (P = 180)
+fn pseudorotation_sector(phase_angle: f64) -> String {
+    match phase_angle {
+        p if !(45. ..315.).contains(&p) => "North",
+        p if p < 135. => "East",
+        p if p < 225. => "South",
+        _ => "West",
+    }.to_string()
+}
+
+fn ring_atomnames(atomnames: Option<Vec<String>>, default: &[&str]) -> PyResult<Vec<String>> {
+
+    match atomnames {
+        None => Ok(default.iter().map(|s| s.to_string()).collect()),
+        Some(names) if names.len() == default.len() => Ok(names),
+        Some(names) => Err(RingSizeError::new_err(format!("An amount of {}, not equal to {}, has been queried. Expected {} elements.", names.len(), default.len(), default.len())))
+    }
+}
+
+// Shorthand of an atom in the IUPAC label : its locant for carbons (C2' -> 2), else its element (O4' -> O)
+fn locant(atomname: &str) -> String {
+
+    let digits: String = atomname.chars().skip(1).take_while(|c| c.is_ascii_digit()).collect();
+    match atomname.chars().next() {
+        Some('C') if !digits.is_empty() => digits,
+        Some(c) => c.to_string(),
+        None => String::new(),
+    }
+}
+
+fn label(canonical: &Canonical, atomnames: &[String]) -> String {
+
+    let join = |positions: &[usize]| -> String {
+        positions.iter().map(|p| locant(&atomnames[*p])).collect::<Vec<String>>().join(",")
+    };
+
+    format!("{}{}{}", join(canonical.endo), canonical.shape, join(canonical.exo))
+}
+
+fn description(canonical: &Canonical, atomnames: &[String]) -> String {
+
+    canonical.endo.iter().map(|p| format!("{}-endo", atomnames[*p]))
+        .chain(canonical.exo.iter().map(|p| format!("{}-exo", atomnames[*p])))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Smallest difference between two angles (degrees), in the range of 0 -> 180
+fn angular_difference(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(360.);
+    d.min(360. - d)
+}
+
+// Angle (degrees) between two points on the Cremer-Pople sphere
+fn great_circle_distance(phi1: f64, theta1: f64, phi2: f64, theta2: f64) -> f64 {
+
+    let (phi1, theta1, phi2, theta2) = (phi1.to_radians(), theta1.to_radians(), phi2.to_radians(), theta2.to_radians());
+    let cosine = theta1.cos() * theta2.cos() + theta1.sin() * theta2.sin() * (phi1 - phi2).cos();
+
+    cosine.clamp(-1., 1.).acos().to_degrees()
+}
+
+
+#[cfg(test)]
+mod test_iupac {
+
+    use super::*;
+
+    #[test]
+    pub fn furanose_conformers() {
+        let c3endo = fivering_conformer(18., None).unwrap();
+        assert_eq!(c3endo.label, "3E");
+        assert_eq!(c3endo.description, "C3'-endo");
+        assert_eq!(c3endo.sector.as_deref(), Some("North"));
+
+        let c2endo = fivering_conformer(175., None).unwrap();
+        assert_eq!(c2endo.label, "2T3");
+        assert_eq!(c2endo.distance, 5.);
+        assert_eq!(c2endo.sector.as_deref(), Some("South"));
+
+        assert_eq!(fivering_conformer(355., None).unwrap().label, "3T2");
+    }
+
+    #[test]
+    pub fn pyranose_conformers() {
+        assert_eq!(sixring_conformer(0., 5., None).unwrap().label, "4C1");
+        assert_eq!(sixring_conformer(200., 175., None).unwrap().label, "1C4");
+        assert_eq!(sixring_conformer(302., 88., None).unwrap().label, "B2,5");
+        assert_eq!(sixring_conformer(208., 91., None).unwrap().label, "1S3");
+        assert_eq!(sixring_conformer(330., 50., None).unwrap().label, "OH5");
+    }
+}
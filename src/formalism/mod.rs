@@ -2,10 +2,17 @@ pub mod cremerpople;
 pub mod altonasund;
 pub mod strausspickett;
 pub mod moleculefile;
+pub mod iupac;          // assign the IUPAC canonical conformer (E, T, C, B, S, H) to a puckering coordinate
+pub mod ringperception; // infer the bonds and find the rings of a molecule
+pub mod trajectory; // apply the formalisms over every frame of a trajectory, in parallel
+pub mod residues;   // group the atoms of a Pdb by residue
+pub mod nucleicacid; // backbone torsions, glycosidic torsion and sugar pucker of a nucleic acid strand
+pub mod protein;    // backbone and side-chain dihedrals of a protein
+pub mod interconversion; // convert between the AS and CP5 coordinates of a five-membered ring
 
 
-mod search_atomname;// match a pattern in a Vec<String>. If not found, Err(()) => panic!()
-                    // Used in self.from_atomnames() methods
+pub mod search_atomname;// match a pattern in a Vec<String>. If not found, Err(()) => AtomNotFoundError
+                        // Used in self.from_atomnames() methods
 
 pub mod inversion;  // include the inversion module
                     // inversion methods on the CP*{} Structs
@@ -1,53 +1,167 @@
-use core::panic;
 use std::fs::read_to_string;
-use pyo3::{pyclass, pymethods, PyErr, pyfunction};
+use pyo3::{pyclass, pymethods, PyErr, PyResult, pyfunction};
 
-use std::{ffi::OsString, fs::File, io::Write};
+use std::{ffi::OsString, fs::File, io::{BufWriter, Write}};
+
+use crate::errors::{FileFormatError, OutOfRangeError, PuckepyError, RingSizeError};
+use crate::formalism::ringperception::bonds_by_conect;
 
 
 
 // Read in queried file and see if it is valid
-fn validate_contents(fname: &String, fileformat: &str) -> String {
+fn validate_contents(fname: &String, fileformat: &str) -> PyResult<String> {
 
     if !fname.ends_with(fileformat) {
-        panic!("The {} is not a valid `{}` file format ", &fname, fileformat)
+        return Err(FileFormatError::new_err(format!("The {} is not a valid `{}` file format ", &fname, fileformat)))
     };
 
-    // Read contents once
-    let filecontents = match read_to_string(&fname) {
-        Ok(contents) => contents,
-        Err(e) => panic!("{}", e)
-    };
+    // Read contents once. An io::Error is raised as the matching OSError in Python
+    let filecontents = read_to_string(fname)?;
 
     // Check if contents exists
-    if filecontents.is_empty() { panic!("The {} is empty!", &filecontents)};
+    if filecontents.is_empty() { 
+        return Err(FileFormatError::new_err(format!("The {} is empty!", &fname)))
+    };
 
-    filecontents
+    Ok(filecontents)
     
 }
 
-// anames : atomnames Vector
-// coords : coordinates Vector
-fn populate_pdbfields(lines: &String, anames_container: &mut Vec<String>, coords_container: &mut Vec<[f64;3]>) {
+// Slice a fixed-width column out of a pdb line
+// lineno : 1-based line number in the file, to point the user to the faulty line
+fn pdb_column<'a>(line: &'a str, columns: std::ops::Range<usize>, field: &str, lineno: usize, fname: &str) -> PyResult<&'a str> {
 
-    anames_container.push(lines[12..16].trim().into());
+    match line.get(columns) {
+        Some(a) => Ok(a),
+        None => Err(FileFormatError::new_err(format!("Cannot read the {} column. At line {} of {}\n{}", field, lineno, fname, line)))
+    }
+}
 
-    let x = match lines[31..39].trim().parse::<f64>() {
-        Ok(a) => a,
-        Err(e) => panic!("Cannot parse x-coordinate : {}. At line\n{}", e, &lines)
-    };
-    let y = match lines[39..47].trim().parse::<f64>() {
-        Ok(a) => a,
-        Err(e) => panic!("Cannot parse y-coordinate : {}. At line\n{}", e, &lines)
-    };
-    let z = match lines[47..55].trim().parse::<f64>() {
-        Ok(a) => a,
-        Err(e) => panic!("Cannot parse z-coordinate : {}. At line\n{}", e, &lines)
-    };
-    coords_container.push([x,y,z]);
+// Parse a fixed-width coordinate column out of a pdb line
+fn pdb_coordinate(line: &str, columns: std::ops::Range<usize>, field: &str, lineno: usize, fname: &str) -> PyResult<f64> {
+
+    match pdb_column(line, columns, field, lineno, fname)?.trim().parse::<f64>() {
+        Ok(a) => Ok(a),
+        Err(e) => Err(FileFormatError::new_err(format!("Cannot parse {} : {}. At line {} of {}\n{}", field, e, lineno, fname, line)))
+    }
+}
+
+// Derive the element symbol from an atom name, when the element column is missing
+// Strips the leading digits (e.g. `1HB`) and keeps the first letter : `C1'` -> `C`, `HO5'` -> `H`
+pub fn element_from_atomname(atomname: &str) -> String {
+
+    match atomname.chars().find(|c| c.is_ascii_alphabetic()) {
+        Some(c) => c.to_ascii_uppercase().to_string(),
+        None => String::new()
+    }
+}
 
+// Optional numeric column of a pdb line (occupancy, B-factor), with its default when left blank
+fn pdb_optional(line: &str, columns: std::ops::Range<usize>, field: &str, default: f64, lineno: usize, fname: &str) -> PyResult<f64> {
 
+    match line.get(columns).map(|s| s.trim()) {
+        None | Some("") => Ok(default),
+        Some(s) => match s.parse::<f64>() {
+            Ok(a) => Ok(a),
+            Err(e) => Err(FileFormatError::new_err(format!("Cannot parse {} : {}. At line {} of {}\n{}", field, e, lineno, fname, line)))
+        }
+    }
+}
 
+// The columns of the ATOM/HETATM records, gathered while parsing
+#[derive(Default)]
+struct AtomRecords {
+    hetatm: Vec<bool>,
+    atomnames: Vec<String>,
+    elements: Vec<String>,
+    residuenames: Vec<String>,
+    residuenumbers: Vec<i32>,
+    chains: Vec<String>,
+    occupancies: Vec<f64>,
+    bfactors: Vec<f64>,
+    coordinates: Vec<[f64;3]>,
+}
+
+impl AtomRecords {
+
+    // Columns follow the PDB v3.3 specification (1-based : x 31-38, y 39-46, z 47-54, ...)
+    fn push_line(&mut self, line: &str, lineno: usize, fname: &str) -> PyResult<()> {
+
+        let atomname: String = pdb_column(line, 12..16, "atom name", lineno, fname)?.trim().into();
+
+        // The element column (77-78) is optional in older pdb files
+        let element: String = match line.get(76..78).map(|e| e.trim()) {
+            Some(e) if !e.is_empty() => e.to_string(),
+            _ => element_from_atomname(&atomname)
+        };
+
+        let residuenumber: i32 = match pdb_column(line, 22..26, "residue number", lineno, fname)?.trim().parse() {
+            Ok(a) => a,
+            Err(_) => return Err(FileFormatError::new_err(
+                    format!("Residue number cannot be parsed as an integer. At line {} of {}\n{}", lineno, fname, line)
+                    ))
+        };
+
+        let x = pdb_coordinate(line, 30..38, "x-coordinate", lineno, fname)?;
+        let y = pdb_coordinate(line, 38..46, "y-coordinate", lineno, fname)?;
+        let z = pdb_coordinate(line, 46..54, "z-coordinate", lineno, fname)?;
+
+        self.hetatm.push(line.starts_with("HETATM"));
+        self.atomnames.push(atomname);
+        self.elements.push(element);
+        self.residuenames.push(pdb_column(line, 17..20, "residue name", lineno, fname)?.trim().into());
+        self.residuenumbers.push(residuenumber);
+        self.chains.push(pdb_column(line, 21..22, "chain identifier", lineno, fname)?.trim().into());
+        self.occupancies.push(pdb_optional(line, 54..60, "occupancy", 1., lineno, fname)?);
+        self.bfactors.push(pdb_optional(line, 60..66, "B-factor", 0., lineno, fname)?);
+        self.coordinates.push([x,y,z]);
+
+        Ok(())
+    }
+
+    // Move the last parsed atom of `other` into self
+    fn take_last_from(&mut self, other: &mut AtomRecords) {
+        self.hetatm.extend(other.hetatm.pop());
+        self.atomnames.extend(other.atomnames.pop());
+        self.elements.extend(other.elements.pop());
+        self.residuenames.extend(other.residuenames.pop());
+        self.residuenumbers.extend(other.residuenumbers.pop());
+        self.chains.extend(other.chains.pop());
+        self.occupancies.extend(other.occupancies.pop());
+        self.bfactors.extend(other.bfactors.pop());
+        self.coordinates.extend(other.coordinates.pop());
+    }
+
+    // Split off the atoms from index `at` onwards, like Vec::split_off()
+    fn split_off(&mut self, at: usize) -> AtomRecords {
+        AtomRecords {
+            hetatm: self.hetatm.split_off(at),
+            atomnames: self.atomnames.split_off(at),
+            elements: self.elements.split_off(at),
+            residuenames: self.residuenames.split_off(at),
+            residuenumbers: self.residuenumbers.split_off(at),
+            chains: self.chains.split_off(at),
+            occupancies: self.occupancies.split_off(at),
+            bfactors: self.bfactors.split_off(at),
+            coordinates: self.coordinates.split_off(at),
+        }
+    }
+
+    fn into_pdb(self, filename: &str, data: String) -> Pdb {
+        Pdb {
+            filename: filename.to_string(),
+            data,
+            hetatm: self.hetatm,
+            atomnames: self.atomnames,
+            elements: self.elements,
+            residuenames: self.residuenames,
+            residuenumbers: self.residuenumbers,
+            chains: self.chains,
+            occupancies: self.occupancies,
+            bfactors: self.bfactors,
+            coordinates: self.coordinates,
+        }
+    }
 }
 //pub struct FileContents {
 //    fc: String // filecontents
@@ -55,11 +169,21 @@ fn populate_pdbfields(lines: &String, anames_container: &mut Vec<String>, coords
 //
 /// The only thing we need from the pdb is 
 /// Atom names Vec<String>
+/// Elements Vec<String>, to perceive the rings of the molecule
 /// Coordinates, best to do as Vec<[f64;3]>
+/// The other columns are kept to write the molecule back out with self.write()
 #[pyclass(get_all)]
 pub struct Pdb {
+    pub filename : String,
     pub data : String,
+    pub hetatm: Vec<bool>,
     pub atomnames: Vec<String>,
+    pub elements: Vec<String>,
+    pub residuenames: Vec<String>,
+    pub residuenumbers: Vec<i32>,
+    pub chains: Vec<String>,
+    pub occupancies: Vec<f64>,
+    pub bfactors: Vec<f64>,
     pub coordinates: Vec<[f64;3]>
 }
 /// Parses an pdb-file format
@@ -76,117 +200,394 @@ impl Pdb {
 
     // Result<Pdb,PyErr>  
     // This is a Result type because the user might mistype the name of the file,
-    // causing the function to appropriately crash
+    // causing the function to raise an exception
     #[new]
     fn new(filename: String) -> Result<Pdb, PyErr> {
 
-        let filecontents = validate_contents(&filename, ".pdb");
+        let filecontents = validate_contents(&filename, ".pdb")?;
 
-        Ok(Pdb {
-            data: filecontents,
-            atomnames: vec![],
-            coordinates: vec![],
-        })
+        Ok(AtomRecords::default().into_pdb(&filename, filecontents))
     }
 
-    fn parse(&self) -> Pdb {
+    fn parse(&self) -> PyResult<Pdb> {
 
-        if self.atomnames.len() > 0 || self.coordinates.len() > 0 {
-            panic!("This Pdb object has already been populated. Will not parse again.")
+        if !self.atomnames.is_empty() || !self.coordinates.is_empty() {
+            return Err(PuckepyError::new_err("This Pdb object has already been populated. Will not parse again."))
         };
 
-        let pdblines = &self.data.lines()
-                                 .map(|s| s.into())
-                                 .collect::<Vec<String>>();
-
-        let mut atomnames: Vec<String> = vec![];
-        let mut coordinates: Vec<[f64;3]> = vec![];
+        let mut records = AtomRecords::default();
 
-        for lines in pdblines.iter() {
+        for (i, lines) in self.data.lines().enumerate() {
             if lines.starts_with("ATOM") || lines.starts_with("HETATM") { 
-                populate_pdbfields(lines, &mut atomnames, &mut coordinates);
+                records.push_line(lines, i + 1, &self.filename)?;
             }
         };
         
-        Pdb { 
-            data: self.data.to_string(),
-            coordinates,
-            atomnames,
-        }
+        Ok(records.into_pdb(&self.filename, self.data.to_string()))
     }
 
 
     // Go over the molecular structure and parse by the change of residue numbers. 
     // Iterate if there are multiple residue numbers to begin with, 
     // then store a Vec of Pdb structs and return this
-    fn parse_by_monomers(&self) -> Vec<Pdb> {
-
-        let pdblines = &self.data.lines()
-                                 .map(|s| s.into())
-                                 .collect::<Vec<String>>();
+    fn parse_by_monomers(&self) -> PyResult<Vec<Pdb>> {
 
         let mut pdbs: Vec<Pdb> = vec![];
-        let mut resnumber: u16 = 42069; // residue names can only go to 9999, so this is safe :^)
-
-        let mut atomnames_container: Vec<String> = vec![];
-        let mut coordinates_container: Vec<[f64;3]> = vec![];
+        let mut resnumber: Option<i32> = None;
 
+        let mut records = AtomRecords::default();
 
-        let mut iter_lines = pdblines.iter();
-        while let Some(lines) = iter_lines.next() {
+        for (i, lines) in self.data.lines().enumerate() {
 
             if lines.starts_with("ATOM") || lines.starts_with("HETATM") { 
 
-                // Check residue number first
-                let parsed_resname: u16 = match lines[22..26].trim().parse() {
-                    Ok(a) => a,
-                    Err(_) => panic!("Residue number cannot be parsed as an integer, at\n{}", &lines)
-                };
-                // If there is a valid u16, set it as the current residue number, for the first one
-                // parsed from the file
-                if resnumber == 42069 { 
-                    resnumber = parsed_resname 
-                };
+                // Parse the line first, to check its residue number
+                records.push_line(lines, i + 1, &self.filename)?;
+                let parsed_resnumber = *records.residuenumbers.last().unwrap();
+
+                match resnumber {
+                    // The first residue number parsed from the file
+                    None => resnumber = Some(parsed_resnumber),
+                    Some(current) if current != parsed_resnumber => {
+                        // Move the current line into the next monomer, and drain the others into a Pdb 
+                        let mut next = AtomRecords::default();
+                        next.take_last_from(&mut records);
+
+                        pdbs.push(std::mem::replace(&mut records, next).into_pdb(&self.filename, format!("monomer_{}", current)));
+                        resnumber = Some(parsed_resnumber); // reset the parsed residuenumber to the residue number
+                    },
+                    Some(_) => (),
+                }
+            }
+        }
+        // Drain the final atomnames and coordinates Vecs into the last Pdb 
+        pdbs.push(records.into_pdb(&self.filename, format!("monomer_{}", resnumber.unwrap_or_default())));
 
-                if resnumber != parsed_resname {
-                    // Drain the atomnames and coordinates Vecs into a Pdb 
-                    // Push the Pdb onto the Vec<Pdb>
-                    pdbs.push( Pdb {
-                                   data: "monomer_".to_string() + &resnumber.to_string(),
-                                   atomnames: atomnames_container.drain(..).collect(),
-                                   coordinates: coordinates_container.drain(..).collect(),
-                               }
-                    );
+        Ok(pdbs) // return Vec<Pdb>
+    }
 
-                    resnumber = parsed_resname; // reset the parsed residuename to the residue name
+    // Write the molecule to a pdb file, e.g. after its coordinates have been modified
+    // `bonds` are written as CONECT records. Defaults to the CONECT records of the parsed file
+    // `coordinates` replace those of the parsed file, one for every atom
+    #[pyo3(signature = (filename, bonds=None, coordinates=None))]
+    fn write(&self, filename: OsString, bonds: Option<Vec<(usize, usize)>>, coordinates: Option<Vec<[f64;3]>>) -> PyResult<()> {
 
-                    // Start pushing to the cleared Vecs at the current line for a new Pdb struct
-                    populate_pdbfields(lines, &mut atomnames_container, &mut coordinates_container);
+        let coordinates = coordinates.unwrap_or_else(|| self.coordinates.clone());
+        if coordinates.len() != self.atomnames.len() {
+            return Err(PuckepyError::new_err(format!("Got {} coordinates for {} atomnames.", coordinates.len(), self.atomnames.len())))
+        };
 
-                } else {
-                    // Populate the current containers as normal
-                    populate_pdbfields(lines, &mut atomnames_container, &mut coordinates_container);
-                }
+        let bonds = match bonds {
+            Some(b) => b,
+            None => bonds_by_conect(self, self.coordinates.len())?,
+        };
+
+        let records = PdbRecords {
+            hetatm: &self.hetatm,
+            atomnames: &self.atomnames,
+            elements: &self.elements,
+            residuenames: &self.residuenames,
+            residuenumbers: &self.residuenumbers,
+            chains: &self.chains,
+            occupancies: &self.occupancies,
+            bfactors: &self.bfactors,
+            coordinates: &coordinates,
+        };
+
+        let filename = filename_with_extension(filename, ".pdb")?;
+        let mut buffer = BufWriter::new(File::create(filename)?);
+        buffer.write_all(format_pdb(&records, &bonds)?.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+
+
+
+
+
+
+
+
+
+
+
+/// Parses an mmCIF/PDBx-file format, as distributed by the PDB for large structures
+/// Only the `_atom_site` loop is read, which looks like this
+/// ```
+/// loop_
+/// _atom_site.group_PDB
+/// _atom_site.id
+/// _atom_site.type_symbol
+/// _atom_site.label_atom_id
+/// ...
+/// ATOM   1    O "O5'" . DA A 1 ? 7.577 2.015 28.826 1.00 0.00 ? 1 DA J "O5'" 1
+/// ```
+/// The parsed atoms are returned as a Pdb, so they can be queried by every formalism
+#[pyclass]
+pub struct Mmcif {
+    filename: String,
+    filecontents: String,
+}
+
+#[pymethods]
+impl Mmcif {
+
+    #[new]
+    fn new(filename: String) -> Result<Mmcif, PyErr> {
+
+        let extension = if filename.ends_with(".mmcif") { ".mmcif" } else { ".cif" };
+        let filecontents = validate_contents(&filename, extension)?;
+
+        Ok(Mmcif { filename, filecontents })
+    }
+
+    fn parse(&self) -> PyResult<Pdb> {
+
+        Ok(parse_atom_site(&self.filecontents, &self.filename)?.into_pdb(&self.filename, String::new()))
+    }
+
+    // Split the atoms into monomers at every change of chain or residue number, 
+    // as the numbering of the residues restarts in every chain of large assemblies
+    fn parse_by_monomers(&self) -> PyResult<Vec<Pdb>> {
+
+        let mut records = parse_atom_site(&self.filecontents, &self.filename)?;
+
+        let boundaries: Vec<usize> = (1..records.atomnames.len())
+            .filter(|&i| records.residuenumbers[i] != records.residuenumbers[i - 1] || records.chains[i] != records.chains[i - 1])
+            .collect();
+
+        // Split from the back, so the indices of the remaining boundaries stay valid
+        let mut pdbs: Vec<Pdb> = vec![];
+        for at in boundaries.into_iter().rev() {
+            let monomer = records.split_off(at);
+            let resnumber = monomer.residuenumbers[0];
+            pdbs.push(monomer.into_pdb(&self.filename, format!("monomer_{}", resnumber)));
+        }
+        let resnumber = records.residuenumbers.first().copied().unwrap_or_default();
+        pdbs.push(records.into_pdb(&self.filename, format!("monomer_{}", resnumber)));
+        pdbs.reverse();
+
+        Ok(pdbs)
+    }
+}
+
+// Split a line of a CIF data loop into its values
+// Values are separated by whitespace, or quoted by ' or " when they contain whitespace or quotes (`"C1'"`) 
+fn cif_tokens(line: &str) -> Vec<&str> {
+
+    let mut tokens: Vec<&str> = vec![];
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue
+        };
+
+        let start = i;
+        if bytes[i] == b'\'' || bytes[i] == b'"' {
+            // A quote only closes the value when followed by whitespace or the end of the line
+            let quote = bytes[i];
+            i += 1;
+            while i < bytes.len() && !(bytes[i] == quote && bytes.get(i + 1).map_or(true, |b| b.is_ascii_whitespace())) {
+                i += 1;
+            }
+            tokens.push(&line[start + 1..i.min(bytes.len())]);
+            i += 1;
+        } else {
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
             }
+            tokens.push(&line[start..i]);
+        }
+    }
+
+    tokens
+}
+
+// The positions of the `_atom_site` items in a row of the loop
+// The `auth_` items are preferred, as these match the names and numbering of the pdb-file format
+struct AtomSiteColumns {
+    group: Option<usize>,
+    atomname: usize,
+    element: Option<usize>,
+    residuename: Option<usize>,
+    residuenumber: [Option<usize>; 2],
+    chain: Option<usize>,
+    occupancy: Option<usize>,
+    bfactor: Option<usize>,
+    model: Option<usize>,
+    xyz: [usize; 3],
+}
+
+impl AtomSiteColumns {
+
+    fn new(headers: &[&str], fname: &str) -> PyResult<AtomSiteColumns> {
+
+        let find = |item: &str| headers.iter().position(|h| h.strip_prefix("_atom_site.") == Some(item));
+        let required = |item: &str| match find(item) {
+            Some(a) => Ok(a),
+            None => Err(FileFormatError::new_err(format!("The _atom_site loop of {} has no _atom_site.{} item.", fname, item)))
+        };
+
+        Ok(AtomSiteColumns {
+            group: find("group_PDB"),
+            atomname: match find("auth_atom_id") {
+                Some(a) => a,
+                None => required("label_atom_id")?
+            },
+            element: find("type_symbol"),
+            residuename: find("auth_comp_id").or(find("label_comp_id")),
+            residuenumber: [find("auth_seq_id"), find("label_seq_id")],
+            chain: find("auth_asym_id").or(find("label_asym_id")),
+            occupancy: find("occupancy"),
+            bfactor: find("B_iso_or_equiv"),
+            model: find("pdbx_PDB_model_num"),
+            xyz: [required("Cartn_x")?, required("Cartn_y")?, required("Cartn_z")?],
+        })
+    }
+}
+
+// A CIF value is left out by `?` (unknown) or `.` (not applicable)
+fn cif_value<'a>(row: &[&'a str], column: Option<usize>) -> Option<&'a str> {
+
+    match column.map(|c| row[c]) {
+        Some("?") | Some(".") | None => None,
+        Some(a) => Some(a),
+    }
+}
+
+fn cif_number(row: &[&str], column: Option<usize>, field: &str, default: f64, lineno: usize, fname: &str) -> PyResult<f64> {
+
+    match cif_value(row, column) {
+        None => Ok(default),
+        Some(s) => match s.parse::<f64>() {
+            Ok(a) => Ok(a),
+            Err(e) => Err(FileFormatError::new_err(format!("Cannot parse {} : {}. At line {} of {}", field, e, lineno, fname)))
+        }
+    }
+}
+
+impl AtomRecords {
+
+    fn push_row(&mut self, row: &[&str], columns: &AtomSiteColumns, lineno: usize, fname: &str) -> PyResult<()> {
+
+        let atomname: String = match cif_value(row, Some(columns.atomname)) {
+            Some(a) => a.to_string(),
+            None => return Err(FileFormatError::new_err(format!("Missing atom name. At line {} of {}", lineno, fname)))
+        };
+
+        let element: String = match cif_value(row, columns.element) {
+            Some(e) => e.to_string(),
+            None => element_from_atomname(&atomname)
+        };
+
+        // label_seq_id is `.` for waters and ligands, so fall back on it only when needed
+        let residuenumber: i32 = match columns.residuenumber.iter().find_map(|&c| cif_value(row, c)) {
+            Some(a) => match a.parse() {
+                Ok(a) => a,
+                Err(_) => return Err(FileFormatError::new_err(
+                        format!("Residue number cannot be parsed as an integer. At line {} of {}", lineno, fname)
+                        ))
+            },
+            None => return Err(FileFormatError::new_err(format!("Missing residue number. At line {} of {}", lineno, fname)))
+        };
+
+        let mut xyz = [0.; 3];
+        for (coord, (column, field)) in xyz.iter_mut().zip(columns.xyz.iter().zip(["x-coordinate", "y-coordinate", "z-coordinate"])) {
+            *coord = match cif_value(row, Some(*column)) {
+                Some(_) => cif_number(row, Some(*column), field, 0., lineno, fname)?,
+                None => return Err(FileFormatError::new_err(format!("Missing {}. At line {} of {}", field, lineno, fname)))
+            };
         }
-        // Drain the final atomnames and coordinates Vecs into the last Pdb 
-        pdbs.push( Pdb {
-                       data: "monomer_".to_string() + &resnumber.to_string(),
-                       atomnames: atomnames_container.drain(..).collect(),
-                       coordinates: coordinates_container.drain(..).collect(),
-                   }
-        );
 
-        pdbs // return Vec<Pdb>
+        self.hetatm.push(cif_value(row, columns.group) == Some("HETATM"));
+        self.atomnames.push(atomname);
+        self.elements.push(element);
+        self.residuenames.push(cif_value(row, columns.residuename).unwrap_or_default().to_string());
+        self.residuenumbers.push(residuenumber);
+        self.chains.push(cif_value(row, columns.chain).unwrap_or_default().to_string());
+        self.occupancies.push(cif_number(row, columns.occupancy, "occupancy", 1., lineno, fname)?);
+        self.bfactors.push(cif_number(row, columns.bfactor, "B-factor", 0., lineno, fname)?);
+        self.coordinates.push(xyz);
+
+        Ok(())
     }
 }
 
+// Read the atoms of the `_atom_site` loop. 
+// Of multi-model files (e.g. NMR ensembles) only the first model is kept
+fn parse_atom_site(data: &str, fname: &str) -> PyResult<AtomRecords> {
+
+    let mut lines = data.lines().enumerate().peekable();
+
+    while let Some((_, line)) = lines.next() {
+
+        if line.trim() != "loop_" {
+            continue
+        };
+
+        // The item names of the loop precede its values
+        let mut headers: Vec<&str> = vec![];
+        while let Some((_, l)) = lines.peek() {
+            match l.split_whitespace().next() {
+                Some(h) if h.starts_with('_') => headers.push(h),
+                _ => break
+            }
+            lines.next();
+        }
+
+        if !headers.first().is_some_and(|h| h.starts_with("_atom_site.")) {
+            continue
+        };
+
+        let columns = AtomSiteColumns::new(&headers, fname)?;
+        let mut records = AtomRecords::default();
+        let mut first_model: Option<&str> = None;
+
+        // A row may be wrapped over several lines
+        let mut row: Vec<&str> = Vec::with_capacity(headers.len());
+        let mut row_lineno: usize = 0;
+
+        while let Some((i, l)) = lines.peek() {
+            let trimmed = l.trim();
+            if trimmed.starts_with('_') || trimmed.starts_with('#') || trimmed.starts_with("loop_") || trimmed.starts_with("data_") {
+                break
+            };
 
+            if row.is_empty() {
+                row_lineno = i + 1;
+            };
+            row.extend(cif_tokens(trimmed));
+            lines.next();
 
+            while row.len() >= headers.len() {
+                let values: Vec<&str> = row.drain(..headers.len()).collect();
 
+                let model = cif_value(&values, columns.model);
+                if first_model.is_none() {
+                    first_model = model;
+                };
+                if model == first_model {
+                    records.push_row(&values, &columns, row_lineno, fname)?;
+                };
+            }
+        }
 
+        if !row.is_empty() {
+            return Err(FileFormatError::new_err(
+                    format!("The last row of the _atom_site loop of {} has {} values, expected {}. At line {}", fname, row.len(), headers.len(), row_lineno)
+                    ))
+        };
 
+        return Ok(records)
+    }
 
+    Err(FileFormatError::new_err(format!("No _atom_site loop was found in {}", fname)))
+}
 
 
 
@@ -195,6 +596,7 @@ impl Pdb {
 /// Coordinates, best to do as Vec<[f64;3]>
 #[pyclass]
 pub struct Xyz {
+    filename: String,
     filecontents: String,
 }
 
@@ -213,56 +615,47 @@ impl Xyz {
     
     // Result<Pdb,PyErr>  
     // This is a Result type because the user might mistype the name of the file,
-    // causing the function to appropriately crash
+    // causing the function to raise an exception
     #[new]
     fn new(filename: String) -> Result<Xyz, PyErr> {
 
-        let filecontents = validate_contents(&filename, ".xyz");
+        let filecontents = validate_contents(&filename, ".xyz")?;
 
         Ok(Xyz {
+            filename,
             filecontents,
         })
 
     }
 
     // Parses filecontents and returns an array of coordinates
-    fn parse(&self) -> Vec<[f64;3]> {
+    fn parse(&self) -> PyResult<Vec<[f64;3]>> {
 
         let mut coordinates: Vec<[f64;3]> = vec![];
-        let xyz_lines = &self.filecontents.lines()
-                                  .map(|s| s.into())
-                                  .collect::<Vec<String>>();
-        let mut xyz_iter = xyz_lines.iter();
 
-        // Two next calls, because xyz files always start with two lines of header data
+        // Skip two lines, because xyz files always start with two lines of header data
         // We just discard this
-        let _ = &xyz_iter.next();
-        let _ = &xyz_iter.next();
-
-        for l in xyz_iter {
+        for (i, l) in self.filecontents.lines().enumerate().skip(2) {
             let splits: Vec<&str> = l.split_whitespace().collect();
 
             if splits.len() != 4 {
                 continue
             };
 
-            let x = match splits[1].parse::<f64>() {
-                Ok(a) => a,
-                Err(e) => panic!("{}", e)
-            };
-            let y = match splits[2].trim().parse::<f64>() {
-                Ok(a) => a,
-                Err(e) => panic!("{}", e)
-            };
-            let z = match splits[3].trim().parse::<f64>() {
-                Ok(a) => a,
-                Err(e) => panic!("{}", e)
-            };
-            coordinates.push([x,y,z]);
+            let mut xyz = [0.;3];
+            for (j, dim) in ["x", "y", "z"].iter().enumerate() {
+                xyz[j] = match splits[j + 1].parse::<f64>() {
+                    Ok(a) => a,
+                    Err(e) => return Err(FileFormatError::new_err(
+                            format!("Cannot parse {}-coordinate : {}. At line {} of {}\n{}", dim, e, i + 1, &self.filename, l)
+                            ))
+                };
+            }
+            coordinates.push(xyz);
 
         }
 
-        coordinates
+        Ok(coordinates)
     }
 }
 
@@ -270,6 +663,21 @@ impl Xyz {
 
 
 
+// Transform the prompted filename to a String and append the extension, if it is missing
+fn filename_with_extension(filename: OsString, extension: &str) -> PyResult<String> {
+
+    let mut filename: String = match filename.into_string() {
+        Ok(a) => a,
+        Err(_) => return Err(PuckepyError::new_err("Passed argument `filename` contains invalid UTF-8"))
+    };
+
+    if !filename.ends_with(extension){ 
+        filename.push_str(extension)
+    };
+
+    Ok(filename)
+}
+
 
 /// https://www.cgl.ucsf.edu/chimera/docs/UsersGuide/tutorials/pdbintro.html : PDB format
 ///
@@ -277,71 +685,314 @@ impl Xyz {
 #[pyfunction]
 pub fn write_to_pdb(filename: OsString,  coordinates: Vec<[f64;3]>, residuename: String) -> Result<(), PyErr> {
     
-    let mut filename: String = filename.to_str().expect("Passed argument `filename` contains invalid UTF-8").to_owned();
-    if !filename.ends_with(".pdb"){ 
-        filename.push_str(".pdb")
+    let filename = filename_with_extension(filename, ".pdb")?;
+    let content = format_ring_pdb(&coordinates, &residuename)?;
+
+    let mut buffer = File::create(filename)?;
+    buffer.write_all(content.as_bytes())?;
+    
+    Ok(())
+}
+
+#[pyfunction]
+pub fn write_to_xyz(filename: OsString, coordinates: Vec<[f64;3]>) -> Result<(), PyErr> {
+
+    let filename = filename_with_extension(filename, ".xyz")?;
+    let content = format_ring_xyz(&coordinates, "Coordinates generated by pucke.py")?;
+
+    let mut buffer = File::create(filename)?;
+    buffer.write_all(content.as_bytes())?;
+    
+    Ok(())
+}
+
+/// Write any molecule to a pdb file, following the PDB v3.3 specification
+/// Only the `atomnames` and the `coordinates` are required. The other columns default to :
+/// elements guessed from the atomnames, residue UNK 1 on chain A, occupancy 1.00 and B-factor 0.00
+/// `bonds` are (i, j) atom indices (0-based), written as CONECT records
+#[pyfunction]
+#[pyo3(signature = (filename, atomnames, coordinates, elements=None, residuenames=None, residuenumbers=None, chains=None, occupancies=None, bfactors=None, bonds=None))]
+#[allow(clippy::too_many_arguments)] // every column of the ATOM record is a keyword argument in Python
+pub fn write_pdb(filename: OsString, atomnames: Vec<String>, coordinates: Vec<[f64;3]>,
+                 elements: Option<Vec<String>>, residuenames: Option<Vec<String>>, residuenumbers: Option<Vec<i32>>,
+                 chains: Option<Vec<String>>, occupancies: Option<Vec<f64>>, bfactors: Option<Vec<f64>>,
+                 bonds: Option<Vec<(usize, usize)>>) -> PyResult<()> {
+
+    let n_atoms = atomnames.len();
+    if coordinates.len() != n_atoms {
+        return Err(PuckepyError::new_err(format!("Got {} coordinates for {} atomnames.", coordinates.len(), n_atoms)))
     };
 
-    // Residue Name limitations of PBB format
-    if residuename.len() > 3 {
-        panic!("Residue name cannot be larger than three characters")
+    // Fill out the missing columns with their defaults and check the lengths of the others
+    fn column<T: Clone>(values: Option<Vec<T>>, default: T, field: &str, n_atoms: usize) -> PyResult<Vec<T>> {
+        match values {
+            None => Ok(vec![default; n_atoms]),
+            Some(v) if v.len() == n_atoms => Ok(v),
+            Some(v) => Err(PuckepyError::new_err(format!("Got {} {} for {} atomnames.", v.len(), field, n_atoms)))
+        }
+    }
+
+    let elements = match elements {
+        None => atomnames.iter().map(|name| element_from_atomname(name)).collect(),
+        e => column(e, String::new(), "elements", n_atoms)?,
     };
+    let residuenames = column(residuenames, "UNK".to_string(), "residuenames", n_atoms)?;
+    let residuenumbers = column(residuenumbers, 1, "residuenumbers", n_atoms)?;
+    let chains = column(chains, "A".to_string(), "chains", n_atoms)?;
+    let occupancies = column(occupancies, 1., "occupancies", n_atoms)?;
+    let bfactors = column(bfactors, 0., "bfactors", n_atoms)?;
+
+    let records = PdbRecords {
+        hetatm: &vec![false; n_atoms],
+        atomnames: &atomnames,
+        elements: &elements,
+        residuenames: &residuenames,
+        residuenumbers: &residuenumbers,
+        chains: &chains,
+        occupancies: &occupancies,
+        bfactors: &bfactors,
+        coordinates: &coordinates,
+    };
+
+    let filename = filename_with_extension(filename, ".pdb")?;
+    let content = format_pdb(&records, &bonds.unwrap_or_default())?;
+
+    let mut buffer = BufWriter::new(File::create(filename)?);
+    buffer.write_all(content.as_bytes())?;
+
+    Ok(())
+}
 
-    let mut atomnames: Vec<&str> = vec!["O4'", "C1'", "C2'", "C3'", "C4'"];
-    let mut atomnumbs: Vec<&str> = vec!["1", "2", "3", "4", "5"];
+// The columns of the ATOM/HETATM records to write out, one value per atom
+struct PdbRecords<'a> {
+    hetatm: &'a [bool],
+    atomnames: &'a [String],
+    elements: &'a [String],
+    residuenames: &'a [String],
+    residuenumbers: &'a [i32],
+    chains: &'a [String],
+    occupancies: &'a [f64],
+    bfactors: &'a [f64],
+    coordinates: &'a [[f64;3]],
+}
 
-    // If coordinates is a sixring system
-    if coordinates.len() == 6 {
-        atomnames.push("C5'");
-        atomnumbs.push("6");
-        atomnames[0] = "O5'"; 
+// Atom names start in column 14, unless they fill all four columns or have a two-letter element (e.g. `FE`)
+fn pdb_atomname(atomname: &str, element: &str) -> String {
+    match atomname.len() >= 4 || element.len() == 2 {
+        true => format!("{:<4}", atomname),
+        false => format!(" {:<3}", atomname),
+    }
+}
+
+// Format the ATOM/HETATM records, with serial numbers starting at 1, followed by the CONECT records and END
+fn format_pdb(records: &PdbRecords, bonds: &[(usize, usize)]) -> PyResult<String> {
+
+    let n_atoms = records.coordinates.len();
+
+    if n_atoms > 99999 {
+        return Err(FileFormatError::new_err(format!("The pdb format holds at most 99999 atoms. Got {}.", n_atoms)))
     };
 
-    let mut buffer = File::create(filename).expect("Cannot open file!");
+    let mut content = String::with_capacity(81 * n_atoms);
 
-    // Iterate over coordinates of Coordinates and format the pdb file correctly
-    for (i, aname) in atomnames.iter().enumerate() {
+    for i in 0..n_atoms {
 
-        let coordinate = &coordinates[i];
-        let content = format!(
-            "ATOM   {:>4} {:<4} {:>3} A   1    {:width$.precision$}{:width$.precision$}{:width$.precision$}  {:>22}\n",
-            atomnumbs[i], aname, residuename, coordinate[0], coordinate[1], coordinate[2], aname.chars().nth(0).unwrap(), width=8, precision=3 
-            // Atom number, Atom name, residue name, x coord, y, coord, z coord, element symbol
-            );
-        buffer.write_all(content.as_bytes()).expect("Cannot convert String to bytes");
+        let (atomname, residuename, chain) = (&records.atomnames[i], &records.residuenames[i], &records.chains[i]);
+
+        if atomname.len() > 4 || residuename.len() > 3 || chain.len() > 1 || records.elements[i].len() > 2 {
+            return Err(FileFormatError::new_err(format!(
+                        "Atom name \"{}\", residue name \"{}\", chain \"{}\" or element \"{}\" is too wide for the pdb columns (4, 3, 1 and 2 characters)",
+                        atomname, residuename, chain, records.elements[i]
+                        )))
+        };
+
+        if !(-999..=9999).contains(&records.residuenumbers[i]) {
+            return Err(FileFormatError::new_err(format!("Residue number {} does not fit in the pdb columns", records.residuenumbers[i])))
+        };
+
+        let c = records.coordinates[i];
+        content.push_str(&format!(
+            "{:<6}{:>5} {} {:>3} {:1}{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}          {:>2}\n",
+            if records.hetatm[i] { "HETATM" } else { "ATOM" }, i + 1,
+            pdb_atomname(atomname, &records.elements[i]), residuename, chain, records.residuenumbers[i],
+            c[0], c[1], c[2], records.occupancies[i], records.bfactors[i], records.elements[i].to_uppercase()
+            ));
+    }
+
+    // Every atom lists all of its bonded atoms, at most four per CONECT record
+    let mut bonded: Vec<Vec<usize>> = vec![vec![]; n_atoms];
+    for (i, j) in bonds {
+        if *i >= n_atoms || *j >= n_atoms {
+            return Err(OutOfRangeError::new_err(format!("Bond ({}, {}) is out of range for {} atoms.", i, j, n_atoms)))
+        };
+        bonded[*i].push(*j);
+        bonded[*j].push(*i);
     }
+
+    for (i, partners) in bonded.iter_mut().enumerate() {
+        partners.sort_unstable();
+        partners.dedup();
+        for chunk in partners.chunks(4) {
+            content.push_str(&format!("CONECT{:>5}", i + 1));
+            for j in chunk {
+                content.push_str(&format!("{:>5}", j + 1));
+            }
+            content.push('\n');
+        }
+    }
+
+    content.push_str("END\n");
+
+    Ok(content)
+}
+
+/// Write every conformer as a MODEL/ENDMDL block of a single pdb file
+#[pyfunction]
+pub fn write_models_to_pdb(filename: OsString, conformers: Vec<Vec<[f64;3]>>, residuename: String) -> Result<(), PyErr> {
+
+    let filename = filename_with_extension(filename, ".pdb")?;
+
+    let mut buffer = BufWriter::new(File::create(filename)?);
+
+    for (i, coordinates) in conformers.iter().enumerate() {
+        buffer.write_all(format!("MODEL     {:>4}\n", i + 1).as_bytes())?;
+        buffer.write_all(format_ring_pdb(coordinates, &residuename)?.as_bytes())?;
+        buffer.write_all("ENDMDL\n".as_bytes())?;
+    }
+    buffer.write_all("END\n".as_bytes())?;
     
     Ok(())
 }
 
+/// Write every conformer as a frame of a single, concatenated xyz file
 #[pyfunction]
-pub fn write_to_xyz(filename: OsString, coordinates: Vec<[f64;3]>) -> Result<(), PyErr> {
+pub fn write_frames_to_xyz(filename: OsString, conformers: Vec<Vec<[f64;3]>>) -> Result<(), PyErr> {
 
-    let mut filename: String = filename.to_str().expect("Passed argument `filename` contains invalid UTF-8").to_owned();
-    if !filename.ends_with(".xyz"){ 
-        filename.push_str(".xyz")
-    };
-    let mut buffer = File::create(filename).expect("Cannot open file!");
+    let filename = filename_with_extension(filename, ".xyz")?;
 
-    let mut elements: Vec<&str> = vec!["O", "C", "C", "C", "C"];
-    if coordinates.len() == 6 {
-        elements.push("C");
+    let mut buffer = BufWriter::new(File::create(filename)?);
+
+    for (i, coordinates) in conformers.iter().enumerate() {
+        let comment = format!("Frame {}, coordinates generated by pucke.py", i + 1);
+        buffer.write_all(format_ring_xyz(coordinates, &comment)?.as_bytes())?;
+    }
+    
+    Ok(())
+}
+
+// The atom names and elements a ring is written with
+// Five- and six-membered rings are named as furanoses (O4', C1' .. C4') and pyranoses (O5', C1' .. C5'),
+// every other ring size as a ring of carbons (C1 .. CN)
+fn ring_atoms(ring_size: usize) -> PyResult<Vec<(String, &'static str)>> {
+
+    match ring_size {
+        0..=2 => Err(RingSizeError::new_err(format!("Expected at least 3 coordinates to write. Got {}.", ring_size))),
+        5 | 6 => {
+            let oxygen = if ring_size == 5 { "O4'" } else { "O5'" };
+            Ok(std::iter::once((oxygen.to_string(), "O"))
+                .chain((1..ring_size).map(|i| (format!("C{}'", i), "C")))
+                .collect())
+        },
+        _ => Ok((1..=ring_size).map(|i| (format!("C{}", i), "C")).collect()),
+    }
+}
+
+// Format the ATOM records of a ring
+fn format_ring_pdb(coordinates: &[[f64;3]], residuename: &str) -> PyResult<String> {
+
+    // Residue Name limitations of PBB format
+    if residuename.len() > 3 {
+        return Err(FileFormatError::new_err(format!("Residue name \"{}\" cannot be larger than three characters", residuename)))
     };
 
-    buffer.write_all(format!("{}\n", coordinates.len()).as_bytes()).expect("Cannot convert &str to bytes");
-    buffer.write_all("Coordinates generated by pucke.py\n".as_bytes()).expect("Cannot convert &str to bytes");
+    let mut content = String::new();
+
+    // Iterate over coordinates of Coordinates and format the pdb file correctly
+    for (i, ((aname, element), coordinate)) in ring_atoms(coordinates.len())?.iter().zip(coordinates.iter()).enumerate() {
+
+        content.push_str(&format!(
+            "ATOM   {:>4} {:<4} {:>3} A   1    {:width$.precision$}{:width$.precision$}{:width$.precision$}  {:>22}\n",
+            i + 1, aname, residuename, coordinate[0], coordinate[1], coordinate[2], element, width=8, precision=3 
+            // Atom number, Atom name, residue name, x coord, y, coord, z coord, element symbol
+            ));
+    }
+
+    Ok(content)
+}
+
+// Format a frame of a ring : the amount of atoms, a comment line and the coordinates
+fn format_ring_xyz(coordinates: &[[f64;3]], comment: &str) -> PyResult<String> {
+
+    let mut content = format!("{}\n{}\n", coordinates.len(), comment);
 
     // Iterate over coordinates of Coordinates and format the xyz file correctly
-    for i in 0..coordinates.len() {
+    for ((_, element), coordinate) in ring_atoms(coordinates.len())?.iter().zip(coordinates.iter()) {
 
-        let coordinate = &coordinates[i];
-        let content = format!(
+        content.push_str(&format!(
             "{:>2} {:width$.precision$}   {:width$.precision$}   {:width$.precision$}\n",
-            elements[i], coordinate[0], coordinate[1], coordinate[2], width=19, precision=14 
+            element, coordinate[0], coordinate[1], coordinate[2], width=19, precision=14 
             // Element symbol, x coord, y, coord, z coord
-            );
-        buffer.write_all(content.as_bytes()).expect("Cannot convert String to bytes");
+            ));
     }
     
-    Ok(())
+    Ok(content)
+}
+
+
+#[cfg(test)]
+mod test_mmcif {
+
+    use super::*;
+
+    const ATOM_SITE: &str = "data_TEST
+#
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.occupancy
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+_atom_site.pdbx_PDB_model_num
+ATOM   1 O \"O4'\" DA 1 0.0 1.0 2.0 ? 5 A 1
+ATOM   2 C \"C1'\" DA 1 1.0 1.0 2.0 1.00 5 A 1
+ATOM   3 C C2' DG 2
+2.0 1.0 2.0 0.50 6 A 1
+HETATM 4 O O   HOH . 3.0 1.0 2.0 1.00 101 B 1
+ATOM   5 O \"O4'\" DA 1 9.0 9.0 9.0 1.00 5 A 2
+#
+";
+
+    #[test]
+    fn quoted_tokens() {
+        assert_eq!(cif_tokens("ATOM 1 \"O4'\" 'it''s' . ?"), vec!["ATOM", "1", "O4'", "it''s", ".", "?"]);
+    }
+
+    #[test]
+    fn atom_site_first_model() {
+        let records = parse_atom_site(ATOM_SITE, "test.cif").unwrap();
+
+        assert_eq!(records.atomnames, vec!["O4'", "C1'", "C2'", "O"]);
+        assert_eq!(records.residuenumbers, vec![5, 5, 6, 101]);
+        assert_eq!(records.hetatm, vec![false, false, false, true]);
+        assert_eq!(records.occupancies, vec![1., 1., 0.5, 1.]);
+        assert_eq!(records.coordinates[2], [2., 1., 2.]);
+    }
+
+    #[test]
+    fn monomers_by_chain_and_residue() {
+        let mmcif = Mmcif { filename: "test.cif".to_string(), filecontents: ATOM_SITE.to_string() };
+        let monomers = mmcif.parse_by_monomers().unwrap();
+
+        assert_eq!(monomers.len(), 3);
+        assert_eq!(monomers[0].atomnames, vec!["O4'", "C1'"]);
+        assert_eq!(monomers[2].chains, vec!["B"]);
+        assert_eq!(monomers[2].data, "monomer_101");
+    }
 }
@@ -0,0 +1,218 @@
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::errors::AtomNotFoundError;
+use crate::formalism::{
+    moleculefile::Pdb,
+    altonasund::altona_sundaralingam,
+    iupac::fivering_conformer,
+    residues::{split_residues, Residue},
+};
+
+// The O3'(i-1) - P(i) bond is 1.61 Angstrom. Residues further apart are not linked (chain break)
+const MAX_LINKAGE_BONDLENGTH: f64 = 2.0;
+
+const SUGAR_ATOMNAMES: [&str; 5] = ["O4'", "C1'", "C2'", "C3'", "C4'"];
+
+/// The NucleicAcid struct holds the backbone torsions, glycosidic torsion and sugar pucker
+/// of every nucleotide of a DNA/RNA/XNA strand. Every field holds one value per residue.
+///
+/// alpha   : O3'(i-1) - P - O5' - C5'
+/// beta    : P - O5' - C5' - C4'
+/// gamma   : O5' - C5' - C4' - C3'
+/// delta   : C5' - C4' - C3' - O3'
+/// epsilon : C4' - C3' - O3' - P(i+1)
+/// zeta    : C3' - O3' - P(i+1) - O5'(i+1)
+/// chi     : O4' - C1' - N9 - C4 (purines), O4' - C1' - N1 - C2 (pyrimidines), O4' - C1' - C5 - C4 (pseudouridine)
+///
+/// Torsions are in degrees [-180. -> 180.]. A torsion is None when one of its atoms is missing 
+/// (e.g. alpha of the 5'-terminal residue), or when it crosses a chain break.
+/// `glycosidic` is "syn" for -90 < chi < 90, else "anti"
+/// The sugar pucker is the Altona-Sundaralingam (amplitude, phase_angle) of O4', C1', C2', C3', C4',
+/// with its IUPAC canonical conformer in `sugar_conformer`
+#[pyclass(get_all)]
+pub struct NucleicAcid {
+    pub chains: Vec<String>,
+    pub residuenames: Vec<String>,
+    pub residuenumbers: Vec<i32>,
+    pub alpha: Vec<Option<f64>>,
+    pub beta: Vec<Option<f64>>,
+    pub gamma: Vec<Option<f64>>,
+    pub delta: Vec<Option<f64>>,
+    pub epsilon: Vec<Option<f64>>,
+    pub zeta: Vec<Option<f64>>,
+    pub chi: Vec<Option<f64>>,
+    pub glycosidic: Vec<Option<String>>,
+    pub amplitude: Vec<Option<f64>>,
+    pub phase_angle: Vec<Option<f64>>,
+    pub sugar_conformer: Vec<Option<String>>,
+}
+
+#[pymethods]
+impl NucleicAcid {
+
+    // Residues without a sugar (waters, ions, ligands, amino acids) are left out
+    #[new]
+    fn new(pdb: &Pdb) -> PyResult<NucleicAcid> {
+
+        let residues: Vec<Residue> = split_residues(pdb)
+            .into_iter()
+            .filter(|r| r.has("C1'") || r.has("C4'"))
+            .collect();
+
+        if residues.is_empty() {
+            return Err(AtomNotFoundError::new_err(format!("Could not find any nucleotide (C1' or C4' atomname) in {}.", pdb.filename)))
+        };
+
+        let mut na = NucleicAcid::with_capacity(residues.len());
+
+        for (i, residue) in residues.iter().enumerate() {
+
+            let previous = match i.checked_sub(1).map(|j| &residues[j]) {
+                Some(p) if p.is_linked(residue, "O3'", "P", MAX_LINKAGE_BONDLENGTH) => Some(p),
+                _ => None,
+            };
+            let next = match residues.get(i + 1) {
+                Some(n) if residue.is_linked(n, "O3'", "P", MAX_LINKAGE_BONDLENGTH) => Some(n),
+                _ => None,
+            };
+            let this = Some(residue);
+
+            na.chains.push(residue.chain.clone());
+            na.residuenames.push(residue.name.clone());
+            na.residuenumbers.push(residue.number);
+
+            na.alpha.push(Residue::dihedral([(previous, "O3'"), (this, "P"), (this, "O5'"), (this, "C5'")]));
+            na.beta.push(Residue::dihedral([(this, "P"), (this, "O5'"), (this, "C5'"), (this, "C4'")]));
+            na.gamma.push(Residue::dihedral([(this, "O5'"), (this, "C5'"), (this, "C4'"), (this, "C3'")]));
+            na.delta.push(Residue::dihedral([(this, "C5'"), (this, "C4'"), (this, "C3'"), (this, "O3'")]));
+            na.epsilon.push(Residue::dihedral([(this, "C4'"), (this, "C3'"), (this, "O3'"), (next, "P")]));
+            na.zeta.push(Residue::dihedral([(this, "C3'"), (this, "O3'"), (next, "P"), (next, "O5'")]));
+
+            let chi = glycosidic_torsion(residue);
+            na.chi.push(chi);
+            na.glycosidic.push(chi.map(|c| if c.abs() < 90. { "syn" } else { "anti" }.to_string()));
+
+            let sugar: Option<Vec<[f64;3]>> = SUGAR_ATOMNAMES.iter().map(|name| residue.atom(name)).collect();
+            match sugar {
+                Some(molarray) => {
+                    let (amplitude, phase_angle) = altona_sundaralingam(&molarray);
+                    na.amplitude.push(Some(amplitude));
+                    na.phase_angle.push(Some(phase_angle));
+                    na.sugar_conformer.push(Some(fivering_conformer(phase_angle, None)?.label));
+                },
+                None => {
+                    na.amplitude.push(None);
+                    na.phase_angle.push(None);
+                    na.sugar_conformer.push(None);
+                }
+            }
+        }
+
+        Ok(na)
+    }
+
+    fn __len__(&self) -> usize {
+        self.residuenumbers.len()
+    }
+}
+
+impl NucleicAcid {
+
+    fn with_capacity(n: usize) -> NucleicAcid {
+        NucleicAcid {
+            chains: Vec::with_capacity(n),
+            residuenames: Vec::with_capacity(n),
+            residuenumbers: Vec::with_capacity(n),
+            alpha: Vec::with_capacity(n),
+            beta: Vec::with_capacity(n),
+            gamma: Vec::with_capacity(n),
+            delta: Vec::with_capacity(n),
+            epsilon: Vec::with_capacity(n),
+            zeta: Vec::with_capacity(n),
+            chi: Vec::with_capacity(n),
+            glycosidic: Vec::with_capacity(n),
+            amplitude: Vec::with_capacity(n),
+            phase_angle: Vec::with_capacity(n),
+            sugar_conformer: Vec::with_capacity(n),
+        }
+    }
+}
+
+// The glycosidic atoms are selected by the base : purines carry an N9, pyrimidines bond through N1
+// Pseudouridine is a C-nucleoside, bonded through C5
+fn glycosidic_torsion(residue: &Residue) -> Option<f64> {
+
+    let this = Some(residue);
+    let (base_atom, next_atom) = match residue.name.as_str() {
+        "PSU" => ("C5", "C4"),
+        _ if residue.has("N9") => ("N9", "C4"),
+        _ => ("N1", "C2"),
+    };
+
+    Residue::dihedral([(this, "O4'"), (this, "C1'"), (this, base_atom), (this, next_atom)])
+}
+
+
+#[cfg(test)]
+mod test_nucleicacid {
+
+    use super::*;
+
+    // Two linked residues in chain A, a third one in chain B
+    // The O3' of residue 2 lies within bonding distance of the P of residue 3, so only the chain ID separates them
+    fn dinucleotide() -> Pdb {
+
+        let atoms: Vec<(&str, &str, i32, [f64;3])> = vec![
+            ("A", "O5'", 1, [0., 0., 0.]),
+            ("A", "C5'", 1, [1.5, 0., 0.]),
+            ("A", "C4'", 1, [2., 1.4, 0.]),
+            ("A", "C3'", 1, [3.5, 1.4, 0.5]),
+            ("A", "O3'", 1, [4., 2.8, 0.5]),
+            ("A", "P",   2, [5.5, 2.8, 1.]),
+            ("A", "O5'", 2, [6., 4.2, 1.]),
+            ("A", "C5'", 2, [7.5, 4.2, 1.5]),
+            ("A", "C4'", 2, [8., 5.6, 1.5]),
+            ("A", "C3'", 2, [9.5, 5.6, 2.]),
+            ("A", "O3'", 2, [10., 7., 2.]),
+            ("B", "P",   3, [11.5, 7., 2.5]),
+            ("B", "O5'", 3, [12., 8.4, 2.5]),
+            ("B", "C5'", 3, [13.5, 8.4, 3.]),
+            ("B", "C4'", 3, [14., 9.8, 3.]),
+        ];
+
+        Pdb {
+            filename: "dinucleotide.pdb".to_string(),
+            data: String::new(),
+            hetatm: vec![false; atoms.len()],
+            atomnames: atoms.iter().map(|a| a.1.to_string()).collect(),
+            elements: atoms.iter().map(|a| a.1[..1].to_string()).collect(),
+            residuenames: vec!["DA".to_string(); atoms.len()],
+            residuenumbers: atoms.iter().map(|a| a.2).collect(),
+            chains: atoms.iter().map(|a| a.0.to_string()).collect(),
+            occupancies: vec![1.; atoms.len()],
+            bfactors: vec![0.; atoms.len()],
+            coordinates: atoms.iter().map(|a| a.3).collect(),
+        }
+    }
+
+    #[test]
+    fn torsions_across_residues() {
+        let na = NucleicAcid::new(&dinucleotide()).unwrap();
+
+        assert_eq!(na.residuenumbers, vec![1, 2, 3]);
+        // 5'-terminal residue has no alpha, and no beta without its phosphorus
+        assert!(na.alpha[0].is_none() && na.beta[0].is_none());
+        assert!(na.gamma[0].is_some() && na.epsilon[0].is_some() && na.zeta[0].is_some());
+        assert!(na.alpha[1].is_some() && na.beta[1].is_some());
+        // the chain break between residue 2 and 3
+        assert!(na.epsilon[1].is_none() && na.zeta[1].is_none() && na.alpha[2].is_none());
+        // no sugar ring, no pucker
+        assert!(na.chi.iter().chain(na.amplitude.iter()).all(|v| v.is_none()));
+
+        // in the same chain, residue 2 and 3 are linked
+        let mut pdb = dinucleotide();
+        pdb.chains = vec!["A".to_string(); pdb.chains.len()];
+        let na = NucleicAcid::new(&pdb).unwrap();
+        assert!(na.epsilon[1].is_some() && na.zeta[1].is_some() && na.alpha[2].is_some());
+    }
+}
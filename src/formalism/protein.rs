@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::errors::AtomNotFoundError;
+use crate::formalism::{
+    moleculefile::Pdb,
+    residues::{split_residues, Residue},
+};
+
+// The C(i-1) - N(i) peptide bond is 1.33 Angstrom. Residues further apart are not linked (chain break)
+const MAX_LINKAGE_BONDLENGTH: f64 = 2.0;
+
+// A peptide bond is cis when its omega lies within 30 degrees of 0
+const CIS_OMEGA: f64 = 30.;
+
+type SideChain = &'static [[&'static str; 4]];
+
+// The side-chain dihedrals chi1..chi4, by their standard atom names
+fn side_chain(residuename: &str) -> SideChain {
+
+    const CHI1: [&str; 4] = ["N", "CA", "CB", "CG"];
+    const CHI2: [&str; 4] = ["CA", "CB", "CG", "CD"];
+
+    match residuename {
+        "ARG" => &[CHI1, CHI2, ["CB", "CG", "CD", "NE"], ["CG", "CD", "NE", "CZ"]],
+        "ASN" | "ASP" => &[CHI1, ["CA", "CB", "CG", "OD1"]],
+        "CYS" | "CYX" => &[["N", "CA", "CB", "SG"]],
+        "GLN" | "GLU" => &[CHI1, CHI2, ["CB", "CG", "CD", "OE1"]],
+        "HIS" | "HID" | "HIE" | "HIP" => &[CHI1, ["CA", "CB", "CG", "ND1"]],
+        "ILE" => &[["N", "CA", "CB", "CG1"], ["CA", "CB", "CG1", "CD1"]],
+        "LEU" | "PHE" | "TRP" | "TYR" => &[CHI1, ["CA", "CB", "CG", "CD1"]],
+        "LYS" => &[CHI1, CHI2, ["CB", "CG", "CD", "CE"], ["CG", "CD", "CE", "NZ"]],
+        "MET" => &[CHI1, ["CA", "CB", "CG", "SD"], ["CB", "CG", "SD", "CE"]],
+        "MSE" => &[CHI1, ["CA", "CB", "CG", "SE"], ["CB", "CG", "SE", "CE"]],
+        "PRO" => &[CHI1, CHI2],
+        "SER" => &[["N", "CA", "CB", "OG"]],
+        "THR" => &[["N", "CA", "CB", "OG1"]],
+        "VAL" => &[["N", "CA", "CB", "CG1"]],
+        _ => &[],
+    }
+}
+
+/// The Protein struct holds the backbone and side-chain dihedrals of every amino acid of a protein.
+/// Every field holds one value per residue.
+///
+/// phi   : C(i-1) - N - CA - C
+/// psi   : N - CA - C - N(i+1)
+/// omega : CA(i-1) - C(i-1) - N - CA, the peptide bond preceding the residue
+/// chi1..chi4 : side-chain dihedrals, by the standard atom names of the residue (e.g. N - CA - CB - OG for SER)
+///
+/// Dihedrals are in degrees [-180. -> 180.]. A dihedral is None when one of its atoms is missing
+/// (e.g. phi of the N-terminal residue, chi1 of glycine), or when it crosses a chain break.
+/// `cis` flags the peptide bond preceding the residue : |omega| < 30
+#[pyclass(get_all)]
+pub struct Protein {
+    pub chains: Vec<String>,
+    pub residuenames: Vec<String>,
+    pub residuenumbers: Vec<i32>,
+    pub phi: Vec<Option<f64>>,
+    pub psi: Vec<Option<f64>>,
+    pub omega: Vec<Option<f64>>,
+    pub cis: Vec<Option<bool>>,
+    pub chi1: Vec<Option<f64>>,
+    pub chi2: Vec<Option<f64>>,
+    pub chi3: Vec<Option<f64>>,
+    pub chi4: Vec<Option<f64>>,
+}
+
+#[pymethods]
+impl Protein {
+
+    // Residues without a backbone (waters, ions, ligands, nucleotides) are left out
+    #[new]
+    fn new(pdb: &Pdb) -> PyResult<Protein> {
+
+        let residues: Vec<Residue> = split_residues(pdb)
+            .into_iter()
+            .filter(|r| r.has("N") && r.has("CA") && r.has("C"))
+            .collect();
+
+        if residues.is_empty() {
+            return Err(AtomNotFoundError::new_err(format!("Could not find any amino acid (N, CA and C atomnames) in {}.", pdb.filename)))
+        };
+
+        let mut protein = Protein::with_capacity(residues.len());
+
+        for (i, residue) in residues.iter().enumerate() {
+
+            let previous = match i.checked_sub(1).map(|j| &residues[j]) {
+                Some(p) if p.is_linked(residue, "C", "N", MAX_LINKAGE_BONDLENGTH) => Some(p),
+                _ => None,
+            };
+            let next = match residues.get(i + 1) {
+                Some(n) if residue.is_linked(n, "C", "N", MAX_LINKAGE_BONDLENGTH) => Some(n),
+                _ => None,
+            };
+            let this = Some(residue);
+
+            protein.chains.push(residue.chain.clone());
+            protein.residuenames.push(residue.name.clone());
+            protein.residuenumbers.push(residue.number);
+
+            protein.phi.push(Residue::dihedral([(previous, "C"), (this, "N"), (this, "CA"), (this, "C")]));
+            protein.psi.push(Residue::dihedral([(this, "N"), (this, "CA"), (this, "C"), (next, "N")]));
+
+            let omega = Residue::dihedral([(previous, "CA"), (previous, "C"), (this, "N"), (this, "CA")]);
+            protein.omega.push(omega);
+            protein.cis.push(omega.map(|w| w.abs() < CIS_OMEGA));
+
+            let chis = side_chain(&residue.name);
+            for (n, chi) in [&mut protein.chi1, &mut protein.chi2, &mut protein.chi3, &mut protein.chi4].into_iter().enumerate() {
+                chi.push(chis.get(n).and_then(|atoms| Residue::dihedral(atoms.map(|a| (this, a)))));
+            }
+        }
+
+        Ok(protein)
+    }
+
+    fn __len__(&self) -> usize {
+        self.residuenumbers.len()
+    }
+
+    // The (phi, psi) of every residue where both are defined, keyed by (chain, residue number)
+    fn phi_psi(&self) -> HashMap<(String, i32), (f64, f64)> {
+
+        self.chains.iter()
+            .zip(self.residuenumbers.iter())
+            .zip(self.phi.iter().zip(self.psi.iter()))
+            .filter_map(|((chain, number), (phi, psi))| Some(((chain.clone(), *number), ((*phi)?, (*psi)?))))
+            .collect()
+    }
+}
+
+impl Protein {
+
+    fn with_capacity(n: usize) -> Protein {
+        Protein {
+            chains: Vec::with_capacity(n),
+            residuenames: Vec::with_capacity(n),
+            residuenumbers: Vec::with_capacity(n),
+            phi: Vec::with_capacity(n),
+            psi: Vec::with_capacity(n),
+            omega: Vec::with_capacity(n),
+            cis: Vec::with_capacity(n),
+            chi1: Vec::with_capacity(n),
+            chi2: Vec::with_capacity(n),
+            chi3: Vec::with_capacity(n),
+            chi4: Vec::with_capacity(n),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::formalism::moleculefile::Pdb;
+use crate::geometry::molecule_ops::{bondlength, dihedral};
+
+/// A residue of a Pdb : a consecutive run of atoms with the same chain and residue number
+/// Atom names are normalised to the current nomenclature, with `*` written as `'` (C1* -> C1')
+pub struct Residue {
+    pub chain: String,
+    pub name: String,
+    pub number: i32,
+    atoms: HashMap<String, [f64;3]>,
+}
+
+impl Residue {
+
+    // The first atom with this name. Alternate locations after the first are ignored
+    pub fn atom(&self, atomname: &str) -> Option<[f64;3]> {
+        self.atoms.get(atomname).copied()
+    }
+
+    pub fn has(&self, atomname: &str) -> bool {
+        self.atoms.contains_key(atomname)
+    }
+
+    // Dihedral over four atoms, which may be spread out over neighbouring residues
+    // None when any of the atoms is missing
+    pub fn dihedral(atoms: [(Option<&Residue>, &str); 4]) -> Option<f64> {
+
+        let mut p = [[0.;3]; 4];
+        for (coordinate, (residue, atomname)) in p.iter_mut().zip(atoms) {
+            *coordinate = residue?.atom(atomname)?;
+        }
+
+        Some(dihedral(p[0], p[1], p[2], p[3]))
+    }
+
+    // Residues are linked when they lie in the same chain and `from` of self is bonded to `to` of the next residue
+    // Guards against chain breaks, where the residues follow each other in the file but are not bonded
+    pub fn is_linked(&self, next: &Residue, from: &str, to: &str, max_bondlength: f64) -> bool {
+
+        match (self.atom(from), next.atom(to)) {
+            (Some(a), Some(b)) => self.chain == next.chain && bondlength(a, b) <= max_bondlength,
+            _ => false,
+        }
+    }
+}
+
+/// Group the atoms of a Pdb by residue, in the order of the file
+pub fn split_residues(pdb: &Pdb) -> Vec<Residue> {
+
+    let mut residues: Vec<Residue> = vec![];
+
+    for (i, atomname) in pdb.atomnames.iter().enumerate() {
+
+        let chain = pdb.chains.get(i).cloned().unwrap_or_default();
+        let number = pdb.residuenumbers.get(i).copied().unwrap_or_default();
+
+        let is_new = match residues.last() {
+            Some(r) => r.chain != chain || r.number != number,
+            None => true,
+        };
+
+        if is_new {
+            residues.push(Residue {
+                chain,
+                name: pdb.residuenames.get(i).cloned().unwrap_or_default(),
+                number,
+                atoms: HashMap::new(),
+            });
+        };
+
+        residues.last_mut().unwrap().atoms
+            .entry(atomname.replace('*', "'"))
+            .or_insert(pdb.coordinates[i]);
+    }
+
+    residues
+}
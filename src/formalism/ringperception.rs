@@ -0,0 +1,451 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use pyo3::{pyfunction, PyResult};
+
+use crate::errors::{FileFormatError, OutOfRangeError};
+use crate::formalism::moleculefile::{element_from_atomname, Pdb};
+use crate::geometry::molecule_ops::bondlength;
+
+// Added to the sum of the covalent radii of two atoms, to decide whether they are bonded
+const BOND_TOLERANCE: f64 = 0.45;
+// Anything closer than this is an overlapping atom (e.g. alternate locations), not a bond
+const MIN_BONDLENGTH: f64 = 0.40;
+
+/// Covalent radii (Å), from Cordero et al., Dalton Trans., 2008, 2832-2838
+/// Elements not found in the table are given a generous radius, typical of metals
+pub fn covalent_radius(element: &str) -> f64 {
+    match element.to_ascii_uppercase().as_str() {
+        "H" | "D" => 0.31,
+        "B" => 0.84,
+        "C" => 0.76,
+        "N" => 0.71,
+        "O" => 0.66,
+        "F" => 0.57,
+        "SI" => 1.11,
+        "P" => 1.07,
+        "S" => 1.05,
+        "CL" => 1.02,
+        "SE" => 1.20,
+        "BR" => 1.20,
+        "I" => 1.39,
+        _ => 1.50,
+    }
+}
+
+// Hydrogens never partake in a ring, so they are left out of the molecular graph
+fn is_hydrogen(element: &str) -> bool {
+    matches!(element.to_ascii_uppercase().as_str(), "H" | "D")
+}
+
+/// Priority of the heteroatoms to start numbering a ring from, following the Hantzsch-Widman
+/// nomenclature : O > S > Se > Te > N > P > As > Si > B
+/// Carbon (or any other element) has the lowest priority
+fn heteroatom_priority(element: &str) -> usize {
+    match element.to_ascii_uppercase().as_str() {
+        "O" => 0,
+        "S" => 1,
+        "SE" => 2,
+        "TE" => 3,
+        "N" => 4,
+        "P" => 5,
+        "AS" => 6,
+        "SI" => 7,
+        "B" => 8,
+        _ => 9,
+    }
+}
+
+
+
+/// Infer the covalent bonds of the molecule.
+/// If the pdb contains CONECT records, the atoms that are listed in them are bonded accordingly.
+/// All other atoms are bonded by distance : d(i,j) <= r_i + r_j + 0.45 Å
+/// Hydrogens are left out. Returns the (i, j) atom indices, with i < j
+#[pyfunction]
+pub fn infer_bonds(pdb: &Pdb) -> PyResult<Vec<(usize, usize)>> {
+    perceive_bonds(pdb)
+}
+
+/// Find the smallest set of smallest rings (SSSR) of the molecule, up to `max_ring_size` atoms
+/// Every ring is returned as a list of atom indices, ordered cyclically.
+/// A ring starts at its heteroatom (O > S > Se > Te > N > P > As > Si > B), else at the atom
+/// whose name sorts first, and proceeds towards the neighbour whose name sorts first.
+/// A furanose then reads O4' -> C1' -> C2' -> C3' -> C4'
+#[pyfunction]
+#[pyo3(signature = (pdb, max_ring_size=8))]
+pub fn find_rings(pdb: &Pdb, max_ring_size: usize) -> PyResult<Vec<Vec<usize>>> {
+    perceive_rings(pdb, max_ring_size)
+}
+
+
+
+// Bonds, SSSR and cyclic ordering of the rings in one go
+pub fn perceive_rings(pdb: &Pdb, max_ring_size: usize) -> PyResult<Vec<Vec<usize>>> {
+
+    if max_ring_size < 3 {
+        return Err(OutOfRangeError::new_err(format!("max_ring_size should at least be 3. Got {}.", max_ring_size)))
+    };
+
+    let bonds = perceive_bonds(pdb)?;
+    let rings = smallest_set_of_smallest_rings(pdb.atomnames.len(), &bonds, max_ring_size);
+    let elements = elements_of(pdb);
+
+    Ok(rings.iter()
+            .map(|ring| order_ring(ring, &pdb.atomnames, &elements))
+            .collect())
+}
+
+// Every ring, identified by its atomnames in cyclic order, paired with a value (coordinates, puckering parameters, ...)
+pub type NamedRings<T> = Vec<(Vec<String>, T)>;
+
+// Every ring of `ring_size` atoms in the pdb, as its atomnames and its coordinates in cyclic order
+// Used in the self.from_rings() methods of the formalisms
+pub fn rings_of_size(pdb: &Pdb, ring_size: usize) -> PyResult<NamedRings<Vec<[f64;3]>>> {
+
+    Ok(perceive_rings(pdb, ring_size)?
+        .into_iter()
+        .filter(|ring| ring.len() == ring_size)
+        .map(|ring| (
+            ring.iter().map(|i| pdb.atomnames[*i].clone()).collect(),
+            ring.iter().map(|i| pdb.coordinates[*i]).collect(),
+        ))
+        .collect())
+}
+
+// Elements are only absent when the Pdb has not been parsed from a file, so they are guessed from the atomnames
+fn elements_of(pdb: &Pdb) -> Vec<String> {
+    if pdb.elements.len() == pdb.atomnames.len() {
+        pdb.elements.clone()
+    } else {
+        pdb.atomnames.iter().map(|name| element_from_atomname(name)).collect()
+    }
+}
+
+fn perceive_bonds(pdb: &Pdb) -> PyResult<Vec<(usize, usize)>> {
+
+    let n_atoms = pdb.coordinates.len();
+    let elements = elements_of(pdb);
+
+    let mut bonds: HashSet<(usize, usize)> = HashSet::new();
+    let mut in_conect: Vec<bool> = vec![false; n_atoms];
+
+    for (i, j) in bonds_by_conect(pdb, n_atoms)? {
+        in_conect[i] = true;
+        in_conect[j] = true;
+        bonds.insert((i.min(j), i.max(j)));
+    }
+
+    let skip: Vec<bool> = (0..n_atoms).map(|i| in_conect[i] || is_hydrogen(&elements[i])).collect();
+    bonds.extend(bonds_by_distance(&pdb.coordinates, &elements, &skip));
+
+    let mut bonds: Vec<(usize, usize)> = bonds.into_iter().collect();
+    bonds.sort_unstable();
+
+    Ok(bonds)
+}
+
+// Read the CONECT records from the file contents. The serial numbers of the ATOM/HETATM records
+// are mapped onto the indices of the parsed atoms
+// Returns no bonds when the Pdb does not hold the file contents (e.g. after parse_by_monomers())
+pub fn bonds_by_conect(pdb: &Pdb, n_atoms: usize) -> PyResult<Vec<(usize, usize)>> {
+
+    let mut serials: HashMap<i64, usize> = HashMap::new();
+    let mut conect_lines: Vec<(usize, &str)> = vec![];
+    let mut idx: usize = 0;
+
+    for (i, line) in pdb.data.lines().enumerate() {
+        if line.starts_with("ATOM") || line.starts_with("HETATM") {
+            if let Some(Ok(serial)) = line.get(6..11).map(|s| s.trim().parse::<i64>()) {
+                serials.insert(serial, idx);
+            };
+            idx += 1;
+        } else if line.starts_with("CONECT") {
+            conect_lines.push((i + 1, line));
+        }
+    }
+
+    // The contents do not belong to these atoms
+    if idx != n_atoms {
+        return Ok(vec![])
+    };
+
+    let mut bonds: Vec<(usize, usize)> = vec![];
+
+    for (lineno, line) in conect_lines {
+        let mut fields: Vec<i64> = Vec::with_capacity(5);
+
+        for columns in [6..11, 11..16, 16..21, 21..26, 26..31] {
+            match line.get(columns).map(|s| s.trim()) {
+                Some("") | None => continue,
+                Some(s) => match s.parse::<i64>() {
+                    Ok(a) => fields.push(a),
+                    Err(e) => return Err(FileFormatError::new_err(
+                            format!("Cannot parse CONECT serial number : {}. At line {} of {}\n{}", e, lineno, &pdb.filename, line)
+                            ))
+                }
+            }
+        }
+
+        // Serials that point to atoms outside of the parsed records are ignored
+        if let Some(i) = fields.first().and_then(|s| serials.get(s)) {
+            for j in fields[1..].iter().filter_map(|s| serials.get(s)) {
+                if i != j { bonds.push((*i, *j)) }
+            }
+        }
+    }
+
+    Ok(bonds)
+}
+
+// Bond atoms by their interatomic distance. The space is partitioned in cubic cells, the size of
+// the largest possible bondlength, so only atoms in neighbouring cells need to be compared
+fn bonds_by_distance(coordinates: &[[f64;3]], elements: &[String], skip: &[bool]) -> Vec<(usize, usize)> {
+
+    let radii: Vec<f64> = elements.iter().map(|e| covalent_radius(e)).collect();
+    let cellsize: f64 = 2. * radii.iter().cloned().fold(0., f64::max) + BOND_TOLERANCE;
+
+    let cell_of = |c: &[f64;3]| -> [i64;3] { c.map(|x| (x / cellsize).floor() as i64) };
+
+    let mut cells: HashMap<[i64;3], Vec<usize>> = HashMap::new();
+    for (i, c) in coordinates.iter().enumerate() {
+        if !skip[i] {
+            cells.entry(cell_of(c)).or_default().push(i);
+        }
+    }
+
+    let mut bonds: Vec<(usize, usize)> = vec![];
+
+    for (i, c) in coordinates.iter().enumerate() {
+        if skip[i] { continue };
+
+        let cell = cell_of(c);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(neighbours) = cells.get(&[cell[0] + dx, cell[1] + dy, cell[2] + dz]) else { continue };
+
+                    for j in neighbours.iter().filter(|j| **j > i) {
+                        let d = bondlength(*c, coordinates[*j]);
+                        if d > MIN_BONDLENGTH && d <= radii[i] + radii[*j] + BOND_TOLERANCE {
+                            bonds.push((i, *j))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    bonds
+}
+
+
+
+/// Find the smallest set of smallest rings, up to `max_ring_size` atoms, of a molecular graph.
+///
+/// Candidate rings are generated as in Horton's algorithm : for every root atom and every bond
+/// (x, y), the ring consists of the shortest paths root -> x and y -> root, plus the bond itself.
+/// The candidates are then taken from small to large, and only kept if they are linearly
+/// independent of the rings kept so far (Gaussian elimination over GF(2) on the bonds of a ring).
+pub fn smallest_set_of_smallest_rings(n_atoms: usize, bonds: &[(usize, usize)], max_ring_size: usize) -> Vec<Vec<usize>> {
+
+    let mut adjacency: Vec<Vec<usize>> = vec![vec![]; n_atoms];
+    let mut edge_index: HashMap<(usize, usize), usize> = HashMap::new();
+    for (e, (i, j)) in bonds.iter().enumerate() {
+        adjacency[*i].push(*j);
+        adjacency[*j].push(*i);
+        edge_index.insert((*i.min(j), *i.max(j)), e);
+    }
+
+    // Peel off the atoms that can never be part of a ring (degree < 2), until none are left
+    let mut degree: Vec<usize> = adjacency.iter().map(|a| a.len()).collect();
+    let mut removed: Vec<bool> = vec![false; n_atoms];
+    let mut queue: VecDeque<usize> = (0..n_atoms).filter(|i| degree[*i] < 2).collect();
+    while let Some(i) = queue.pop_front() {
+        if removed[i] { continue };
+        removed[i] = true;
+        for j in adjacency[i].iter() {
+            if !removed[*j] {
+                degree[*j] -= 1;
+                if degree[*j] < 2 { queue.push_back(*j) }
+            }
+        }
+    }
+
+    // Generate the candidates
+    let max_depth = max_ring_size / 2;
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    let mut candidates: Vec<(Vec<usize>, Vec<usize>)> = vec![]; // (ordered atoms, sorted edges)
+
+    let mut parent: Vec<usize> = vec![usize::MAX; n_atoms];
+    let mut depth: Vec<usize> = vec![usize::MAX; n_atoms];
+
+    for root in (0..n_atoms).filter(|i| !removed[*i]) {
+
+        // Breadth-first search, limited in depth
+        let mut visited: Vec<usize> = vec![root];
+        let mut queue: VecDeque<usize> = VecDeque::from([root]);
+        depth[root] = 0;
+        parent[root] = root;
+
+        while let Some(i) = queue.pop_front() {
+            if depth[i] >= max_depth { continue };
+            for j in adjacency[i].iter().filter(|j| !removed[**j]) {
+                if depth[*j] == usize::MAX {
+                    depth[*j] = depth[i] + 1;
+                    parent[*j] = i;
+                    visited.push(*j);
+                    queue.push_back(*j);
+                }
+            }
+        }
+
+        let path_to_root = |mut i: usize| -> Vec<usize> {
+            let mut path = vec![i];
+            while i != root {
+                i = parent[i];
+                path.push(i);
+            }
+            path
+        };
+
+        for x in visited.iter() {
+            for y in adjacency[*x].iter().filter(|y| !removed[**y] && **y > *x && depth[**y] != usize::MAX) {
+                // Bonds of the tree do not close a ring
+                if parent[*y] == *x || parent[*x] == *y { continue };
+                if depth[*x] + depth[*y] + 1 > max_ring_size { continue };
+
+                let px = path_to_root(*x); // x -> root
+                let py = path_to_root(*y); // y -> root
+
+                // Both paths may only share the root
+                let shared: HashSet<&usize> = px.iter().collect();
+                if py[..py.len() - 1].iter().any(|a| shared.contains(a)) { continue };
+
+                // root -> ... -> x -> y -> ... (-> root)
+                let ring: Vec<usize> = px.iter().rev().chain(py[..py.len() - 1].iter()).cloned().collect();
+
+                let mut edges: Vec<usize> = (0..ring.len()).map(|k| {
+                    let (a, b) = (ring[k], ring[(k + 1) % ring.len()]);
+                    edge_index[&(a.min(b), a.max(b))]
+                }).collect();
+                edges.sort_unstable();
+
+                if seen.insert(edges.clone()) {
+                    candidates.push((ring, edges));
+                }
+            }
+        }
+
+        // Reset the search for the next root
+        for i in visited {
+            depth[i] = usize::MAX;
+            parent[i] = usize::MAX;
+        }
+    }
+
+    candidates.sort_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.1.cmp(&b.1)));
+
+    // Keep the linearly independent rings, from small to large
+    let mut basis: HashMap<usize, Vec<usize>> = HashMap::new(); // pivot (largest edge) -> edges
+    let mut rings: Vec<Vec<usize>> = vec![];
+
+    for (ring, edges) in candidates {
+        let mut vector = edges;
+
+        while let Some(pivot) = vector.last().copied() {
+            match basis.get(&pivot) {
+                Some(row) => vector = symmetric_difference(&vector, row),
+                None => {
+                    basis.insert(pivot, vector);
+                    rings.push(ring);
+                    break
+                }
+            }
+        }
+    }
+
+    rings.sort_by_key(|ring| *ring.iter().min().unwrap());
+    rings
+}
+
+// XOR of two sorted sets of edges
+fn symmetric_difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+
+    let mut c: Vec<usize> = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => { c.push(a[i]); i += 1 },
+            std::cmp::Ordering::Greater => { c.push(b[j]); j += 1 },
+            std::cmp::Ordering::Equal => { i += 1; j += 1 },
+        }
+    }
+    c.extend_from_slice(&a[i..]);
+    c.extend_from_slice(&b[j..]);
+
+    c
+}
+
+// Rotate and orient the cyclic order of a ring, so it starts at its heteroatom of highest
+// priority (else the atom name that sorts first) and proceeds towards the neighbour whose name
+// sorts first
+fn order_ring(ring: &[usize], atomnames: &[String], elements: &[String]) -> Vec<usize> {
+
+    let size = ring.len();
+    let element = |i: usize| -> &str { &elements[i] };
+
+    let start = (0..size).min_by(|a, b| {
+        heteroatom_priority(element(ring[*a])).cmp(&heteroatom_priority(element(ring[*b])))
+            .then_with(|| atomnames[ring[*a]].cmp(&atomnames[ring[*b]]))
+            .then_with(|| ring[*a].cmp(&ring[*b]))
+    }).unwrap();
+
+    let next = ring[(start + 1) % size];
+    let previous = ring[(start + size - 1) % size];
+    let forward = (&atomnames[next], next) <= (&atomnames[previous], previous);
+
+    (0..size).map(|k| {
+        if forward { ring[(start + k) % size] } else { ring[(start + size - k) % size] }
+    }).collect()
+}
+
+
+#[cfg(test)]
+mod test_ringperception {
+
+    use super::*;
+
+    #[test]
+    pub fn fused_rings() {
+        // Decalin : two six-membered rings sharing the 0-5 bond
+        let bonds = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (0, 5),
+                     (5, 6), (6, 7), (7, 8), (8, 9), (0, 9)];
+        let rings = smallest_set_of_smallest_rings(10, &bonds, 8);
+
+        assert_eq!(rings.len(), 2);
+        assert!(rings.iter().all(|r| r.len() == 6));
+    }
+
+    #[test]
+    pub fn ring_with_substituents() {
+        // Furanose with an exocyclic C5' and a base, which do not belong to any ring
+        let bonds = [(0, 1), (1, 2), (2, 3), (3, 4), (0, 4), (4, 5), (1, 6)];
+        let names: Vec<String> = ["O4'", "C1'", "C2'", "C3'", "C4'", "C5'", "N9"].map(String::from).to_vec();
+        let elements: Vec<String> = ["O", "C", "C", "C", "C", "C", "N"].map(String::from).to_vec();
+
+        let rings = smallest_set_of_smallest_rings(7, &bonds, 8);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(order_ring(&rings[0], &names, &elements), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    pub fn cubane() {
+        // Cubane has six four-membered faces, of which five are independent
+        let bonds = [(0, 1), (1, 2), (2, 3), (0, 3), (4, 5), (5, 6), (6, 7), (4, 7),
+                     (0, 4), (1, 5), (2, 6), (3, 7)];
+        let rings = smallest_set_of_smallest_rings(8, &bonds, 8);
+
+        assert_eq!(rings.len(), 5);
+        assert!(rings.iter().all(|r| r.len() == 4));
+    }
+}
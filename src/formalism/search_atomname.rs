@@ -23,3 +23,28 @@ impl FindString for Vec<String> {
         Err(())
     }
 }
+
+
+// Implement a map to select the coordinates at the queried indices from a Vec<[f64;3]>.
+// If an index is out of bounds, the faulty index is returned.
+pub trait SelectCoordinates {
+    fn at_indices(&self, indices: &[usize]) -> Result<Vec<[f64;3]>, usize> ;
+}
+
+// Implement this on Vec<[f64;3]> . Allows the match while looping
+impl SelectCoordinates for Vec<[f64;3]> {
+
+    fn at_indices(&self, indices: &[usize]) -> Result<Vec<[f64;3]>, usize> {
+
+        let mut molarray: Vec<[f64;3]> = Vec::with_capacity(indices.len());
+
+        for idx in indices {
+            match self.get(*idx) {
+                Some(c) => molarray.push(*c),
+                None => return Err(*idx)
+            }
+        };
+
+        Ok(molarray)
+    }
+}
@@ -1,15 +1,21 @@
-use pyo3::{pyclass,pymethods};
+use ndarray::Array2;
+use numpy::{IntoPyArray, PyReadonlyArray3};
+use pyo3::{pyclass, pymethods, PyResult, Python};
 
+use crate::errors::{AtomNotFoundError, OutOfRangeError, RingSizeError};
 use crate::geometry::molecule_ops::{dihedral, bondangle};
 use crate::formalism::{
     moleculefile::Pdb,
-    search_atomname::FindString
+    search_atomname::{FindString, SelectCoordinates},
+    ringperception::{rings_of_size, NamedRings},
+    trajectory::{map_frames, FramesMatrix},
 };
 
 #[pyclass(get_all)]
 pub struct SP { }
 
 #[pymethods]
+#[allow(clippy::wrong_self_convention)]
 impl SP {
 
     #[new]
@@ -19,26 +25,57 @@ impl SP {
     }
 
     // Calculate Cremer-Pople formalism by prompted indices
-    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> ([f64;3], [f64;3]) {
+    fn from_indices(&self, coordinates : Vec<[f64; 3]>, indices: Vec<usize>) -> PyResult<([f64;3], [f64;3])> {
         
-        let mut molarray: Vec<[f64; 3]> = vec![];
+        if indices.len() != 6 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 6, has been queried. Expected 6 elements.", indices.len())))
+        };
+
+        let molarray: Vec<[f64; 3]> = match coordinates.at_indices(&indices) {
+            Ok(a) => a,
+            Err(idx) => return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} coordinates.", idx, coordinates.len())))
+        };
+
+        Ok(strauss_pickett(molarray))
+    }
+
+    // Calculate Strauss-Pickett formalism on every frame of an (n_frames, n_atoms, 3) array
+    // Returns the alphas and the betas as two (n_frames, 3) arrays
+    fn from_trajectory<'py>(&self, py: Python<'py>, coordinates: PyReadonlyArray3<'py, f64>, indices: Vec<usize>) 
+        -> PyResult<(FramesMatrix<'py>, FramesMatrix<'py>)> {
 
-        for idx in indices {
-            molarray.push(coordinates[idx])
+        let frames = map_frames(py, &coordinates, &indices, 6, strauss_pickett)?;
+
+        let mut alphas = Array2::<f64>::zeros((frames.len(), 3));
+        let mut betas = Array2::<f64>::zeros((frames.len(), 3));
+
+        for (i, (alpha, beta)) in frames.iter().enumerate() {
+            for j in 0..3 {
+                alphas[[i, j]] = alpha[j];
+                betas[[i, j]] = beta[j];
+            }
         }
 
-        strauss_pickett(molarray)
+        Ok((alphas.into_pyarray_bound(py), betas.into_pyarray_bound(py)))
+    }
+
+    // Find every six-membered ring in the pdb and calculate its Strauss-Pickett formalism
+    fn from_rings(&self, pdb: &Pdb) -> PyResult<NamedRings<([f64;3], [f64;3])>> {
+
+        Ok(rings_of_size(pdb, 6)?
+            .into_iter()
+            .map(|(names, molarray)| (names, strauss_pickett(molarray)))
+            .collect())
     }
     
     // Find indices of atomnames and pass them to self.cp_from_indices()
-    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> ([f64;3], [f64;3])  {
+    fn from_atomnames(&self, pdb : &Pdb, query_names: Vec<String>) -> PyResult<([f64;3], [f64;3])>  {
 
         // Make empty vec :
         let mut indices: Vec<usize> = Vec::with_capacity(6);
 
-        let _ = match query_names.len() {
-            6 => 6,
-           _ => panic!("An amount, not equal to 5, has been queried. Expected 5 elements.")
+        if query_names.len() != 6 {
+            return Err(RingSizeError::new_err(format!("An amount of {}, not equal to 6, has been queried. Expected 6 elements.", query_names.len())))
         };
 
 
@@ -46,7 +83,7 @@ impl SP {
         for name in query_names.iter() {
             match pdb.atomnames.at_position(name) {
                 Ok(a) => indices.push(a),
-                Err(()) => panic!("Could not find \"{}\" atomname in the queried pdb.", name)
+                Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in the queried pdb.", name)))
             }
         }
 
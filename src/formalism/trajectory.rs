@@ -0,0 +1,47 @@
+use numpy::{PyArray1, PyArray2, PyReadonlyArray3};
+use pyo3::{Bound, PyResult, Python};
+use rayon::prelude::*;
+
+use crate::errors::{OutOfRangeError, RingSizeError};
+
+// Arrays returned to Python, holding one value (n_frames,) or one row (n_frames, 3) per frame
+pub type FramesArray<'py> = Bound<'py, PyArray1<f64>>;
+pub type FramesMatrix<'py> = Bound<'py, PyArray2<f64>>;
+
+/// Apply a puckering formalism on every frame of a trajectory, in parallel across the frames.
+///
+/// `coordinates` is an array of shape (n_frames, n_atoms, 3).
+/// Every frame is sliced by the `indices` of the ring atoms, before it is passed to `formalism`
+///
+/// The GIL is released while the frames are being computed
+pub fn map_frames<T, F>(py: Python<'_>, coordinates: &PyReadonlyArray3<f64>, indices: &[usize], ring_size: usize, formalism: F) -> PyResult<Vec<T>>
+where
+    T: Send,
+    F: Fn(Vec<[f64;3]>) -> T + Send + Sync,
+{
+    if indices.len() != ring_size {
+        return Err(RingSizeError::new_err(format!("An amount of {}, not equal to {}, has been queried. Expected {} elements.", indices.len(), ring_size, ring_size)))
+    };
+
+    let array = coordinates.as_array();
+    let (n_frames, n_atoms, n_dims) = array.dim();
+
+    if n_dims != 3 {
+        return Err(OutOfRangeError::new_err(format!("Expected an array of shape (n_frames, n_atoms, 3). Got ({}, {}, {}).", n_frames, n_atoms, n_dims)))
+    };
+
+    if let Some(idx) = indices.iter().find(|idx| **idx >= n_atoms) {
+        return Err(OutOfRangeError::new_err(format!("Index {} is out of range for {} atoms per frame.", idx, n_atoms)))
+    };
+
+    Ok(py.allow_threads(|| {
+        (0..n_frames).into_par_iter()
+                     .map(|f| {
+                         let molarray: Vec<[f64;3]> = indices.iter()
+                                                             .map(|i| [array[[f, *i, 0]], array[[f, *i, 1]], array[[f, *i, 2]]])
+                                                             .collect();
+                         formalism(molarray)
+                     })
+                     .collect()
+    }))
+}
@@ -130,6 +130,7 @@ pub fn subtract_arr(a : Coordinate, b : Coordinate) -> Coordinate {
 /// Custom trait to extend primitive type :
 /// Make extension trait on the primitive type `RotationMatrix`
 pub trait RotMatrix {
+    #[allow(clippy::new_ret_no_self)] // RotationMatrix is the type alias of Self
     fn new(phi: f64) -> RotationMatrix; 
     fn apply_rotation(&self, p : Coordinate) -> Coordinate; 
     fn apply_rotation_around_g(&self, p : Coordinate, idx: usize) -> f64; 
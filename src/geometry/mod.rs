@@ -4,3 +4,4 @@
 
 pub mod molecule_ops; // Public functions
 pub mod fundamental_ops; // Functions private from the user
+pub mod superposition; // Optimal superposition and RMSD of two sets of coordinates
@@ -0,0 +1,332 @@
+//! OPTIMAL SUPERPOSITION AND RMSD OF TWO SETS OF COORDINATES
+//!
+//! The rotation is found by the quaternion method of Horn (J. Opt. Soc. Am. A, 1987, 4, 629-642),
+//! which gives the same optimal rotation as the Kabsch algorithm without having to correct for reflections.
+//! The quaternion is the eigenvector of the largest eigenvalue of a symmetric 4x4 matrix,
+//! which is diagonalised by Jacobi rotations
+
+use pyo3::{pyclass, pyfunction, pymethods, PyResult};
+use rayon::prelude::*;
+
+use crate::errors::{AtomNotFoundError, OutOfRangeError, PuckepyError};
+use crate::formalism::moleculefile::{element_from_atomname, Pdb};
+use crate::formalism::search_atomname::FindString;
+use crate::geometry::fundamental_ops::{Coordinate, RotationMatrix};
+
+const JACOBI_SWEEPS: usize = 50;
+
+
+/// The optimal superposition of the `mobile` coordinates onto the `reference` coordinates,
+/// which minimises the (weighted) RMSD between them
+/// A superposed coordinate is `rotation` . coordinate + `translation`
+#[pyclass(get_all)]
+pub struct Superposition {
+    pub rotation: RotationMatrix,
+    pub translation: Coordinate,
+    pub rmsd: f64,
+}
+
+#[pymethods]
+impl Superposition {
+
+    #[new]
+    #[pyo3(signature = (mobile, reference, weights=None))]
+    fn new(mobile: Vec<Coordinate>, reference: Vec<Coordinate>, weights: Option<Vec<f64>>) -> PyResult<Self> {
+        let weights = check_weights(mobile.len(), reference.len(), weights)?;
+        Ok(superpose(&mobile, &reference, &weights))
+    }
+
+    /// Superpose the atoms of `mobile` onto the same atoms of `reference`, selected by their atomnames
+    /// The atoms are weighted by their atomic mass if `mass_weighted`
+    #[staticmethod]
+    #[pyo3(signature = (mobile, reference, atomnames, mass_weighted=false))]
+    fn from_atomnames(mobile: &Pdb, reference: &Pdb, atomnames: Vec<String>, mass_weighted: bool) -> PyResult<Self> {
+
+        let (mobile_coordinates, _) = select_atoms(mobile, &atomnames)?;
+        let (reference_coordinates, elements) = select_atoms(reference, &atomnames)?;
+        let weights = match mass_weighted {
+            true => Some(masses(elements)?),
+            false => None,
+        };
+
+        Self::new(mobile_coordinates, reference_coordinates, weights)
+    }
+
+    /// Move any coordinates along the superposition, e.g. every atom of the molecule that was superposed on a subset of its atoms
+    fn apply(&self, coordinates: Vec<Coordinate>) -> Vec<Coordinate> {
+        coordinates.iter().map(|c| self.transform(*c)).collect()
+    }
+}
+
+impl Superposition {
+
+    fn transform(&self, c: Coordinate) -> Coordinate {
+        [0, 1, 2].map(|i| (0..3).map(|j| self.rotation[i][j] * c[j]).sum::<f64>() + self.translation[i])
+    }
+}
+
+
+/// The RMSD between two sets of coordinates, after their optimal superposition if `fit`
+/// Every atom is weighted by its `weights`, e.g. the masses of the atoms
+#[pyfunction]
+#[pyo3(signature = (a, b, weights=None, fit=true))]
+pub fn rmsd(a: Vec<Coordinate>, b: Vec<Coordinate>, weights: Option<Vec<f64>>, fit: bool) -> PyResult<f64> {
+    let weights = check_weights(a.len(), b.len(), weights)?;
+    Ok(match fit {
+        true => superpose(&a, &b, &weights).rmsd,
+        false => deviation(&a, &b, &weights),
+    })
+}
+
+/// The RMSD between every pair of conformers, as a symmetric matrix
+/// Every atom is weighted by its `weights`, e.g. the masses of the atoms
+#[pyfunction]
+#[pyo3(signature = (conformers, weights=None, fit=true))]
+pub fn rmsd_matrix(conformers: Vec<Vec<Coordinate>>, weights: Option<Vec<f64>>, fit: bool) -> PyResult<Vec<Vec<f64>>> {
+
+    let n_atoms = conformers.first().map_or(0, |c| c.len());
+    let weights = check_weights(n_atoms, n_atoms, weights)?;
+    if let Some(c) = conformers.iter().find(|c| c.len() != n_atoms) {
+        return Err(PuckepyError::new_err(format!("A conformer of {} atoms, not equal to {}, has been queried. Expected the same atoms in every conformer.", c.len(), n_atoms)))
+    };
+
+    let upper: Vec<Vec<f64>> = (0..conformers.len()).into_par_iter().map(|i| {
+        (i + 1..conformers.len()).map(|j| match fit {
+            true => superpose(&conformers[i], &conformers[j], &weights).rmsd,
+            false => deviation(&conformers[i], &conformers[j], &weights),
+        }).collect()
+    }).collect();
+
+    let mut matrix = vec![vec![0.; conformers.len()]; conformers.len()];
+    for (i, row) in upper.iter().enumerate() {
+        for (k, value) in row.iter().enumerate() {
+            matrix[i][i + 1 + k] = *value;
+            matrix[i + 1 + k][i] = *value;
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// The standard atomic weights (u) of the elements, to weigh the atoms by their mass
+#[pyfunction]
+pub fn masses(elements: Vec<String>) -> PyResult<Vec<f64>> {
+    elements.iter().map(|e| match atomic_mass(e) {
+        Some(m) => Ok(m),
+        None => Err(PuckepyError::new_err(format!("The mass of element \"{}\" is not known.", e))),
+    }).collect()
+}
+
+
+/// IUPAC standard atomic weights, abridged to the elements of organic molecules and common ions
+fn atomic_mass(element: &str) -> Option<f64> {
+    match element.to_ascii_uppercase().as_str() {
+        "H" => Some(1.008),
+        "D" => Some(2.014),
+        "B" => Some(10.81),
+        "C" => Some(12.011),
+        "N" => Some(14.007),
+        "O" => Some(15.999),
+        "F" => Some(18.998),
+        "NA" => Some(22.990),
+        "MG" => Some(24.305),
+        "SI" => Some(28.085),
+        "P" => Some(30.974),
+        "S" => Some(32.06),
+        "CL" => Some(35.45),
+        "K" => Some(39.098),
+        "CA" => Some(40.078),
+        "MN" => Some(54.938),
+        "FE" => Some(55.845),
+        "ZN" => Some(65.38),
+        "SE" => Some(78.971),
+        "BR" => Some(79.904),
+        "I" => Some(126.904),
+        _ => None,
+    }
+}
+
+// The coordinates and elements of the queried atoms, in the order of the atomnames
+// A missing element is taken from the atomname
+fn select_atoms(pdb: &Pdb, atomnames: &[String]) -> PyResult<(Vec<Coordinate>, Vec<String>)> {
+
+    let mut coordinates = Vec::with_capacity(atomnames.len());
+    let mut elements = Vec::with_capacity(atomnames.len());
+
+    for name in atomnames {
+        match pdb.atomnames.at_position(name) {
+            Ok(i) => {
+                coordinates.push(pdb.coordinates[i]);
+                elements.push(match pdb.elements[i].is_empty() {
+                    true => element_from_atomname(name),
+                    false => pdb.elements[i].clone(),
+                });
+            },
+            Err(()) => return Err(AtomNotFoundError::new_err(format!("Could not find \"{}\" atomname in {}.", name, pdb.filename)))
+        }
+    }
+
+    Ok((coordinates, elements))
+}
+
+// The weight of every atom, which defaults to 1
+fn check_weights(a: usize, b: usize, weights: Option<Vec<f64>>) -> PyResult<Vec<f64>> {
+
+    if a != b {
+        return Err(PuckepyError::new_err(format!("Sets of {} and {} coordinates have been queried. Expected the same amount of atoms.", a, b)))
+    };
+
+    let weights = weights.unwrap_or(vec![1.; a]);
+    if weights.len() != a {
+        return Err(PuckepyError::new_err(format!("An amount of {} weights, not equal to {}, has been queried. Expected a weight for every atom.", weights.len(), a)))
+    };
+
+    if weights.iter().any(|w| w.is_nan() || *w < 0.) || weights.iter().sum::<f64>() <= 0. {
+        return Err(OutOfRangeError::new_err("The weights of the atoms should not be negative, and should not all be zero."))
+    };
+
+    Ok(weights)
+}
+
+fn centroid(coordinates: &[Coordinate], weights: &[f64]) -> Coordinate {
+    let total: f64 = weights.iter().sum();
+    [0, 1, 2].map(|i| coordinates.iter().zip(weights.iter()).map(|(c, w)| c[i] * w).sum::<f64>() / total)
+}
+
+// The weighted RMSD of the coordinates as they are
+fn deviation(a: &[Coordinate], b: &[Coordinate], weights: &[f64]) -> f64 {
+    let squared: f64 = a.iter().zip(b.iter()).zip(weights.iter())
+        .map(|((p, q), w)| w * (0..3).map(|i| (p[i] - q[i]).powi(2)).sum::<f64>())
+        .sum();
+    (squared / weights.iter().sum::<f64>()).sqrt()
+}
+
+fn superpose(mobile: &[Coordinate], reference: &[Coordinate], weights: &[f64]) -> Superposition {
+
+    let center_mobile = centroid(mobile, weights);
+    let center_reference = centroid(reference, weights);
+
+    // The weighted correlation between the centered coordinates, S[a][b] = sum w * x_a * y_b
+    let mut s = [[0.; 3]; 3];
+    for ((x, y), w) in mobile.iter().zip(reference.iter()).zip(weights.iter()) {
+        for a in 0..3 {
+            for b in 0..3 {
+                s[a][b] += w * (x[a] - center_mobile[a]) * (y[b] - center_reference[b])
+            }
+        }
+    }
+
+    // Horn's matrix, whose eigenvector of the largest eigenvalue is the optimal rotation as a unit quaternion
+    let n = [
+        [s[0][0] + s[1][1] + s[2][2], s[1][2] - s[2][1], s[2][0] - s[0][2], s[0][1] - s[1][0]],
+        [s[1][2] - s[2][1], s[0][0] - s[1][1] - s[2][2], s[0][1] + s[1][0], s[2][0] + s[0][2]],
+        [s[2][0] - s[0][2], s[0][1] + s[1][0], -s[0][0] + s[1][1] - s[2][2], s[1][2] + s[2][1]],
+        [s[0][1] - s[1][0], s[2][0] + s[0][2], s[1][2] + s[2][1], -s[0][0] - s[1][1] + s[2][2]],
+    ];
+    let rotation = quaternion_to_rotation(largest_eigenvector(n));
+
+    let rotated_center = [0, 1, 2].map(|i| (0..3).map(|j| rotation[i][j] * center_mobile[j]).sum::<f64>());
+    let translation = [0, 1, 2].map(|i| center_reference[i] - rotated_center[i]);
+
+    let mut superposition = Superposition { rotation, translation, rmsd: 0. };
+    let superposed: Vec<Coordinate> = mobile.iter().map(|c| superposition.transform(*c)).collect();
+    superposition.rmsd = deviation(&superposed, reference, weights);
+    superposition
+}
+
+fn quaternion_to_rotation(q: [f64; 4]) -> RotationMatrix {
+    let [q0, q1, q2, q3] = q;
+    [
+        [(q0 * q0) + (q1 * q1) - (q2 * q2) - (q3 * q3), 2. * ((q1 * q2) - (q0 * q3)), 2. * ((q1 * q3) + (q0 * q2))],
+        [2. * ((q1 * q2) + (q0 * q3)), (q0 * q0) - (q1 * q1) + (q2 * q2) - (q3 * q3), 2. * ((q2 * q3) - (q0 * q1))],
+        [2. * ((q1 * q3) - (q0 * q2)), 2. * ((q2 * q3) + (q0 * q1)), (q0 * q0) - (q1 * q1) - (q2 * q2) + (q3 * q3)],
+    ]
+}
+
+// The eigenvector of the largest eigenvalue of a symmetric matrix, by cyclic Jacobi rotations
+fn largest_eigenvector(mut a: [[f64; 4]; 4]) -> [f64; 4] {
+
+    let mut v = [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal: f64 = (0..4).flat_map(|p| (p + 1..4).map(move |q| (p, q))).map(|(p, q)| a[p][q].powi(2)).sum();
+        if off_diagonal < 1e-30 {
+            break
+        };
+
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if a[p][q] == 0. {
+                    continue
+                };
+
+                // The rotation in the (p, q) plane that zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2. * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta.powi(2) + 1.).sqrt());
+                let c = 1. / (t.powi(2) + 1.).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = (c * akp) - (s * akq);
+                    row[q] = (s * akp) + (c * akq);
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = [0, 1, 2, 3].map(|k| (c * row_p[k]) - (s * row_q[k]));
+                a[q] = [0, 1, 2, 3].map(|k| (s * row_p[k]) + (c * row_q[k]));
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = (c * vkp) - (s * vkq);
+                    row[q] = (s * vkp) + (c * vkq);
+                }
+            }
+        }
+    }
+
+    let largest = (0..4).fold(0, |best, i| if a[i][i] > a[best][best] { i } else { best });
+    [0, 1, 2, 3].map(|k| v[k][largest])
+}
+
+
+#[cfg(test)]
+mod test_superposition {
+
+    use super::*;
+
+    fn ring() -> Vec<Coordinate> {
+        vec![[1.2, 0.3, 0.1], [0.4, 1.4, -0.2], [-0.9, 1.0, 0.3], [-1.1, -0.4, -0.1], [0.1, -1.3, 0.2], [0.3, 0.2, 1.5]]
+    }
+
+    #[test]
+    fn recovers_rotation_and_translation() {
+        // 40 degrees around (1, 2, 2) / 3, then shifted
+        let axis = [1. / 3., 2. / 3., 2. / 3.];
+        let (sin, cos) = 40_f64.to_radians().sin_cos();
+        let half = 20_f64.to_radians();
+        let q = [half.cos(), axis[0] * half.sin(), axis[1] * half.sin(), axis[2] * half.sin()];
+        let rotation = quaternion_to_rotation(q);
+        assert!((rotation[0][0] - (cos + (axis[0].powi(2) * (1. - cos)))).abs() < 1e-12);
+        assert!((rotation[1][0] - ((axis[0] * axis[1] * (1. - cos)) + (axis[2] * sin))).abs() < 1e-12);
+
+        let moved = Superposition { rotation, translation: [3., -1., 2.], rmsd: 0. }.apply(ring());
+        let superposition = superpose(&ring(), &moved, &[1.; 6]);
+
+        assert!(superposition.rmsd < 1e-10);
+        assert!((0..3).all(|i| (0..3).all(|j| (superposition.rotation[i][j] - rotation[i][j]).abs() < 1e-10)));
+    }
+
+    #[test]
+    fn reflection_is_not_a_rotation() {
+        let mirrored: Vec<Coordinate> = ring().iter().map(|c| [c[0], c[1], -c[2]]).collect();
+        assert!(rmsd(ring(), mirrored, None, true).unwrap() > 0.1);
+    }
+
+    #[test]
+    fn symmetric_matrix() {
+        let moved: Vec<Coordinate> = ring().iter().map(|c| [c[0] + 0.1, c[1], c[2] * 1.1]).collect();
+        let matrix = rmsd_matrix(vec![ring(), moved.clone(), ring()], None, false).unwrap();
+        assert_eq!(matrix[0][2], 0.);
+        assert_eq!(matrix[0][1], matrix[1][0]);
+        assert_eq!(matrix[1][2], rmsd(moved, ring(), None, false).unwrap());
+    }
+}
@@ -1,29 +1,70 @@
 use pyo3::prelude::*;
 
+mod errors;
+use errors::{
+    PuckepyError,
+    FileFormatError,
+    AtomNotFoundError,
+    RingSizeError,
+    OutOfRangeError,
+};
+
 mod geometry;
 use geometry::molecule_ops::{
     dihedral,
     bondangle,
     bondlength
 };
+use geometry::superposition::{
+    Superposition,
+    rmsd,
+    rmsd_matrix,
+    masses,
+};
 
 mod conf_sampling;
 use conf_sampling::{
     peptide::{Peptide, PeptideAxes},
-    fivering::{Fivering, FiveringAxes},
+    fourring::Fourring,
+    fivering::{Fivering, FiveringAxes, FiveringPolar},
     sixring::{Sixring, SixringAxes},
+    sevenring::{Sevenring, SevenringAxes},
+    ramachandran::Ramachandran,
+    refinement::Refinement,
+};
+
+mod analysis;
+use analysis::{
+    pes::{SpherePES, TorusPES},
+    fes::{SphereFES, PlaneFES},
+    clustering::{Clusters, kmedoids, dbscan, hierarchical},
+    boltzmann::{Ensemble, boltzmann},
+};
+
+mod statistics;
+use statistics::{
+    circular::{circular_mean, circular_variance, VonMises},
+    spherical::{spherical_mean, spherical_variance, Fisher},
 };
 
 mod formalism;
 use formalism::{
-    cremerpople::{CP5, CP6},
+    cremerpople::{CP4, CP5, CP6, CPN},
     altonasund::AS,
     strausspickett::SP,
     moleculefile::{Pdb, 
+                   Mmcif,
                    Xyz,
                    write_to_pdb,
-                   write_to_xyz
+                   write_to_xyz,
+                   write_pdb,
+                   write_models_to_pdb,
+                   write_frames_to_xyz,
                     },
+    iupac::Conformer,
+    nucleicacid::NucleicAcid,
+    protein::Protein,
+    ringperception::{infer_bonds, find_rings},
 };
 
 /// A Python module implemented in Rust. The name of this function must match
@@ -39,36 +80,89 @@ fn puckepy(m: &Bound<'_, PyModule>) -> PyResult<()> {
 
 fn register_child_modules(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
     //
+    // Add exceptions to the public API
+    let py = parent_module.py();
+    parent_module.add("PuckepyError", py.get_type_bound::<PuckepyError>())?;
+    parent_module.add("FileFormatError", py.get_type_bound::<FileFormatError>())?;
+    parent_module.add("AtomNotFoundError", py.get_type_bound::<AtomNotFoundError>())?;
+    parent_module.add("RingSizeError", py.get_type_bound::<RingSizeError>())?;
+    parent_module.add("OutOfRangeError", py.get_type_bound::<OutOfRangeError>())?;
+
     // Add geometry functions to the public API
     let geom_sub_module = PyModule::new_bound(parent_module.py(), "geometry")?;
     geom_sub_module.add_function(wrap_pyfunction!(dihedral, &geom_sub_module)?)?;
     geom_sub_module.add_function(wrap_pyfunction!(bondangle, &geom_sub_module)?)?;
     geom_sub_module.add_function(wrap_pyfunction!(bondlength, &geom_sub_module)?)?;
+    geom_sub_module.add_function(wrap_pyfunction!(rmsd, &geom_sub_module)?)?;
+    geom_sub_module.add_function(wrap_pyfunction!(rmsd_matrix, &geom_sub_module)?)?;
+    geom_sub_module.add_function(wrap_pyfunction!(masses, &geom_sub_module)?)?;
+    geom_sub_module.add_class::<Superposition>()?;
 
     // Add conformational sampling methods to the public API
     let cs_module = PyModule::new_bound(parent_module.py(), "confsampling")?;
     cs_module.add_class::<Peptide>()?;
     cs_module.add_class::<PeptideAxes>()?;
+    cs_module.add_class::<Fourring>()?;
     cs_module.add_class::<Fivering>()?;
     cs_module.add_class::<FiveringAxes>()?;
+    cs_module.add_class::<FiveringPolar>()?;
     cs_module.add_class::<Sixring>()?;
     cs_module.add_class::<SixringAxes>()?;
+    cs_module.add_class::<Sevenring>()?;
+    cs_module.add_class::<SevenringAxes>()?;
+    cs_module.add_class::<Ramachandran>()?;
+    cs_module.add_class::<Refinement>()?;
 
     // Add formalisms to the public API
     let form_module = PyModule::new_bound(parent_module.py(), "formalism")?;
+    form_module.add_class::<CP4>()?;
     form_module.add_class::<CP5>()?;
     form_module.add_class::<CP6>()?;
+    form_module.add_class::<CPN>()?;
     form_module.add_class::<AS>()?;
     form_module.add_class::<SP>()?;
     form_module.add_class::<Pdb>()?;
+    form_module.add_class::<Mmcif>()?;
     form_module.add_class::<Xyz>()?;
+    form_module.add_class::<Conformer>()?;
+    form_module.add_class::<NucleicAcid>()?;
+    form_module.add_class::<Protein>()?;
     form_module.add_function(wrap_pyfunction!(write_to_pdb, &form_module)?)?;
     form_module.add_function(wrap_pyfunction!(write_to_xyz, &form_module)?)?;
+    form_module.add_function(wrap_pyfunction!(write_pdb, &form_module)?)?;
+    form_module.add_function(wrap_pyfunction!(write_models_to_pdb, &form_module)?)?;
+    form_module.add_function(wrap_pyfunction!(write_frames_to_xyz, &form_module)?)?;
+    form_module.add_function(wrap_pyfunction!(infer_bonds, &form_module)?)?;
+    form_module.add_function(wrap_pyfunction!(find_rings, &form_module)?)?;
+
+    // Add the analysis of sampled energies to the public API
+    let analysis_module = PyModule::new_bound(parent_module.py(), "analysis")?;
+    analysis_module.add_class::<SpherePES>()?;
+    analysis_module.add_class::<TorusPES>()?;
+    analysis_module.add_class::<SphereFES>()?;
+    analysis_module.add_class::<PlaneFES>()?;
+    analysis_module.add_class::<Clusters>()?;
+    analysis_module.add_function(wrap_pyfunction!(kmedoids, &analysis_module)?)?;
+    analysis_module.add_function(wrap_pyfunction!(dbscan, &analysis_module)?)?;
+    analysis_module.add_function(wrap_pyfunction!(hierarchical, &analysis_module)?)?;
+    analysis_module.add_class::<Ensemble>()?;
+    analysis_module.add_function(wrap_pyfunction!(boltzmann, &analysis_module)?)?;
+
+    // Add the statistics of angles to the public API
+    let stats_module = PyModule::new_bound(parent_module.py(), "statistics")?;
+    stats_module.add_function(wrap_pyfunction!(circular_mean, &stats_module)?)?;
+    stats_module.add_function(wrap_pyfunction!(circular_variance, &stats_module)?)?;
+    stats_module.add_function(wrap_pyfunction!(spherical_mean, &stats_module)?)?;
+    stats_module.add_function(wrap_pyfunction!(spherical_variance, &stats_module)?)?;
+    stats_module.add_class::<VonMises>()?;
+    stats_module.add_class::<Fisher>()?;
 
     // Append submodule to root module
     parent_module.add_submodule(&geom_sub_module)?;
     parent_module.add_submodule(&cs_module)?;
     parent_module.add_submodule(&form_module)?;
+    parent_module.add_submodule(&analysis_module)?;
+    parent_module.add_submodule(&stats_module)?;
     Ok(())
 
 }
@@ -0,0 +1,206 @@
+use pyo3::{pyclass, pyfunction, pymethods, PyResult};
+
+use crate::conf_sampling::sixring::TWOPI;
+use crate::errors::OutOfRangeError;
+use crate::statistics::{angle_weights, concentration, degenerate_density};
+
+
+/// The weighted circular mean of `angles` (degrees), in [-180, 180) if any of the angles is negative, else in [0, 360)
+#[pyfunction]
+#[pyo3(signature = (angles, weights=None))]
+pub fn circular_mean(angles: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<f64> {
+    let weights = angle_weights(angles.len(), weights)?;
+    let (mean, _) = mean_direction(&angles, &weights);
+    Ok(wrap_degrees(mean, is_signed(&angles)))
+}
+
+/// The weighted circular variance of `angles` (degrees), 1 - the mean resultant length :
+/// 0 if all angles are equal, towards 1 if they spread out over the circle
+#[pyfunction]
+#[pyo3(signature = (angles, weights=None))]
+pub fn circular_variance(angles: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<f64> {
+    let weights = angle_weights(angles.len(), weights)?;
+    let (_, length) = mean_direction(&angles, &weights);
+    Ok(1. - length)
+}
+
+
+/// The von Mises distribution of angles on the circle, in degrees
+/// `mean` is its mean direction and `kappa` its concentration, 0 for a uniform distribution
+#[pyclass(get_all)]
+pub struct VonMises {
+    pub mean: f64,
+    pub kappa: f64,
+}
+
+#[pymethods]
+impl VonMises {
+
+    #[new]
+    fn new(mean: f64, kappa: f64) -> PyResult<Self> {
+        if kappa.is_nan() || kappa < 0. {
+            return Err(OutOfRangeError::new_err(format!("The concentration kappa ({}) should not be negative.", kappa)))
+        };
+        Ok(Self { mean, kappa })
+    }
+
+    /// Fit the distribution to the `angles` by maximum likelihood
+    /// The mean is the circular mean, and kappa solves I1(kappa) / I0(kappa) = the mean resultant length
+    #[staticmethod]
+    #[pyo3(signature = (angles, weights=None))]
+    fn fit(angles: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<Self> {
+        let weights = angle_weights(angles.len(), weights)?;
+        let (mean, length) = mean_direction(&angles, &weights);
+
+        // Best and Fisher's approximation (Appl. Statist., 1981, 30, 152-157) as a start
+        let start = match length {
+            r if r < 0.53 => (2. * r) + r.powi(3) + (5. * r.powi(5) / 6.),
+            r if r < 0.85 => -0.4 + (1.39 * r) + (0.43 / (1. - r)),
+            r => 1. / (r.powi(3) - (4. * r.powi(2)) + (3. * r)),
+        };
+        let kappa = concentration(length, start, mean_resultant_length, |k| {
+            let a = mean_resultant_length(k);
+            1. - (a / k) - a.powi(2)
+        });
+
+        Ok(Self { mean: wrap_degrees(mean, is_signed(&angles)), kappa })
+    }
+
+    /// The probability density at every angle, per degree
+    /// An infinite kappa, as fitted to identical angles, is all density at the mean
+    fn pdf(&self, angles: Vec<f64>) -> Vec<f64> {
+        let normalisation = 360. * scaled_bessel(0, self.kappa);
+        angles.iter()
+            .map(|a| {
+                let deviation = (a - self.mean).to_radians().cos() - 1.;
+                match self.kappa.is_infinite() {
+                    true => degenerate_density(deviation),
+                    false => (self.kappa * deviation).exp() / normalisation,
+                }
+            })
+            .collect()
+    }
+}
+
+
+// The weighted mean direction of angles (degrees) in (-180, 180], and the length of the mean resultant vector
+pub fn mean_direction(angles: &[f64], weights: &[f64]) -> (f64, f64) {
+    let (sin, cos) = angles.iter().zip(weights.iter())
+        .fold((0., 0.), |(s, c), (a, w)| {
+            let (sin_a, cos_a) = a.to_radians().sin_cos();
+            (s + (w * sin_a), c + (w * cos_a))
+        });
+    let total: f64 = weights.iter().sum();
+    (sin.atan2(cos).to_degrees(), sin.hypot(cos) / total)
+}
+
+fn is_signed(angles: &[f64]) -> bool {
+    angles.iter().any(|a| *a < 0.)
+}
+
+// Wrap an angle onto [-180, 180) or [0, 360), where an angle that rounds to the upper bound wraps to the lower bound
+pub fn wrap_degrees(angle: f64, signed: bool) -> f64 {
+    let shift = if signed { 180. } else { 0. };
+    let wrapped = (angle + shift).rem_euclid(360.);
+    match 360. - wrapped < 1e-9 {
+        true => if signed { -180. } else { 0. },
+        false => wrapped - shift,
+    }
+}
+
+// The mean resultant length I1(kappa) / I0(kappa) of a von Mises distribution
+fn mean_resultant_length(kappa: f64) -> f64 {
+    scaled_bessel(1, kappa) / scaled_bessel(0, kappa)
+}
+
+// The modified Bessel function of the first kind of order 0 or 1, scaled by exp(-x) to not overflow
+// Its power series up to x = 30, and its asymptotic expansion beyond
+fn scaled_bessel(order: u32, x: f64) -> f64 {
+    let n = order as f64;
+
+    if x <= 30. {
+        let half = x / 2.;
+        let mut term = half.powi(order as i32);
+        let mut sum = term;
+        let mut k = 0.;
+        while term > 1e-17 * sum {
+            k += 1.;
+            term *= half.powi(2) / (k * (k + n));
+            sum += term
+        }
+        return sum * (-x).exp()
+    };
+
+    let mu = 4. * n.powi(2);
+    let mut term = 1.;
+    let mut sum = 1.;
+    for k in 1..12 {
+        let odd = ((2 * k) - 1) as f64;
+        term *= -(mu - odd.powi(2)) / (k as f64 * 8. * x);
+        sum += term
+    }
+    sum / (TWOPI * x).sqrt()
+}
+
+
+#[cfg(test)]
+mod test_circular {
+
+    use super::*;
+
+    #[test]
+    fn mean_across_zero() {
+        assert!(circular_mean(vec![350., 10.], None).unwrap().abs() < 1e-9);
+        assert!((circular_mean(vec![-170., 170.], None).unwrap() + 180.).abs() < 1e-9);
+        assert!((circular_mean(vec![0., 90.], Some(vec![1., 0.])).unwrap()).abs() < 1e-9);
+        assert!((circular_variance(vec![0., 90., 180., 270.], None).unwrap() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bessel_across_the_expansions() {
+        // I0(1) = 1.2660658777520082, I1(1) = 0.5651591039924851
+        assert!((scaled_bessel(0, 1.) * 1_f64.exp() - 1.2660658777520082).abs() < 1e-14);
+        assert!((scaled_bessel(1, 1.) * 1_f64.exp() - 0.5651591039924851).abs() < 1e-14);
+        // both sides of x = 30 agree, up to the slope of the function over 2e-9
+        for order in [0, 1] {
+            assert!((scaled_bessel(order, 30. - 1e-9) - scaled_bessel(order, 30. + 1e-9)).abs() < 1e-11);
+        }
+    }
+
+    #[test]
+    fn fit_recovers_kappa() {
+        // Angles that reproduce the mean resultant length of kappa = 4
+        let length = mean_resultant_length(4.);
+        let spread = length.acos().to_degrees();
+        let fitted = VonMises::fit(vec![-spread, spread], None).unwrap();
+
+        assert!(fitted.mean.abs() < 1e-9);
+        assert!((fitted.kappa - 4.).abs() < 1e-9);
+
+        // The density integrates to 1 over the circle
+        let total: f64 = fitted.pdf((0..3600).map(|i| i as f64 / 10.).collect()).iter().sum::<f64>() / 10.;
+        assert!((total - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn identical_angles() {
+        let fitted = VonMises::fit(vec![37.3, 37.3], None).unwrap();
+        assert!(fitted.kappa.is_infinite());
+        assert_eq!(fitted.pdf(vec![37.3, 40., 210.]), vec![f64::INFINITY, 0., 0.]);
+
+        // as are angles closer together than the precision of the fit
+        let close = VonMises::fit(vec![30., 30.00001], None).unwrap();
+        assert_eq!(close.pdf(vec![30., 30.00001]), vec![f64::INFINITY; 2]);
+
+        let single = VonMises::fit(vec![-75.], None).unwrap();
+        assert!(single.pdf(vec![-75., 285., 0.]).iter().all(|p| !p.is_nan()));
+    }
+
+    #[test]
+    fn wrap_onto_the_lower_bound() {
+        // An angle that rounds up to 360 wraps onto 0, not -0
+        assert!(wrap_degrees(360. - 1e-12, false).is_sign_positive());
+        assert_eq!(wrap_degrees(180. - 1e-12, true), -180.);
+        assert_eq!(wrap_degrees(-90., false), 270.);
+    }
+}
@@ -0,0 +1,64 @@
+//! Statistics of angles, which cannot be averaged like ordinary numbers :
+//! the mean of 350 and 10 degrees is 0 degrees, not 180 degrees.
+//! Every angle is treated as a direction, a unit vector on the circle or on the sphere
+
+
+pub mod circular; // phase angles of AS and CP5, and peptide torsions, on the circle
+pub mod spherical; // (theta, phi) of CP6, on the sphere
+
+use pyo3::PyResult;
+
+use crate::errors::{OutOfRangeError, PuckepyError};
+
+
+// The weights of the angles, 1 for every angle if none are queried
+fn angle_weights(amount: usize, weights: Option<Vec<f64>>) -> PyResult<Vec<f64>> {
+
+    if amount == 0 {
+        return Err(PuckepyError::new_err("No angles have been queried. Expected at least one angle."))
+    };
+
+    let weights = weights.unwrap_or(vec![1.; amount]);
+    if weights.len() != amount {
+        return Err(PuckepyError::new_err(format!("The amount of angles ({}) and weights ({}) are not equal. Expected a weight for every angle.", amount, weights.len())))
+    };
+
+    if weights.iter().any(|w| w.is_nan() || *w < 0.) || weights.iter().sum::<f64>() <= 0. {
+        return Err(OutOfRangeError::new_err("The weights of the angles should not be negative, and not all be zero."))
+    };
+
+    Ok(weights)
+}
+
+// Solve A(kappa) = length for the concentration kappa of a distribution, by Newton steps from `start`
+// The mean resultant length A is concave and increasing in kappa, so that a step never passes the root from below
+fn concentration(length: f64, start: f64, a: impl Fn(f64) -> f64, derivative: impl Fn(f64) -> f64) -> f64 {
+
+    if length < 1e-12 {
+        return 0.
+    };
+    if length > 1. - 1e-12 {
+        return f64::INFINITY
+    };
+
+    let mut kappa = start;
+    for _ in 0..100 {
+        let next = kappa - ((a(kappa) - length) / derivative(kappa));
+        let next = if next > 0. { next } else { kappa / 2. };
+        if (next - kappa).abs() < 1e-12 * kappa {
+            return next
+        };
+        kappa = next
+    }
+
+    kappa
+}
+
+// The density of an infinite concentration, all at the mean direction, at a deviation cos(angle) - 1 from it
+// Directions as close to the mean as those that fit an infinite kappa lie at the mean
+fn degenerate_density(deviation: f64) -> f64 {
+    match deviation > -1e-12 {
+        true => f64::INFINITY,
+        false => 0.,
+    }
+}
@@ -0,0 +1,164 @@
+use pyo3::{pyclass, pyfunction, pymethods, PyResult};
+
+use crate::conf_sampling::sixring::TWOPI;
+use crate::errors::{OutOfRangeError, PuckepyError};
+use crate::statistics::{angle_weights, concentration, degenerate_density};
+
+
+/// The weighted spherical mean direction of the (`theta`, `phi`) of CP6 (degrees), as (theta, phi) with phi in [0, 360)
+#[pyfunction]
+#[pyo3(signature = (theta, phi, weights=None))]
+pub fn spherical_mean(theta: Vec<f64>, phi: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<(f64, f64)> {
+    let weights = direction_weights(&theta, &phi, weights)?;
+    let (theta, phi, _) = mean_direction(&theta, &phi, &weights);
+    Ok((theta, phi))
+}
+
+/// The weighted spherical variance of the (`theta`, `phi`) of CP6 (degrees), 1 - the mean resultant length :
+/// 0 if all directions are equal, towards 1 if they spread out over the sphere
+#[pyfunction]
+#[pyo3(signature = (theta, phi, weights=None))]
+pub fn spherical_variance(theta: Vec<f64>, phi: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<f64> {
+    let weights = direction_weights(&theta, &phi, weights)?;
+    let (_, _, length) = mean_direction(&theta, &phi, &weights);
+    Ok(1. - length)
+}
+
+
+/// The Fisher (von Mises-Fisher) distribution of directions on the Cremer-Pople sphere, in degrees
+/// (`theta`, `phi`) is its mean direction and `kappa` its concentration, 0 for a uniform distribution
+#[pyclass(get_all)]
+pub struct Fisher {
+    pub theta: f64,
+    pub phi: f64,
+    pub kappa: f64,
+}
+
+#[pymethods]
+impl Fisher {
+
+    #[new]
+    fn new(theta: f64, phi: f64, kappa: f64) -> PyResult<Self> {
+        if kappa.is_nan() || kappa < 0. {
+            return Err(OutOfRangeError::new_err(format!("The concentration kappa ({}) should not be negative.", kappa)))
+        };
+        Ok(Self { theta, phi, kappa })
+    }
+
+    /// Fit the distribution to the (`theta`, `phi`) by maximum likelihood
+    /// The mean direction is the spherical mean, and kappa solves coth(kappa) - 1 / kappa = the mean resultant length
+    #[staticmethod]
+    #[pyo3(signature = (theta, phi, weights=None))]
+    fn fit(theta: Vec<f64>, phi: Vec<f64>, weights: Option<Vec<f64>>) -> PyResult<Self> {
+        let weights = direction_weights(&theta, &phi, weights)?;
+        let (theta, phi, length) = mean_direction(&theta, &phi, &weights);
+
+        // Banerjee et al.'s approximation (J. Mach. Learn. Res., 2005, 6, 1345-1382) as a start
+        let start = length * (3. - length.powi(2)) / (1. - length.powi(2));
+        let kappa = concentration(length, start, mean_resultant_length, |k| match k < 1e-3 {
+            true => (1. / 3.) - (k.powi(2) / 15.),
+            false => k.powi(-2) - k.sinh().powi(-2),
+        });
+
+        Ok(Self { theta, phi, kappa })
+    }
+
+    /// The probability density at every (`theta`, `phi`), per steradian
+    /// An infinite kappa, as fitted to identical directions, is all density at the mean direction
+    fn pdf(&self, theta: Vec<f64>, phi: Vec<f64>) -> PyResult<Vec<f64>> {
+        if theta.len() != phi.len() {
+            return Err(PuckepyError::new_err(format!("The amount of theta ({}) and phi ({}) are not equal. Expected a direction for every theta and phi.", theta.len(), phi.len())))
+        };
+
+        let normalisation = match self.kappa < 1e-8 {
+            true => 1. / (2. * TWOPI),
+            false => self.kappa / (TWOPI * (1. - (-2. * self.kappa).exp())),
+        };
+        let mean = unit_vector(self.theta, self.phi);
+        Ok(theta.iter().zip(phi.iter())
+            .map(|(t, p)| {
+                let v = unit_vector(*t, *p);
+                let deviation = (mean[0] * v[0]) + (mean[1] * v[1]) + (mean[2] * v[2]) - 1.;
+                match self.kappa.is_infinite() {
+                    true => degenerate_density(deviation),
+                    false => normalisation * (self.kappa * deviation).exp(),
+                }
+            })
+            .collect())
+    }
+}
+
+
+// The weighted mean direction of (theta, phi) in degrees, as (theta, phi) with phi in [0, 360),
+// and the length of the mean resultant vector
+pub fn mean_direction(theta: &[f64], phi: &[f64], weights: &[f64]) -> (f64, f64, f64) {
+    let mut v = [0.; 3];
+    for ((t, p), w) in theta.iter().zip(phi.iter()).zip(weights.iter()) {
+        let u = unit_vector(*t, *p);
+        v = [0, 1, 2].map(|i| v[i] + (w * u[i]));
+    }
+    let length = (v[0].powi(2) + v[1].powi(2) + v[2].powi(2)).sqrt();
+    let total: f64 = weights.iter().sum();
+    let theta = match length > 0. {
+        true => (v[2] / length).clamp(-1., 1.).acos().to_degrees(),
+        false => 0.,
+    };
+    (theta, v[1].atan2(v[0]).to_degrees().rem_euclid(360.), length / total)
+}
+
+fn direction_weights(theta: &[f64], phi: &[f64], weights: Option<Vec<f64>>) -> PyResult<Vec<f64>> {
+    if theta.len() != phi.len() {
+        return Err(PuckepyError::new_err(format!("The amount of theta ({}) and phi ({}) are not equal. Expected a direction for every theta and phi.", theta.len(), phi.len())))
+    };
+    angle_weights(theta.len(), weights)
+}
+
+fn unit_vector(theta: f64, phi: f64) -> [f64; 3] {
+    let (sin_t, cos_t) = theta.to_radians().sin_cos();
+    let (sin_p, cos_p) = phi.to_radians().sin_cos();
+    [sin_t * cos_p, sin_t * sin_p, cos_t]
+}
+
+// The mean resultant length coth(kappa) - 1 / kappa of a Fisher distribution
+fn mean_resultant_length(kappa: f64) -> f64 {
+    match kappa < 1e-3 {
+        true => (kappa / 3.) - (kappa.powi(3) / 45.),
+        false => (1. / kappa.tanh()) - (1. / kappa),
+    }
+}
+
+
+#[cfg(test)]
+mod test_spherical {
+
+    use super::*;
+
+    #[test]
+    fn mean_around_the_pole() {
+        // Directions around the north pole average onto it, whatever their phi
+        let (theta, _) = spherical_mean(vec![10., 10., 10., 10.], vec![0., 90., 180., 270.], None).unwrap();
+        assert!(theta.abs() < 1e-6);
+
+        let (theta, phi) = spherical_mean(vec![80., 100.], vec![350., 10.], None).unwrap();
+        assert!((theta - 90.).abs() < 1e-9);
+        assert!(phi.min(360. - phi) < 1e-9);
+    }
+
+    #[test]
+    fn fit_recovers_kappa() {
+        // Directions that reproduce the mean resultant length of kappa = 10
+        let spread = mean_resultant_length(10.).acos().to_degrees();
+        let fitted = Fisher::fit(vec![spread; 3], vec![0., 120., 240.], None).unwrap();
+
+        assert!(fitted.theta.abs() < 1e-6);
+        assert!((fitted.kappa - 10.).abs() < 1e-9);
+        assert!((spherical_variance(vec![spread; 3], vec![0., 120., 240.], None).unwrap() - (1. - mean_resultant_length(10.))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn identical_directions() {
+        let fitted = Fisher::fit(vec![37., 37.], vec![111., 111.], None).unwrap();
+        assert!(fitted.kappa.is_infinite());
+        assert_eq!(fitted.pdf(vec![37., 90.], vec![111., 0.]).unwrap(), vec![f64::INFINITY, 0.]);
+    }
+}
@@ -1,5 +1,9 @@
 import puckepy
 import unittest
+import math
+import os
+import tempfile
+import numpy as np
 
 #run in the current directory to run tests
 #```
@@ -37,6 +41,7 @@ class Formalism(unittest.TestCase):
         self.cp51 = puckepy.formalism.CP5(0.5, 180.)
         self.cp52 = puckepy.formalism.CP5(amplitude=0.5, phase_angle=180.)
         self.cp53 = puckepy.formalism.CP5(0.5, 180.).invert()
+        self.cp56 = puckepy.formalism.CP5(0.5, 180.).invert(elements=["O", "C", "C", "C", "C"])
 
         self.cp54 = puckepy.formalism.CP5().from_atomnames(
                 pdb=self.fiveringPdb,
@@ -67,6 +72,11 @@ class Formalism(unittest.TestCase):
         self.cp61 = puckepy.formalism.CP6(0.5, 180., 90.)
         self.cp62 = puckepy.formalism.CP6(amplitude=0.5, phase_angle=180., theta=90.)
         self.cp63 = puckepy.formalism.CP6(0.5, 180., 90.).invert()
+        self.cp66 = puckepy.formalism.CP6(0.5, 180., 90.).invert(elements=["O", "C", "C", "N", "C", "C"])
+        self.cp67 = puckepy.formalism.CP6(0.5, 180., 90.).invert(
+                bondlengths=[1.43, 1.52, 1.52, 1.47, 1.47, 1.52],
+                angles=[112., None, 110., None, 106., None]
+                )
 
         self.cp64 = puckepy.formalism.CP6().from_atomnames(
                 pdb=self.sixringPdb,
@@ -79,6 +89,20 @@ class Formalism(unittest.TestCase):
 
                 )
 
+        # CPN() class
+        self.cpn1 = puckepy.formalism.CPN(6)
+        self.cpn2 = puckepy.formalism.CPN(ring_size=5)
+
+        self.cpn3 = puckepy.formalism.CPN(6).from_atomnames(
+                pdb=self.sixringPdb,
+                query_names=["O5'", "C1'", "C2'", "N3'", "C4'", "C5'"]
+                )
+
+        self.cpn4 = puckepy.formalism.CPN(5).from_indices(
+                coordinates=self.fiveringPdb.coordinates,
+                indices=[7, 8, 26, 24, 6]
+                )
+
         # SP() class
         self.sp1 = puckepy.formalism.SP()
 
@@ -141,6 +165,72 @@ class Formalism(unittest.TestCase):
                 query_names=["O4'", "C1'", "C2'", "C3'", "C4'"]
                 )
 
+    def test_invert_with_bondlengths(self):
+        bonds = [puckepy.geometry.bondlength(self.cp67[j], self.cp67[(j + 1) % 6]) for j in range(6)]
+        for bond, expected in zip(bonds, [1.43, 1.52, 1.52, 1.47, 1.47, 1.52]):
+            self.assertAlmostEqual(bond, expected)
+        self.assertAlmostEqual(puckepy.geometry.bondlength(self.cp56[4], self.cp56[0]), 1.42)
+        self.assertAlmostEqual(puckepy.geometry.bondlength(self.cp56[1], self.cp56[2]), 1.54)
+
+    def test_invert_with_angles(self):
+        for j, expected in [(0, 112.), (2, 110.), (4, 106.)]:
+            self.assertAlmostEqual(puckepy.geometry.bondangle(self.cp67[j - 1], self.cp67[j], self.cp67[j + 1]), expected)
+        # the angle on the ring oxygen, first by convention, is enforced when looked up by elements
+        self.assertAlmostEqual(puckepy.geometry.bondangle(self.cp56[4], self.cp56[0], self.cp56[1]), 111.5)
+        self.assertAlmostEqual(puckepy.geometry.bondangle(self.cp66[5], self.cp66[0], self.cp66[1]), 111.5)
+
+    @unittest.expectedFailure
+    def test_too_many_angles_to_enforce(self):
+        self.cp5a = puckepy.formalism.CP5(0.35, 288.).invert(angles=[100.] * 5)
+
+    @unittest.expectedFailure
+    def test_incorrect_amount_of_bondlengths(self):
+        self.cp5a = puckepy.formalism.CP5(0.5, 180.).invert(bondlengths=[1.54, 1.54])
+
+    # CP4 Testings
+    def test_cp4(self):
+        cyclobutane = puckepy.formalism.CP4(0.25).invert()
+        self.assertAlmostEqual(puckepy.formalism.CP4().from_indices(coordinates=cyclobutane, indices=[0, 1, 2, 3]), 0.25)
+        self.assertAlmostEqual(puckepy.geometry.bondlength(cyclobutane[0], cyclobutane[1]), 1.54)
+
+        oxetane = puckepy.formalism.CP4(-0.1).invert(elements=["O", "C", "C", "C"])
+        self.assertAlmostEqual(puckepy.formalism.CP4().from_indices(coordinates=oxetane, indices=[0, 1, 2, 3]), -0.1)
+
+    @unittest.expectedFailure
+    def test_incorrect_amount_of_indices_CP4(self):
+        self.cp4a = puckepy.formalism.CP4().from_indices(
+                coordinates=self.sixringPdb.coordinates,
+                indices=[7, 8, 26, 24, 6]
+                )
+
+    # CPN Testings
+    @unittest.expectedFailure
+    def test_incorrect_amount_of_indices_CPN(self):
+        self.cpna = puckepy.formalism.CPN(6).from_indices(
+                coordinates=self.sixringPdb.coordinates,
+                indices=[7, 8, 26, 24, 6]
+                )
+
+    @unittest.expectedFailure
+    def test_too_small_ring_size_CPN(self):
+        self.cpnb = puckepy.formalism.CPN(3)
+
+    def test_fixture_puckers(self):
+        # the rings of real molecules are irregular; their mean plane normal is still a unit vector
+        amplitude, phase_angle = puckepy.formalism.CP5().from_atomnames(self.fiveringPdb, ["O4'", "C1'", "C2'", "C3'", "C4'"])
+        self.assertAlmostEqual(amplitude, 0.348744, places=5)
+        self.assertAlmostEqual(phase_angle, 90.6135, places=3)
+
+        amplitude, phase_angle, theta = puckepy.formalism.CP6().from_atomnames(self.sixringPdb, ["O5'", "C1'", "C2'", "N3'", "C4'", "C5'"])
+        self.assertAlmostEqual(amplitude, 0.514033, places=5)
+        self.assertAlmostEqual(phase_angle, 134.5362, places=3)
+        self.assertAlmostEqual(theta, 8.8988, places=3)
+
+    def test_fixture_as(self):
+        amplitude, phase_angle = puckepy.formalism.AS().from_atomnames(self.fiveringPdb, ["O4'", "C1'", "C2'", "C3'", "C4'"])
+        self.assertAlmostEqual(amplitude, 0.618571, places=5)
+        self.assertAlmostEqual(phase_angle, 180.4857, places=3)
+
     # SP Testings
     @unittest.expectedFailure
     def test_incorrect_amount_of_indices_SP(self):
@@ -155,12 +245,223 @@ class Formalism(unittest.TestCase):
                 pdb=self.sixringPdb,
                 query_names=["O4'", "C1'", "C2'", "C3'", "C4'"]
                 )
+    # Exception Testings
+    def test_exceptions(self):
+        with self.assertRaises(puckepy.AtomNotFoundError):
+            puckepy.formalism.CP5().from_atomnames(
+                    pdb=self.fiveringPdb,
+                    query_names=["O4'", "C1'", "C2'", "C3'", "X4'"]
+                    )
+
+        with self.assertRaises(puckepy.RingSizeError):
+            puckepy.formalism.SP().from_indices(
+                    coordinates=self.sixringPdb.coordinates,
+                    indices=[7, 8, 26, 24, 6]
+                    )
+
+        with self.assertRaises(puckepy.OutOfRangeError):
+            puckepy.formalism.CP6(1.5, 180., 90.)
+
+        with self.assertRaises(puckepy.OutOfRangeError):
+            puckepy.formalism.AS().from_indices(
+                    coordinates=self.fiveringPdb.coordinates,
+                    indices=[7, 8, 26, 24, 10000]
+                    )
+
+        with self.assertRaises(puckepy.FileFormatError):
+            puckepy.formalism.Pdb(filename="./furanose.xyz")
+
+        # every exception inherits from PuckepyError
+        with self.assertRaises(puckepy.PuckepyError):
+            puckepy.formalism.CPN(3)
+
+
+# Test ring perception
+class RingPerception(unittest.TestCase):
+
+    def setUp(self) :
+        self.fiveringPdb = puckepy.formalism.Pdb(filename="./fivering_adenosine.pdb").parse()
+        self.sixringPdb = puckepy.formalism.Pdb(filename="./sixring_morpholino.pdb").parse()
+
+        self.bonds = puckepy.formalism.infer_bonds(self.fiveringPdb)
+        self.rings = puckepy.formalism.find_rings(self.fiveringPdb)
+
+        self.cp5 = puckepy.formalism.CP5().from_rings(self.fiveringPdb)
+        self.as1 = puckepy.formalism.AS().from_rings(self.fiveringPdb)
+        self.cp6 = puckepy.formalism.CP6().from_rings(self.sixringPdb)
+        self.sp1 = puckepy.formalism.SP().from_rings(self.sixringPdb)
+
+    def test_rings_match_atomnames(self):
+        # ribose, imidazole and pyrimidine ring of the adenosine
+        self.assertEqual(len(self.rings), 3)
+        self.assertEqual(self.cp5[0][0], ["O4'", "C1'", "C2'", "C3'", "C4'"])
+        self.assertEqual(self.cp6[0][0], ["O5'", "C1'", "C2'", "N3'", "C4'", "C5'"])
+
+        amplitude, phase_angle = puckepy.formalism.CP5().from_atomnames(
+                pdb=self.fiveringPdb,
+                query_names=["O4'", "C1'", "C2'", "C3'", "C4'", ]
+                )
+        self.assertAlmostEqual(self.cp5[0][1][0], amplitude)
+        self.assertAlmostEqual(self.cp5[0][1][1], phase_angle)
+
+    def test_conformers(self):
+        amplitude, phase_angle = self.cp5[0][1]
+        conformer = puckepy.formalism.CP5(amplitude, phase_angle).conformer()
+        self.assertEqual(conformer.label, "2T3")
+        self.assertEqual(conformer.sector, "South")
+
+        atomnames, (amplitude, phase_angle, theta) = self.cp6[0]
+        self.assertEqual(puckepy.formalism.CP6(amplitude, phase_angle, theta).conformer(atomnames).label, "4C1")
+
+    def test_nucleic_acid(self):
+        na = puckepy.formalism.NucleicAcid(self.fiveringPdb)
+        self.assertEqual(len(na), 1)
+        # a single nucleoside has no neighbours, nor a phosphate
+        self.assertEqual([na.alpha[0], na.beta[0], na.epsilon[0], na.zeta[0]], [None] * 4)
+        self.assertEqual(na.glycosidic, ["anti"])
+        self.assertEqual(na.sugar_conformer, ["2T3"])
+        self.assertAlmostEqual(na.phase_angle[0], self.as1[0][1][1])
+
+    @unittest.expectedFailure
+    def test_nucleic_acid_without_nucleotides(self):
+        with tempfile.TemporaryDirectory() as tmp :
+            fname = os.path.join(tmp, "water.pdb")
+            puckepy.formalism.write_pdb(fname, ["O", "H1", "H2"], [[0., 0., 0.], [0.957, 0., 0.], [-0.240, 0.927, 0.]])
+            self.na = puckepy.formalism.NucleicAcid(puckepy.formalism.Pdb(filename=fname).parse())
+
+    def test_protein(self):
+        protein = puckepy.formalism.Protein(puckepy.formalism.Pdb(filename="./peptide_cispro.pdb").parse())
+        self.assertEqual(protein.residuenames, ["ALA", "SER", "PRO", "GLY", "ALA"])
+        # the termini lack a phi or a psi
+        self.assertEqual([protein.phi[0], protein.psi[-1], protein.omega[0]], [None] * 3)
+        self.assertEqual(protein.cis, [None, False, True, False, False])
+        self.assertAlmostEqual(protein.phi[1], -60., places=1)
+        self.assertAlmostEqual(protein.chi1[1], -60., places=1)
+        self.assertEqual([protein.chi1[0], protein.chi1[3], protein.chi3[2]], [None] * 3)
+
+        phi, psi = protein.phi_psi()[("A", 3)]
+        self.assertAlmostEqual(phi, -75., places=1)
+        self.assertAlmostEqual(psi, 150., places=1)
+        self.assertEqual(len(protein.phi_psi()), 3)
+
+    @unittest.expectedFailure
+    def test_protein_without_amino_acids(self):
+        self.protein = puckepy.formalism.Protein(self.fiveringPdb)
+
+    def test_mmcif_matches_pdb(self):
+        mmcif = puckepy.formalism.Mmcif(filename="./fivering_adenosine.cif").parse()
+        self.assertEqual(mmcif.atomnames, self.fiveringPdb.atomnames)
+        self.assertEqual(mmcif.coordinates, self.fiveringPdb.coordinates)
+        self.assertEqual(mmcif.chains, self.fiveringPdb.chains)
+
+        query_names = ["O4'", "C1'", "C2'", "C3'", "C4'"]
+        self.assertEqual(puckepy.formalism.CP5().from_atomnames(mmcif, query_names), 
+                         puckepy.formalism.CP5().from_atomnames(self.fiveringPdb, query_names))
+        self.assertEqual(puckepy.formalism.CP5().from_rings(mmcif), self.cp5)
+
+        monomers = puckepy.formalism.Mmcif(filename="./fivering_adenosine.cif").parse_by_monomers()
+        self.assertEqual(len(monomers), 1)
+
+    @unittest.expectedFailure
+    def test_mmcif_from_pdb_file(self):
+        self.mmcif = puckepy.formalism.Mmcif(filename="./fivering_adenosine.pdb")
+
+    def test_write_pdb_roundtrip(self):
+        with tempfile.TemporaryDirectory() as tmp :
+            fname = os.path.join(tmp, "adenosine.pdb")
+            self.fiveringPdb.write(fname, bonds=self.bonds)
+            pdb = puckepy.formalism.Pdb(filename=fname).parse()
+
+            self.assertEqual(pdb.atomnames, self.fiveringPdb.atomnames)
+            self.assertEqual(pdb.elements, self.fiveringPdb.elements)
+            self.assertEqual(pdb.residuenames, self.fiveringPdb.residuenames)
+            self.assertEqual(pdb.coordinates, self.fiveringPdb.coordinates)
+            self.assertEqual(sorted(puckepy.formalism.infer_bonds(pdb)), sorted(self.bonds))
+
+    def test_write_pdb_modified_coordinates(self):
+        moved = [[x + 1.5, y, z - 2.] for x, y, z in self.fiveringPdb.coordinates]
+        with tempfile.TemporaryDirectory() as tmp :
+            fname = os.path.join(tmp, "moved.pdb")
+            self.fiveringPdb.write(fname, coordinates=moved)
+            pdb = puckepy.formalism.Pdb(filename=fname).parse()
+
+            self.assertEqual(pdb.atomnames, self.fiveringPdb.atomnames)
+            for written, expected in zip(pdb.coordinates, moved):
+                for w, e in zip(written, expected):
+                    self.assertAlmostEqual(w, e, places=3)
+
+    @unittest.expectedFailure
+    def test_write_pdb_too_few_coordinates(self):
+        with tempfile.TemporaryDirectory() as tmp :
+            self.fiveringPdb.write(os.path.join(tmp, "moved.pdb"), coordinates=self.fiveringPdb.coordinates[1:])
+
+    @unittest.expectedFailure
+    def test_incorrect_amount_of_elements_write_pdb(self):
+        with tempfile.TemporaryDirectory() as tmp :
+            puckepy.formalism.write_pdb(os.path.join(tmp, "water.pdb"), 
+                                        ["O", "H1", "H2"],
+                                        [[0., 0., 0.], [0.957, 0., 0.], [-0.240, 0.927, 0.]],
+                                        ["O", "H"]
+                                        )
+
+    @unittest.expectedFailure
+    def test_too_small_max_ring_size(self):
+        self.ringsa = puckepy.formalism.find_rings(self.fiveringPdb, max_ring_size=2)
+
+
+# Test batch puckering over trajectories
+class Trajectory(unittest.TestCase):
+
+    def setUp(self) :
+        self.fiveringPdb = puckepy.formalism.Pdb(filename="./fivering_adenosine.pdb").parse()
+        self.sixringPdb = puckepy.formalism.Pdb(filename="./sixring_morpholino.pdb").parse()
+
+        # (n_frames, n_atoms, 3)
+        self.fiveringTraj = np.array([self.fiveringPdb.coordinates] * 10)
+        self.sixringTraj = np.array([self.sixringPdb.coordinates] * 10)
+
+        self.cp5 = puckepy.formalism.CP5().from_trajectory(
+                coordinates=self.fiveringTraj,
+                indices=[7, 8, 26, 24, 6]
+                )
+        self.as1 = puckepy.formalism.AS().from_trajectory(
+                coordinates=self.fiveringTraj,
+                indices=[7, 8, 26, 24, 6]
+                )
+        self.cp6 = puckepy.formalism.CP6().from_trajectory(
+                coordinates=self.sixringTraj,
+                indices=[6, 7, 27, 26, 23, 4]
+                )
+        self.sp1 = puckepy.formalism.SP().from_trajectory(
+                coordinates=self.sixringTraj,
+                indices=[6, 7, 27, 26, 23, 4]
+                )
+
+    def test_trajectory_matches_single_frame(self):
+        amplitude, phase_angle = puckepy.formalism.CP5().from_indices(
+                coordinates=self.fiveringPdb.coordinates,
+                indices=[7, 8, 26, 24, 6]
+                )
+        self.assertEqual(self.cp5[0].shape, (10,))
+        self.assertAlmostEqual(self.cp5[0][9], amplitude)
+        self.assertAlmostEqual(self.cp5[1][9], phase_angle)
+        self.assertEqual(self.sp1[0].shape, (10, 3))
+
+    @unittest.expectedFailure
+    def test_incorrect_amount_of_indices_trajectory(self):
+        self.cp6a = puckepy.formalism.CP6().from_trajectory(
+                coordinates=self.sixringTraj,
+                indices=[7, 8, 26, 24, 6]
+                )
+
+
 # Test Confsampling module
 class Confsampling(unittest.TestCase):
 
     def setUp(self) :
         self.a1 = puckepy.confsampling.Sixring(amount=630)
         self.a2 = puckepy.confsampling.SixringAxes(amount=630)
+        self.a3 = puckepy.confsampling.Sixring(amount=630, keep_coordinates=True)
 
         self.b1 = puckepy.confsampling.Fivering(interval=21)
         self.b2 = puckepy.confsampling.FiveringAxes(interval=21)
@@ -169,9 +470,188 @@ class Confsampling(unittest.TestCase):
         self.c2 = puckepy.confsampling.PeptideAxes(interval=37)
 
     
+    def test_sixring_alphas(self):
+        # the constraint torsions of the sampled pyranoses, whose rings hold 1.54 Angstrom bonds
+        sixring = puckepy.confsampling.Sixring(amount=100)
+        self.assertEqual(len(sixring.alpha1), 99)
+        for i, alphas in [(7, (152.6298, 115.6258, 169.3839)), (42, (136.8049, 232.1416, 171.6162))]:
+            self.assertAlmostEqual(sixring.alpha1[i], alphas[0], places=4)
+            self.assertAlmostEqual(sixring.alpha2[i], alphas[1], places=4)
+            self.assertAlmostEqual(sixring.alpha3[i], alphas[2], places=4)
+
     def test_confsampling(self):
         self.assertEqual('foo'.lower(), 'foo')
 
+    def test_keep_coordinates(self):
+        self.assertEqual(len(self.a1.coordinates), 0)
+        self.assertEqual(len(self.a3.coordinates), len(self.a3.alpha1))
+        self.assertEqual(len(self.a3.coordinates[0]), 6)
+
+    def test_fourring(self):
+        fourring = puckepy.confsampling.Fourring(interval=11, max_amplitude=0.25)
+        self.assertEqual(len(fourring.nu1), 11)
+        self.assertEqual((fourring.amplitude[0], fourring.amplitude[-1]), (-0.25, 0.25))
+        # the torsions of the mirrored puckers only differ in sign
+        self.assertAlmostEqual(fourring.nu1[0], 360. - fourring.nu1[-1])
+
+    @unittest.expectedFailure
+    def test_too_large_max_amplitude_fourring(self):
+        puckepy.confsampling.Fourring(interval=11, max_amplitude=1.5)
+
+    def test_sequences_return_exact_amount(self):
+        for sampling in ["fibonacci", "sobol", "halton", "random"]:
+            self.assertEqual(len(puckepy.confsampling.Sixring(amount=630, sampling=sampling).alpha1), 630)
+            self.assertEqual(len(puckepy.confsampling.SixringAxes(amount=630, sampling=sampling).phi), 630)
+            self.assertEqual(len(puckepy.confsampling.Fivering(interval=21, sampling=sampling, amount=100).nu1), 100)
+            self.assertEqual(len(puckepy.confsampling.PeptideAxes(interval=37, sampling=sampling).x), 37 * 37)
+
+        # random sampling is reproducible by its seed
+        self.assertEqual(puckepy.confsampling.Peptide(37, sampling="random", seed=1).phi,
+                         puckepy.confsampling.Peptide(37, sampling="random", seed=1).phi)
+
+    @unittest.expectedFailure
+    def test_unknown_sampling(self):
+        puckepy.confsampling.Sixring(amount=630, sampling="latin")
+
+    @unittest.expectedFailure
+    def test_amount_on_grid(self):
+        puckepy.confsampling.Fivering(interval=21, amount=100)
+
+    def test_refinement(self):
+        # a single low-energy well around phi = -60, psi = -45
+        axes = puckepy.confsampling.PeptideAxes(interval=36, periodic=True, convention="signed")
+        energy = lambda phi, psi: ((phi + 60.) ** 2 + (psi + 45.) ** 2) / 100.
+        refinement = puckepy.confsampling.Refinement(axes, [energy(x, y) for x, y in zip(axes.x, axes.y)], threshold=2., resolution=2.5)
+        self.assertAlmostEqual(refinement.spacing, 10.)
+
+        while not refinement.converged:
+            new_axes = refinement.refine()
+            refinement.add([energy(x, y) for x, y in zip(new_axes.x, new_axes.y)])
+
+        self.assertAlmostEqual(refinement.spacing, 2.5)
+        self.assertEqual(len(refinement.axes.x), len(refinement.energies))
+        self.assertTrue(all(-180. <= x < 180. for x in refinement.axes.x))
+
+    def test_refinement_of_sixring(self):
+        axes = puckepy.confsampling.SixringAxes(amount=100)
+        refinement = puckepy.confsampling.Refinement(axes, [float(i) for i in range(len(axes.phi))], threshold=0., resolution=1.)
+        new_axes = refinement.refine()
+        self.assertEqual(len(new_axes.phi), 6)
+        self.assertEqual(len(puckepy.confsampling.Sixring.from_axes(new_axes).alpha1), 6)
+
+    @unittest.expectedFailure
+    def test_refine_without_adding_energies(self):
+        axes = puckepy.confsampling.FiveringAxes(interval=5)
+        refinement = puckepy.confsampling.Refinement(axes, [0.] * 25, threshold=1., resolution=1.)
+        refinement.refine()
+        refinement.refine()
+
+    def test_fivering_polar(self):
+        polar = puckepy.confsampling.FiveringPolar(amplitudes=[0., 0.2, 0.4], phase_interval=8)
+        # the planar ring is sampled once
+        self.assertEqual(len(polar.nu1), 1 + 2 * 8)
+        self.assertEqual(len(polar.coordinates), len(polar.nu3))
+
+        amplitude, phase_angle = puckepy.formalism.CP5().from_indices(coordinates=polar.coordinates[3], indices=[0, 1, 2, 3, 4])
+        self.assertAlmostEqual(amplitude, polar.amplitude[3])
+        self.assertAlmostEqual(phase_angle, polar.phase_angle[3])
+
+    @unittest.expectedFailure
+    def test_negative_amplitude_fivering_polar(self):
+        puckepy.confsampling.FiveringPolar(amplitudes=[-0.1], phase_interval=8)
+
+    @unittest.expectedFailure
+    def test_large_amplitude_fivering_polar(self):
+        puckepy.confsampling.FiveringPolar(amplitudes=[1.2], phase_interval=8)
+
+    @unittest.expectedFailure
+    def test_zero_phase_interval_fivering_polar(self):
+        puckepy.confsampling.FiveringPolar(amplitudes=[0.4], phase_interval=0)
+
+    def test_sevenring(self):
+        sevenring = puckepy.confsampling.Sevenring(amplitude_interval=3, phase_interval=6, keep_coordinates=True)
+        axes = puckepy.confsampling.SevenringAxes(amplitude_interval=3, phase_interval=6)
+        self.assertEqual(len(sevenring.nu1), len(axes.q2))
+        self.assertEqual(len(sevenring.coordinates), len(sevenring.nu4))
+
+        # a conformer where both phase angles are defined, away from the 0/360 boundary
+        i = next(i for i, (q2, phi2, q3, phi3) in enumerate(zip(axes.q2, axes.phi2, axes.q3, axes.phi3)) if min(q2, phi2, q3, phi3) > 0.)
+        cpn = puckepy.formalism.CPN(7).from_indices(coordinates=sevenring.coordinates[i], indices=[0, 1, 2, 3, 4, 5, 6])
+        self.assertAlmostEqual(cpn.amplitudes[0], axes.q2[i])
+        self.assertAlmostEqual(cpn.phase_angles[0], axes.phi2[i])
+        self.assertAlmostEqual(cpn.amplitudes[1], axes.q3[i])
+        self.assertAlmostEqual(cpn.phase_angles[1], axes.phi3[i])
+
+    def test_write_any_ring_size(self):
+        sevenring = puckepy.confsampling.Sevenring(amplitude_interval=2, phase_interval=3, keep_coordinates=True)
+        fourring = puckepy.confsampling.Fourring(interval=5)
+        with tempfile.TemporaryDirectory() as tmp :
+            fname = os.path.join(tmp, "sevenring.pdb")
+            puckepy.formalism.write_models_to_pdb(fname, sevenring.coordinates, "SEV")
+            with open(fname) as f :
+                lines = f.readlines()
+            self.assertEqual(sum(line.startswith("MODEL") for line in lines), len(sevenring.coordinates))
+            self.assertEqual([line[12:16].strip() for line in lines[1:8]], ["C1", "C2", "C3", "C4", "C5", "C6", "C7"])
+
+            fname = os.path.join(tmp, "fourring.xyz")
+            puckepy.formalism.write_frames_to_xyz(fname, fourring.coordinates)
+            with open(fname) as f :
+                lines = f.readlines()
+            self.assertEqual(len(lines), 5 * (2 + 4))
+            self.assertAlmostEqual(float(lines[2].split()[1]), fourring.coordinates[0][0][0])
+
+    @unittest.expectedFailure
+    def test_write_too_small_ring(self):
+        with tempfile.TemporaryDirectory() as tmp :
+            puckepy.formalism.write_models_to_pdb(os.path.join(tmp, "bond.pdb"), [[[0., 0., 0.], [1.54, 0., 0.]]], "BND")
+
+    @unittest.expectedFailure
+    def test_negative_max_amplitude_sevenring(self):
+        puckepy.confsampling.Sevenring(amplitude_interval=3, phase_interval=6, max_amplitude=-1.)
+
+    def test_peptide_ranges(self):
+        # the legacy grid samples both 0 and 360
+        self.assertEqual((self.c2.x[0], self.c2.x[-1]), (0., 360.))
+
+        peptide = puckepy.confsampling.Peptide(36, psi_interval=18, periodic=True, convention="signed")
+        self.assertEqual(len(peptide.phi), 36 * 18)
+        self.assertEqual((min(peptide.phi), max(peptide.phi)), (-180., 170.))
+        self.assertEqual(sorted(set(peptide.psi))[:2], [-180., -160.])
+
+        axes = puckepy.confsampling.PeptideAxes(3, phi_range=(-90., 90.), convention="positive")
+        self.assertEqual(sorted(set(axes.x)), [0., 90., 270.])
+
+        # without periodic=True, -180 and 180 are still sampled once
+        peptide = puckepy.confsampling.Peptide(36, psi_interval=1, convention="signed")
+        self.assertEqual(len(set(peptide.phi)), 36)
+        self.assertEqual(peptide.phi, sorted(peptide.phi))
+
+    @unittest.expectedFailure
+    def test_decreasing_peptide_range(self):
+        puckepy.confsampling.Peptide(10, phi_range=(180., -180.))
+
+    def test_ramachandran(self):
+        rama = puckepy.confsampling.Ramachandran()
+        self.assertEqual(rama.classify(-60., -45.), "alpha")
+        self.assertEqual(rama.classify(300., 315.), "alpha") # the [0, 360] range of the Peptide grid
+        self.assertEqual(rama.classify(60., 45., "glycine"), "alpha_L")
+
+        labels = rama.classify_peptide(self.c1)
+        self.assertEqual(len(labels), len(self.c1.phi))
+        self.assertIn("disallowed", labels)
+        # psi = 180 of the grid wraps onto -180, still within the polyproline II region
+        self.assertEqual(labels[list(zip(self.c1.phi, self.c1.psi)).index((300., 180.))], "ppii")
+
+        protein = puckepy.formalism.Protein(puckepy.formalism.Pdb(filename="./peptide_cispro.pdb").parse())
+        self.assertEqual(rama.classify_protein(protein), [None, "alpha", "ppii", "alpha_L", None])
+
+        rama.set_region("general", "alpha", [])
+        self.assertEqual(rama.classify(-60., -45.), "disallowed")
+
+    @unittest.expectedFailure
+    def test_unknown_residue_type(self):
+        puckepy.confsampling.Ramachandran().classify(-60., -45., "d-amino acid")
+
 # Test Geometry module
 class Geometry(unittest.TestCase):
 
@@ -199,5 +679,175 @@ class Geometry(unittest.TestCase):
     def test_geometry(self):
         self.assertEqual('foo'.lower(), 'foo')
 
+    def test_superposition(self):
+        # the ring rebuilt from its own puckering coordinates superposes closely onto the original ring
+        pdb = puckepy.formalism.Pdb(filename="./fivering_adenosine.pdb").parse()
+        ring = ["O4'", "C1'", "C2'", "C3'", "C4'"]
+        original = [pdb.coordinates[pdb.atomnames.index(name)] for name in ring]
+        amplitude, phase_angle = puckepy.formalism.CP5().from_atomnames(pdb=pdb, query_names=ring)
+        rebuilt = puckepy.formalism.CP5(amplitude, phase_angle).invert(elements=["O", "C", "C", "C", "C"])
+
+        fitted = puckepy.geometry.rmsd(rebuilt, original)
+        self.assertLess(fitted, 0.1)
+        self.assertGreater(puckepy.geometry.rmsd(rebuilt, original, fit=False), fitted)
+
+        superposition = puckepy.geometry.Superposition.from_atomnames(pdb, pdb, ring, mass_weighted=True)
+        self.assertAlmostEqual(superposition.rmsd, 0.)
+        self.assertEqual(len(superposition.apply(pdb.coordinates)), len(pdb.coordinates))
+
+        matrix = puckepy.geometry.rmsd_matrix([original, rebuilt, original])
+        self.assertAlmostEqual(matrix[0][1], fitted)
+        self.assertAlmostEqual(matrix[0][2], 0.)
+
+    @unittest.expectedFailure
+    def test_superposition_of_missing_atom(self):
+        pdb = puckepy.formalism.Pdb(filename="./fivering_adenosine.pdb").parse()
+        puckepy.geometry.Superposition.from_atomnames(pdb, pdb, ["O4'", "C9'"])
+
+# Test Analysis module
+class Analysis(unittest.TestCase):
+
+    def test_sphere_pes(self):
+        # the height above the equator, lowest at the south pole
+        axes = puckepy.confsampling.SixringAxes(amount=300)
+        pes = puckepy.analysis.SpherePES(axes.theta, axes.phi, [math.cos(t) for t in axes.theta])
+        self.assertAlmostEqual(pes.energy(1., 2.), math.cos(1.), places=2)
+        self.assertEqual(len(pes.evaluate([0.5, 1.5], [0., 3.])), 2)
+
+        minima = pes.minima()
+        self.assertEqual(len(minima), 1)
+        self.assertAlmostEqual(minima[0][2], -1., places=2)
+
+    def test_torus_pes(self):
+        axes = puckepy.confsampling.PeptideAxes(interval=24, convention="signed")
+        energies = [math.cos(math.radians(x)) + math.cos(math.radians(y)) for x, y in zip(axes.x, axes.y)]
+        pes = puckepy.analysis.TorusPES(axes.x, axes.y, energies, order=2)
+
+        self.assertAlmostEqual(pes.gradient(90., 0.)[0], -math.radians(1.))
+        self.assertEqual(len(pes.minima()), 1)
+        self.assertEqual(sorted((round(x), round(y)) for x, y, _ in pes.saddle_points()), [(-180, 0), (0, -180)])
+
+    @unittest.expectedFailure
+    def test_too_few_points_for_order(self):
+        puckepy.analysis.TorusPES([0., 90., 180.], [0., 90., 180.], [0., 1., 2.], order=4)
+
+    def test_sphere_fes(self):
+        axes = puckepy.confsampling.SixringAxes(amount=400)
+        fes = puckepy.analysis.SphereFES([0.2, 0.2, 2.5], [1., 1., 4.], amount=400)
+        # the bins are centered on the grid of the SixringAxes of the same amount
+        self.assertEqual(len(fes.theta), len(axes.theta))
+        self.assertAlmostEqual(sum(fes.population), 1.)
+        self.assertEqual(min(fes.free_energy), 0.)
+
+    def test_plane_fes(self):
+        # 181 wraps onto -179, the same bin
+        fes = puckepy.analysis.PlaneFES([-179., 181., 10.], [0., 0., 0.], bins=36, unit="kcal/mol", temperature=300.)
+        self.assertEqual(len(fes.x), 36 * 36)
+        self.assertAlmostEqual(sorted(fes.free_energy)[1], 1.987204259e-3 * 300. * math.log(2.))
+
+        smooth = puckepy.analysis.PlaneFES([-179., 179., 10.], [0., 0., 0.], bandwidth=10., weights=[1., 1., 0.])
+        self.assertTrue(all(g < float("inf") for g in smooth.free_energy))
+
+    @unittest.expectedFailure
+    def test_unknown_unit(self):
+        puckepy.analysis.PlaneFES([0.], [0.], unit="eV")
+
+    @unittest.expectedFailure
+    def test_nan_frame_fes(self):
+        puckepy.analysis.SphereFES([float("nan")], [0.])
+
+    def test_clustering(self):
+        # the phase angles around 0/360 belong together
+        puckers = [(0.4, 355.), (0.4, 5.), (0.38, 2.), (0.4, 180.), (0.41, 175.)]
+        for clusters in [puckepy.analysis.kmedoids(puckers, k=2, metric="polar"),
+                         puckepy.analysis.dbscan(puckers, epsilon=0.1, min_points=2, metric="polar"),
+                         puckepy.analysis.hierarchical(puckers, metric="polar", n_clusters=2)] :
+            self.assertEqual(clusters.labels, [0, 0, 0, 1, 1])
+            self.assertEqual(clusters.sizes, [3, 2])
+
+        clusters = puckepy.analysis.hierarchical([(-179., -60.), (179., -60.), (60., 60.)], metric="torus", threshold=10.)
+        self.assertEqual(clusters.labels, [0, 0, 1])
+
+    @unittest.expectedFailure
+    def test_points_do_not_match_metric(self):
+        puckepy.analysis.kmedoids([(0.4, 355.), (0.4, 5.)], k=1, metric="sphere")
+
+    def test_boltzmann(self):
+        # C3'-endo and C2'-endo, RT ln(3) apart at 300 K
+        rt = 8.314462618e-3 * 300.
+        ensemble = puckepy.analysis.boltzmann([(38., 18.), (40., 162.)], [0., rt * math.log(3.)], "AS", temperature=300.)
+        self.assertAlmostEqual(ensemble.populations[0], 0.75)
+        self.assertAlmostEqual(ensemble.amplitude, 38.5)
+        self.assertAlmostEqual(dict(ensemble.sectors)["North"], 0.75)
+        self.assertAlmostEqual(sum(p for _, p in ensemble.families), 1.)
+        self.assertIsNone(ensemble.theta)
+
+        ensemble = puckepy.analysis.boltzmann([(0.6, 0., 0.), (0.6, 180., 0.)], [0., 0.], "CP6")
+        self.assertAlmostEqual(ensemble.theta, 0.)
+
+    @unittest.expectedFailure
+    def test_boltzmann_without_energies(self):
+        puckepy.analysis.boltzmann([(38., 18.), (40., 162.)], [0.], "AS")
+
+    @unittest.expectedFailure
+    def test_boltzmann_nan_energy(self):
+        puckepy.analysis.boltzmann([(0.4, 1.)], [float("nan")], "AS")
+
+# Test the conversion between AS and CP5
+class Interconversion(unittest.TestCase):
+
+    def test_exact_round_trip(self):
+        furanose = ["O", "C", "C", "C", "C"]
+        amplitude, phaseangle = puckepy.formalism.CP5(0.38, 288.).to_as(elements=furanose)
+        q2, phi2 = puckepy.formalism.AS(amplitude, phaseangle).to_cp5(elements=furanose)
+        self.assertAlmostEqual(q2, 0.38)
+        self.assertAlmostEqual(phi2, 288., places=5)
+
+        # the exact conversion measures the inverted ring
+        ring = puckepy.formalism.CP5(0.38, 288.).invert(elements=furanose)
+        self.assertEqual(puckepy.formalism.AS().from_indices(ring, [0, 1, 2, 3, 4]), (amplitude, phaseangle))
+
+    def test_approximate(self):
+        amplitude, phaseangle = puckepy.formalism.CP5(0.2, 288.).to_as(exact=False)
+        self.assertAlmostEqual(phaseangle, 18.)
+        self.assertAlmostEqual(amplitude, 2. * math.sqrt(2.) * 0.2 / 1.54)
+
+        exact = puckepy.formalism.CP5(0.2, 288.).to_as()
+        self.assertLess(abs(exact[1] - phaseangle), 0.7)
+
+    def test_planar_ring(self):
+        self.assertEqual(puckepy.formalism.AS(0., 18.).to_cp5(), (0., 288.))
+
+# Test Statistics module
+class Statistics(unittest.TestCase):
+
+    def test_circular(self):
+        self.assertAlmostEqual(puckepy.statistics.circular_mean([350., 10.]) % 360., 0.)
+        self.assertAlmostEqual(puckepy.statistics.circular_mean([-170., 170.]), -180.)
+        self.assertAlmostEqual(puckepy.statistics.circular_variance([0., 90., 180., 270.]), 1.)
+
+        fit = puckepy.statistics.VonMises.fit([340., 350., 0., 10., 20.])
+        self.assertAlmostEqual(fit.mean % 360., 0.)
+        self.assertGreater(fit.kappa, 10.)
+        self.assertAlmostEqual(puckepy.statistics.VonMises(0., 0.).pdf([90.])[0], 1. / 360.)
+
+    def test_spherical(self):
+        theta, phi = puckepy.statistics.spherical_mean([80., 100.], [350., 10.])
+        self.assertAlmostEqual(theta, 90.)
+        self.assertAlmostEqual(phi % 360., 0.)
+
+        fit = puckepy.statistics.Fisher.fit([5., 5., 5.], [0., 120., 240.])
+        self.assertLess(fit.theta, 1e-6)
+        self.assertAlmostEqual(puckepy.statistics.Fisher(0., 0., 0.).pdf([90.], [0.])[0], 1. / (4. * math.pi))
+
+    def test_degenerate_fit(self):
+        fit = puckepy.statistics.VonMises.fit([42.])
+        self.assertEqual(fit.kappa, math.inf)
+        self.assertEqual(fit.pdf([42., 43.]), [math.inf, 0.])
+
+    @unittest.expectedFailure
+    def test_negative_weights(self):
+        puckepy.statistics.circular_mean([0., 90.], weights=[1., -1.])
+
 if __name__ == '__main__':
     unittest.main()